@@ -0,0 +1,174 @@
+use crate::enums::bencode::BencodeValue;
+use std::collections::BTreeMap;
+
+macro_rules! impl_from_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for BencodeValue {
+                fn from(value: $ty) -> Self {
+                    BencodeValue::Int(value as i64)
+                }
+            }
+        )*
+    };
+}
+
+// Only types that always fit in `i64` get an infallible conversion; `u8` is
+// left out so `Vec<u8>` stays a string rather than a list
+impl_from_integer!(i8, i16, i32, i64, isize, u16, u32);
+
+impl From<bool> for BencodeValue {
+    fn from(value: bool) -> Self {
+        BencodeValue::Int(i64::from(value))
+    }
+}
+
+impl From<&str> for BencodeValue {
+    fn from(value: &str) -> Self {
+        BencodeValue::Str(value.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for BencodeValue {
+    fn from(value: String) -> Self {
+        BencodeValue::Str(value.into_bytes())
+    }
+}
+
+impl From<&[u8]> for BencodeValue {
+    fn from(value: &[u8]) -> Self {
+        BencodeValue::Str(value.to_vec())
+    }
+}
+
+impl<const N: usize> From<&[u8; N]> for BencodeValue {
+    fn from(value: &[u8; N]) -> Self {
+        BencodeValue::Str(value.to_vec())
+    }
+}
+
+impl From<Vec<u8>> for BencodeValue {
+    fn from(value: Vec<u8>) -> Self {
+        BencodeValue::Str(value)
+    }
+}
+
+impl<T: Into<BencodeValue>> From<Vec<T>> for BencodeValue {
+    fn from(value: Vec<T>) -> Self {
+        BencodeValue::List(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<BTreeMap<Vec<u8>, BencodeValue>> for BencodeValue {
+    fn from(value: BTreeMap<Vec<u8>, BencodeValue>) -> Self {
+        BencodeValue::Dict(value)
+    }
+}
+
+impl FromIterator<BencodeValue> for BencodeValue {
+    fn from_iter<I: IntoIterator<Item = BencodeValue>>(iter: I) -> Self {
+        BencodeValue::List(iter.into_iter().collect())
+    }
+}
+
+impl<K: Into<Vec<u8>>, V: Into<BencodeValue>> FromIterator<(K, V)> for BencodeValue {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        BencodeValue::Dict(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_integers() {
+        assert_eq!(BencodeValue::from(42i32), BencodeValue::Int(42));
+        assert_eq!(BencodeValue::from(-7isize), BencodeValue::Int(-7));
+        assert_eq!(BencodeValue::from(i64::MAX), BencodeValue::Int(i64::MAX));
+        assert_eq!(BencodeValue::from(true), BencodeValue::Int(1));
+    }
+
+    #[test]
+    fn test_from_strings() {
+        assert_eq!(
+            BencodeValue::from("spam"),
+            BencodeValue::Str(b"spam".to_vec())
+        );
+        assert_eq!(
+            BencodeValue::from(String::from("spam")),
+            BencodeValue::Str(b"spam".to_vec())
+        );
+        assert_eq!(
+            BencodeValue::from(vec![0xffu8, 0x00]),
+            BencodeValue::Str(vec![0xff, 0x00])
+        );
+        assert_eq!(BencodeValue::from(b"\xff"), BencodeValue::Str(vec![0xff]));
+    }
+
+    #[test]
+    fn test_from_vec_of_values() {
+        assert_eq!(
+            BencodeValue::from(vec!["a", "b"]),
+            BencodeValue::List(vec![
+                BencodeValue::Str(b"a".to_vec()),
+                BencodeValue::Str(b"b".to_vec()),
+            ])
+        );
+        assert_eq!(
+            BencodeValue::from(vec![1i32, 2]),
+            BencodeValue::List(vec![BencodeValue::Int(1), BencodeValue::Int(2)])
+        );
+    }
+
+    #[test]
+    fn test_collect_list_and_dict() {
+        let list: BencodeValue = (1..=3).map(BencodeValue::from).collect();
+        assert_eq!(
+            list,
+            BencodeValue::List(vec![
+                BencodeValue::Int(1),
+                BencodeValue::Int(2),
+                BencodeValue::Int(3),
+            ])
+        );
+
+        let dict: BencodeValue = [("b", 2), ("a", 1)].into_iter().collect();
+        let mut expected = BTreeMap::new();
+        expected.insert(b"a".to_vec(), BencodeValue::Int(1));
+        expected.insert(b"b".to_vec(), BencodeValue::Int(2));
+        assert_eq!(dict, BencodeValue::Dict(expected));
+    }
+
+    #[test]
+    fn test_hash_and_ord() {
+        use std::collections::HashSet;
+
+        let mut set = HashSet::new();
+        set.insert(BencodeValue::from("x"));
+        set.insert(BencodeValue::from("x"));
+        set.insert(BencodeValue::from(1i32));
+        assert_eq!(set.len(), 2);
+
+        let mut values = vec![
+            BencodeValue::from("b"),
+            BencodeValue::from(2i32),
+            BencodeValue::from("a"),
+            BencodeValue::from(1i32),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                BencodeValue::Int(1),
+                BencodeValue::Int(2),
+                BencodeValue::Str(b"a".to_vec()),
+                BencodeValue::Str(b"b".to_vec()),
+            ]
+        );
+    }
+}
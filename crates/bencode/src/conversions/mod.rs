@@ -0,0 +1,2 @@
+pub mod from;
+pub mod try_from;
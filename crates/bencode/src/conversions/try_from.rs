@@ -0,0 +1,121 @@
+use crate::enums::bencode::BencodeValue;
+use std::collections::BTreeMap;
+
+macro_rules! impl_try_from_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<BencodeValue> for $ty {
+                type Error = &'static str;
+
+                fn try_from(value: BencodeValue) -> Result<Self, Self::Error> {
+                    match value {
+                        BencodeValue::Int(n) => {
+                            <$ty>::try_from(n).map_err(|_| "Integer out of range")
+                        }
+                        _ => Err("Not an integer"),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_integer!(i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+
+impl TryFrom<BencodeValue> for Vec<u8> {
+    type Error = &'static str;
+
+    fn try_from(value: BencodeValue) -> Result<Self, Self::Error> {
+        match value {
+            BencodeValue::Str(bytes) => Ok(bytes),
+            _ => Err("Not a string"),
+        }
+    }
+}
+
+impl TryFrom<BencodeValue> for String {
+    type Error = &'static str;
+
+    fn try_from(value: BencodeValue) -> Result<Self, Self::Error> {
+        let bytes = Vec::<u8>::try_from(value)?;
+        String::from_utf8(bytes).map_err(|_| "Invalid UTF-8")
+    }
+}
+
+impl TryFrom<BencodeValue> for Vec<BencodeValue> {
+    type Error = &'static str;
+
+    fn try_from(value: BencodeValue) -> Result<Self, Self::Error> {
+        match value {
+            BencodeValue::List(list) => Ok(list),
+            _ => Err("Not a list"),
+        }
+    }
+}
+
+impl TryFrom<BencodeValue> for BTreeMap<Vec<u8>, BencodeValue> {
+    type Error = &'static str;
+
+    fn try_from(value: BencodeValue) -> Result<Self, Self::Error> {
+        match value {
+            BencodeValue::Dict(dict) => Ok(dict),
+            _ => Err("Not a dictionary"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_from_integers() {
+        assert_eq!(i64::try_from(BencodeValue::Int(-5)), Ok(-5));
+        assert_eq!(u64::try_from(BencodeValue::Int(5)), Ok(5));
+        assert_eq!(
+            u64::try_from(BencodeValue::Int(-5)),
+            Err("Integer out of range")
+        );
+        assert_eq!(
+            u8::try_from(BencodeValue::Int(256)),
+            Err("Integer out of range")
+        );
+        assert_eq!(
+            i64::try_from(BencodeValue::Str(b"5".to_vec())),
+            Err("Not an integer")
+        );
+    }
+
+    #[test]
+    fn test_try_from_strings() {
+        assert_eq!(
+            String::try_from(BencodeValue::Str(b"spam".to_vec())),
+            Ok("spam".to_string())
+        );
+        assert_eq!(
+            String::try_from(BencodeValue::Str(vec![0xff])),
+            Err("Invalid UTF-8")
+        );
+        assert_eq!(
+            Vec::<u8>::try_from(BencodeValue::Str(vec![0xff])),
+            Ok(vec![0xff])
+        );
+        assert_eq!(
+            Vec::<u8>::try_from(BencodeValue::Int(1)),
+            Err("Not a string")
+        );
+    }
+
+    #[test]
+    fn test_try_from_containers() {
+        assert_eq!(
+            Vec::<BencodeValue>::try_from(BencodeValue::List(vec![BencodeValue::Int(1)])),
+            Ok(vec![BencodeValue::Int(1)])
+        );
+        assert!(BTreeMap::try_from(BencodeValue::Dict(BTreeMap::new())).is_ok());
+        assert_eq!(
+            BTreeMap::try_from(BencodeValue::Int(1)),
+            Err("Not a dictionary")
+        );
+    }
+}
@@ -1,4 +1,4 @@
-pub fn decode_integer(data: &[u8]) -> Result<(isize, &[u8]), &'static str> {
+pub fn decode_integer(data: &[u8]) -> Result<(i64, &[u8]), &'static str> {
     if data.is_empty() || data[0] != b'i' {
         return Err("Not an integer");
     }
@@ -21,13 +21,13 @@ pub fn decode_integer(data: &[u8]) -> Result<(isize, &[u8]), &'static str> {
         return Err("Leading zeros are not allowed");
     }
     // Negative zero check
-    if int_bytes[0] == b'-' && int_bytes[1] == b'0' {
+    if int_bytes.starts_with(b"-0") {
         return Err("Negative leading zeros are not allowed");
     }
 
     // Convert to string and parse
     let int_str = std::str::from_utf8(int_bytes).map_err(|_| "Invalid UTF-8")?;
-    let value = int_str.parse::<isize>().map_err(|_| "Invalid integer")?;
+    let value = int_str.parse::<i64>().map_err(|_| "Invalid integer")?;
 
     // Return the value and remaining slice
     Ok((value, &data[end_pos + 1..]))
@@ -74,6 +74,9 @@ mod tests {
         // Leading zeros
         let data = b"i042e";
         assert!(decode_integer(data).is_err());
+
+        let data = b"i-e";
+        assert!(decode_integer(data).is_err());
     }
 
     #[test]
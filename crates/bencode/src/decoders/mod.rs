@@ -1,4 +1,5 @@
 pub mod dictionary;
 pub mod integer;
 pub mod list;
+pub mod raw;
 pub mod string;
@@ -0,0 +1,189 @@
+use crate::decoders::{integer::decode_integer, string::decode_string_slice};
+use std::collections::BTreeMap;
+
+type RawDictionaryOutput<'a> = BTreeMap<&'a [u8], &'a [u8]>;
+type RawEntries<'a> = Vec<(&'a [u8], &'a [u8])>;
+
+/// Splits off the first complete bencoded value, returning its exact input bytes
+pub fn decode_raw(data: &[u8]) -> Result<(&[u8], &[u8]), &'static str> {
+    let rest = skip_value(data)?;
+    let consumed = data.len() - rest.len();
+    Ok((&data[..consumed], rest))
+}
+
+/// Checks the first bencoded value as `decode_bencode` would and returns what
+/// follows it, without building the value
+fn skip_value(data: &[u8]) -> Result<&[u8], &'static str> {
+    match data.first() {
+        None => Err("Empty input"),
+        Some(b) if b.is_ascii_digit() => Ok(decode_string_slice(data)?.1),
+        Some(b'i') => Ok(decode_integer(data)?.1),
+        Some(b'l') => {
+            let mut rest = &data[1..]; // skip 'l'
+            while !rest.is_empty() && rest[0] != b'e' {
+                rest = skip_value(rest)?;
+            }
+            rest.get(1..).ok_or("Missing 'e' to terminate list")
+        }
+        Some(b'd') => {
+            let mut rest = &data[1..]; // skip 'd'
+            while !rest.is_empty() && rest[0] != b'e' {
+                if !rest[0].is_ascii_digit() {
+                    return Err("Dictionary key must be a string");
+                }
+                rest = decode_string_slice(rest)?.1;
+                rest = skip_value(rest)?;
+            }
+            rest.get(1..).ok_or("Missing 'e' to terminate dictionary")
+        }
+        Some(_) => Err("Unknown type prefix"),
+    }
+}
+
+/// Decodes a dictionary whose values are left as their exact input bytes
+pub fn decode_raw_dictionary(
+    data: &[u8],
+) -> Result<(RawDictionaryOutput<'_>, &[u8]), &'static str> {
+    let (entries, rest) = decode_raw_entries(data)?;
+    Ok((entries.into_iter().collect(), rest))
+}
+
+/// Decodes a dictionary into its key-value pairs in input order, keeping duplicate
+/// keys; values are left as their exact input bytes
+pub fn decode_raw_entries(data: &[u8]) -> Result<(RawEntries<'_>, &[u8]), &'static str> {
+    if data.is_empty() || data[0] != b'd' {
+        return Err("Not a dictionary");
+    }
+
+    let mut rest = &data[1..]; // skip 'd'
+    let mut entries = Vec::new();
+
+    while !rest.is_empty() && rest[0] != b'e' {
+        if !rest[0].is_ascii_digit() {
+            return Err("Dictionary key must be a string");
+        }
+        let (key, new_rest) = decode_string_slice(rest)?;
+        rest = new_rest;
+
+        let (value, new_rest) = decode_raw(rest)?;
+        rest = new_rest;
+
+        entries.push((key, value));
+    }
+
+    if rest.is_empty() {
+        return Err("Missing 'e' to terminate dictionary");
+    }
+
+    Ok((entries, &rest[1..]))
+}
+
+/// Decodes a list whose items are left as their exact input bytes
+pub fn decode_raw_list(data: &[u8]) -> Result<(Vec<&[u8]>, &[u8]), &'static str> {
+    if data.is_empty() || data[0] != b'l' {
+        return Err("Not a list");
+    }
+
+    let mut rest = &data[1..]; // skip 'l'
+    let mut items = Vec::new();
+
+    while !rest.is_empty() && rest[0] != b'e' {
+        let (item, new_rest) = decode_raw(rest)?;
+        rest = new_rest;
+        items.push(item);
+    }
+
+    if rest.is_empty() {
+        return Err("Missing 'e' to terminate list");
+    }
+
+    Ok((items, &rest[1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_raw() {
+        let data = b"li1e3:fooei42e";
+        let (raw, rest) = decode_raw(data).unwrap();
+        assert_eq!(raw, b"li1e3:fooe");
+        assert_eq!(rest, b"i42e");
+
+        assert!(decode_raw(b"l").is_err());
+        for invalid in [
+            &b""[..],
+            b"x",
+            b"i-e",
+            b"i01e",
+            b"d1:ae",
+            b"di1ei2ee",
+            b"l5:abce",
+        ] {
+            assert!(decode_raw(invalid).is_err(), "{invalid:?}");
+        }
+        let (raw, rest) = decode_raw(b"d1:ald1:bi-3eee0:0:e3:end").unwrap();
+        assert_eq!(raw, b"d1:ald1:bi-3eee0:0:e");
+        assert_eq!(rest, b"3:end");
+    }
+
+    #[test]
+    fn test_decode_raw_dictionary() {
+        let data = b"d8:announce3:url4:infod6:lengthi1eee";
+        let (dict, rest) = decode_raw_dictionary(data).unwrap();
+        assert_eq!(dict.get(b"announce".as_slice()), Some(&b"3:url".as_slice()));
+        assert_eq!(
+            dict.get(b"info".as_slice()),
+            Some(&b"d6:lengthi1ee".as_slice())
+        );
+        assert_eq!(rest, b"");
+    }
+
+    #[test]
+    fn test_raw_dictionary_keeps_non_canonical_bytes() {
+        // Unsorted keys are preserved verbatim in the value slice
+        let data = b"d4:infod1:bi1e1:ai2eee";
+        let (dict, _) = decode_raw_dictionary(data).unwrap();
+        assert_eq!(
+            dict.get(b"info".as_slice()),
+            Some(&b"d1:bi1e1:ai2ee".as_slice())
+        );
+    }
+
+    #[test]
+    fn test_raw_entries_keep_order_and_duplicates() {
+        let data = b"d1:bi1e1:ai2e1:bi3ee";
+        let (entries, rest) = decode_raw_entries(data).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                (b"b".as_slice(), b"i1e".as_slice()),
+                (b"a".as_slice(), b"i2e".as_slice()),
+                (b"b".as_slice(), b"i3e".as_slice()),
+            ]
+        );
+        assert_eq!(rest, b"");
+
+        // The dictionary keeps the last value, like the regular decoder
+        let (dict, _) = decode_raw_dictionary(data).unwrap();
+        assert_eq!(dict.get(b"b".as_slice()), Some(&b"i3e".as_slice()));
+    }
+
+    #[test]
+    fn test_decode_raw_list() {
+        let (items, rest) = decode_raw_list(b"l03:abcd1:ai1eeei7e").unwrap();
+        assert_eq!(items, vec![b"03:abc".as_slice(), b"d1:ai1ee".as_slice()]);
+        assert_eq!(rest, b"i7e");
+
+        assert!(decode_raw_list(b"d1:ai1ee").is_err());
+        assert!(decode_raw_list(b"li1e").is_err());
+    }
+
+    #[test]
+    fn test_raw_dictionary_errors() {
+        assert!(decode_raw_dictionary(b"le").is_err());
+        assert!(decode_raw_dictionary(b"di1ei2ee").is_err());
+        assert!(decode_raw_dictionary(b"d1:ai1e").is_err());
+    }
+}
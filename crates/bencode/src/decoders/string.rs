@@ -1,4 +1,10 @@
 pub fn decode_string(data: &[u8]) -> Result<(Vec<u8>, &[u8]), &'static str> {
+    let (bytes, rest) = decode_string_slice(data)?;
+    Ok((bytes.to_vec(), rest))
+}
+
+/// Same as `decode_string`, borrowing the bytes from the input
+pub fn decode_string_slice(data: &[u8]) -> Result<(&[u8], &[u8]), &'static str> {
     // Find the colon separating length from content
     let colon_pos = data.iter().position(|&b| b == b':').ok_or("Missing ':'")?;
 
@@ -11,19 +17,19 @@ pub fn decode_string(data: &[u8]) -> Result<(Vec<u8>, &[u8]), &'static str> {
         if !b.is_ascii_digit() {
             return Err("Invalid digit in length");
         }
-        len = len * 10 + (b - b'0') as usize;
+        len = len
+            .checked_mul(10)
+            .and_then(|len| len.checked_add((b - b'0') as usize))
+            .ok_or("Not enough bytes for string")?;
     }
 
     // Check if enough bytes remain
     let start = colon_pos + 1;
-    if data.len() < start + len {
+    if data.len() - start < len {
         return Err("Not enough bytes for string");
     }
 
-    let string_bytes = data[start..start + len].to_vec();
-    let rest = &data[start + len..];
-
-    Ok((string_bytes, rest))
+    Ok((&data[start..start + len], &data[start + len..]))
 }
 #[cfg(test)]
 mod tests {
@@ -70,5 +76,8 @@ mod tests {
 
         let data = b"10:short"; // not enough bytes
         assert!(decode_string(data).is_err());
+
+        let data = b"99999999999999999999999:x"; // length overflows
+        assert!(decode_string(data).is_err());
     }
 }
@@ -1,15 +1,65 @@
 use crate::{
     encoders::{
-        dictionary::encode_dict, integer::encode_integer, list::encode_list, string::encode_string,
+        dictionary::{dict_len, write_dict},
+        integer::{integer_len, write_integer},
+        list::{list_len, write_list},
+        string::{string_len, write_string},
     },
     enums::bencode::BencodeValue,
 };
 
 pub fn encode_bencode(value: BencodeValue) -> Result<Vec<u8>, &'static str> {
+    encode(&value)
+}
+
+pub fn encode(value: &BencodeValue) -> Result<Vec<u8>, &'static str> {
+    let mut encoded = Vec::with_capacity(encoded_len(value));
+    write_bencode(value, &mut encoded);
+    Ok(encoded)
+}
+
+pub fn write_bencode(value: &BencodeValue, out: &mut Vec<u8>) {
+    match value {
+        BencodeValue::Int(n) => write_integer(*n, out),
+        BencodeValue::Str(s) => write_string(s, out),
+        BencodeValue::List(l) => write_list(l, out),
+        BencodeValue::Dict(d) => write_dict(d, out),
+    }
+}
+
+pub fn encoded_len(value: &BencodeValue) -> usize {
     match value {
-        BencodeValue::Int(n) => encode_integer(n),
-        BencodeValue::Str(s) => encode_string(s),
-        BencodeValue::List(l) => encode_list(l),
-        BencodeValue::Dict(d) => encode_dict(d),
+        BencodeValue::Int(n) => integer_len(*n),
+        BencodeValue::Str(s) => string_len(s),
+        BencodeValue::List(l) => list_len(l),
+        BencodeValue::Dict(d) => dict_len(d),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bencode;
+
+    #[test]
+    fn test_encode_borrows() {
+        let value = bencode!({
+            "announce": "http://tracker",
+            "info": { "length": 12, "name": "a.txt" },
+        });
+        let encoded = encode(&value).unwrap();
+        assert_eq!(
+            encoded,
+            b"d8:announce14:http://tracker4:infod6:lengthi12e4:name5:a.txtee".to_vec()
+        );
+        assert_eq!(encoded, encode_bencode(value).unwrap());
+    }
+
+    #[test]
+    fn test_encoded_len_matches_output() {
+        let value = bencode!([0, -1, "", [], {}, { "k": [1, "v"] }]);
+        let encoded = encode(&value).unwrap();
+        assert_eq!(encoded_len(&value), encoded.len());
+        assert_eq!(encoded.capacity(), encoded.len());
     }
 }
@@ -1,22 +1,34 @@
-use super::string::encode_string;
-use crate::{dispatcher::bencode::encode_bencode, enums::bencode::BencodeValue};
+use super::string::{string_len, write_string};
+use crate::{
+    dispatcher::bencode::{encoded_len, write_bencode},
+    enums::bencode::BencodeValue,
+};
 
 use std::collections::BTreeMap;
 
 pub fn encode_dict(data: BTreeMap<Vec<u8>, BencodeValue>) -> Result<Vec<u8>, &'static str> {
-    let mut encoded = Vec::new();
-    encoded.push(b'd');
+    let mut encoded = Vec::with_capacity(dict_len(&data));
+    write_dict(&data, &mut encoded);
+    Ok(encoded)
+}
 
-    for (key, value) in data.into_iter() {
-        let encoded_key = encode_string(key)?;
-        encoded.extend(encoded_key);
+pub fn write_dict(data: &BTreeMap<Vec<u8>, BencodeValue>, out: &mut Vec<u8>) {
+    out.push(b'd');
 
-        let encoded_value = encode_bencode(value)?;
-        encoded.extend(encoded_value);
+    // BTreeMap iterates in sorted key order, as bencode requires
+    for (key, value) in data {
+        write_string(key, out);
+        write_bencode(value, out);
     }
 
-    encoded.push(b'e');
-    Ok(encoded)
+    out.push(b'e');
+}
+
+pub fn dict_len(data: &BTreeMap<Vec<u8>, BencodeValue>) -> usize {
+    data.iter()
+        .map(|(key, value)| string_len(key) + encoded_len(value))
+        .sum::<usize>()
+        + 2
 }
 
 #[cfg(test)]
@@ -79,4 +91,13 @@ mod tests {
         let encoded = encode_dict(dict).unwrap();
         assert_eq!(encoded, b"d5:innerd5:hello5:worldee".to_vec());
     }
+
+    #[test]
+    fn test_dict_len() {
+        let mut dict = BTreeMap::new();
+        dict.insert(b"meaning".to_vec(), BencodeValue::Int(42));
+        dict.insert(b"wiki".to_vec(), BencodeValue::Str(b"bencode".to_vec()));
+        assert_eq!(dict_len(&dict), encode_dict(dict.clone()).unwrap().len());
+        assert_eq!(dict_len(&BTreeMap::new()), 2);
+    }
 }
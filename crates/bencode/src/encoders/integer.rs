@@ -1,6 +1,22 @@
-pub fn encode_integer(data: isize) -> Result<Vec<u8>, &'static str> {
-    let encoded = format!("i{}e", data);
-    Ok(encoded.into_bytes())
+pub fn encode_integer(data: i64) -> Result<Vec<u8>, &'static str> {
+    let mut encoded = Vec::with_capacity(integer_len(data));
+    write_integer(data, &mut encoded);
+    Ok(encoded)
+}
+
+pub fn write_integer(data: i64, out: &mut Vec<u8>) {
+    out.push(b'i');
+    out.extend_from_slice(data.to_string().as_bytes());
+    out.push(b'e');
+}
+
+pub fn integer_len(data: i64) -> usize {
+    // Number of decimal digits, plus the sign and the 'i'/'e' markers
+    let digits = data
+        .unsigned_abs()
+        .checked_ilog10()
+        .map_or(1, |d| d as usize + 1);
+    digits + usize::from(data < 0) + 2
 }
 
 #[cfg(test)]
@@ -21,4 +37,11 @@ mod tests {
         // Test Negative Number
         assert_eq!(encode_integer(-42).unwrap(), b"i-42e".to_vec());
     }
+
+    #[test]
+    fn test_integer_len() {
+        for n in [0, 9, 10, -1, -10, 123456789, i64::MAX, i64::MIN] {
+            assert_eq!(integer_len(n), encode_integer(n).unwrap().len(), "{n}");
+        }
+    }
 }
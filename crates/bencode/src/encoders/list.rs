@@ -1,16 +1,26 @@
-use crate::{dispatcher::bencode::encode_bencode, enums::bencode::BencodeValue};
+use crate::{
+    dispatcher::bencode::{encoded_len, write_bencode},
+    enums::bencode::BencodeValue,
+};
 
 pub fn encode_list(data: Vec<BencodeValue>) -> Result<Vec<u8>, &'static str> {
-    let mut encoded = Vec::new();
-    encoded.push(b'l');
+    let mut encoded = Vec::with_capacity(list_len(&data));
+    write_list(&data, &mut encoded);
+    Ok(encoded)
+}
+
+pub fn write_list(data: &[BencodeValue], out: &mut Vec<u8>) {
+    out.push(b'l');
 
     for item in data {
-        let item_bytes = encode_bencode(item)?;
-        encoded.extend(item_bytes);
+        write_bencode(item, out);
     }
 
-    encoded.push(b'e');
-    Ok(encoded)
+    out.push(b'e');
+}
+
+pub fn list_len(data: &[BencodeValue]) -> usize {
+    data.iter().map(encoded_len).sum::<usize>() + 2
 }
 
 #[cfg(test)]
@@ -60,4 +70,14 @@ mod tests {
         let encoded = encode_list(list).unwrap();
         assert_eq!(encoded, b"l7:bencodei-20ee".to_vec());
     }
+
+    #[test]
+    fn test_list_len() {
+        let list = vec![
+            BencodeValue::Int(-1),
+            BencodeValue::List(vec![BencodeValue::Str(b"nested".to_vec())]),
+        ];
+        assert_eq!(list_len(&list), encode_list(list.clone()).unwrap().len());
+        assert_eq!(list_len(&[]), 2);
+    }
 }
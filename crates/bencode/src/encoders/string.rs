@@ -1,18 +1,24 @@
 pub fn encode_string(data: Vec<u8>) -> Result<Vec<u8>, &'static str> {
-    let len = data.len();
-    let mut result = Vec::new();
+    let mut result = Vec::with_capacity(string_len(&data));
+    write_string(&data, &mut result);
+    Ok(result)
+}
 
+pub fn write_string(data: &[u8], out: &mut Vec<u8>) {
     // Convert length to ASCII bytes
-    let len_str = len.to_string();
-    result.extend_from_slice(len_str.as_bytes());
+    let len_str = data.len().to_string();
+    out.extend_from_slice(len_str.as_bytes());
 
     // Add colon separator as raw byte
-    result.push(b':');
+    out.push(b':');
 
     // Append raw data bytes directly
-    result.extend_from_slice(&data);
+    out.extend_from_slice(data);
+}
 
-    Ok(result)
+pub fn string_len(data: &[u8]) -> usize {
+    let len_digits = data.len().checked_ilog10().map_or(1, |d| d as usize + 1);
+    len_digits + 1 + data.len()
 }
 
 #[cfg(test)]
@@ -44,4 +50,15 @@ mod tests {
         expected.extend_from_slice(&input);
         assert_eq!(encode_string(input).unwrap(), expected);
     }
+
+    #[test]
+    fn test_string_len() {
+        for len in [0, 1, 9, 10, 99, 100, 1000] {
+            let data = vec![b'x'; len];
+            assert_eq!(
+                string_len(&data),
+                encode_string(data.clone()).unwrap().len()
+            );
+        }
+    }
 }
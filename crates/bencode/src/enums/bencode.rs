@@ -1,8 +1,8 @@
 use std::collections::BTreeMap;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub enum BencodeValue {
-    Int(isize),
+    Int(i64),
     Str(Vec<u8>),
     List(Vec<BencodeValue>),
     Dict(BTreeMap<Vec<u8>, BencodeValue>),
@@ -1,4 +1,6 @@
+pub mod conversions;
 pub mod decoders;
 pub mod dispatcher;
 pub mod encoders;
 pub mod enums;
+pub mod macros;
@@ -0,0 +1,132 @@
+/// Builds a [`BencodeValue`](crate::enums::bencode::BencodeValue) from a JSON-like literal.
+///
+/// ```
+/// use bencode::bencode;
+///
+/// let torrent = bencode!({
+///     "announce": "http://tracker.example/announce",
+///     "info": {
+///         "name": "file.txt",
+///         "length": 1024,
+///         "pieces": vec![0u8; 20],
+///     },
+///     "url-list": ["http://mirror.example/"],
+/// });
+/// ```
+///
+/// Keys are anything convertible into `Vec<u8>`, values anything implementing
+/// `Into<BencodeValue>`. Expressions made of more than one token (e.g. `-1`,
+/// `x.len()`) have to be wrapped in parentheses when used as dictionary keys.
+#[macro_export]
+macro_rules! bencode {
+    (@list [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@list [$($elems:expr,)*] {$($map:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($elems,)* $crate::bencode!({$($map)*}),] $($($rest)*)?)
+    };
+    (@list [$($elems:expr,)*] [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($elems,)* $crate::bencode!([$($array)*]),] $($($rest)*)?)
+    };
+    (@list [$($elems:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bencode!(
+            @list [$($elems,)* $crate::enums::bencode::BencodeValue::from($value),] $($($rest)*)?
+        )
+    };
+
+    (@dict $dict:ident) => {};
+    (@dict $dict:ident $key:tt : {$($map:tt)*} $(, $($rest:tt)*)?) => {
+        $dict.insert(::std::vec::Vec::<u8>::from($key), $crate::bencode!({$($map)*}));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+    (@dict $dict:ident $key:tt : [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $dict.insert(::std::vec::Vec::<u8>::from($key), $crate::bencode!([$($array)*]));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+    (@dict $dict:ident $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $dict.insert(
+            ::std::vec::Vec::<u8>::from($key),
+            $crate::enums::bencode::BencodeValue::from($value),
+        );
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+
+    ({ $($map:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut dict = ::std::collections::BTreeMap::<
+            ::std::vec::Vec<u8>,
+            $crate::enums::bencode::BencodeValue,
+        >::new();
+        $crate::bencode!(@dict dict $($map)*);
+        $crate::enums::bencode::BencodeValue::Dict(dict)
+    }};
+    ([ $($array:tt)* ]) => {
+        $crate::enums::bencode::BencodeValue::List($crate::bencode!(@list [] $($array)*))
+    };
+    ($value:expr) => {
+        $crate::enums::bencode::BencodeValue::from($value)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::bencode::BencodeValue;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(bencode!(42), BencodeValue::Int(42));
+        assert_eq!(bencode!(-42), BencodeValue::Int(-42));
+        assert_eq!(bencode!("spam"), BencodeValue::Str(b"spam".to_vec()));
+        assert_eq!(bencode!(b"\xff\x00"), BencodeValue::Str(vec![0xff, 0x00]));
+    }
+
+    #[test]
+    fn test_empty_containers() {
+        assert_eq!(bencode!([]), BencodeValue::List(vec![]));
+        assert_eq!(bencode!({}), BencodeValue::Dict(BTreeMap::new()));
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(
+            bencode!([1, "two", -3, [4], {"five": 5},]),
+            BencodeValue::List(vec![
+                BencodeValue::Int(1),
+                BencodeValue::Str(b"two".to_vec()),
+                BencodeValue::Int(-3),
+                BencodeValue::List(vec![BencodeValue::Int(4)]),
+                BencodeValue::Dict(BTreeMap::from([(b"five".to_vec(), BencodeValue::Int(5))])),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_nested_dict() {
+        let name = "file.txt";
+        let value = bencode!({
+            "announce": "http://tracker",
+            "info": {
+                "name": name,
+                "length": 2 * 512,
+                "files": [],
+            },
+            b"raw": b"\xff",
+        });
+
+        let mut info = BTreeMap::new();
+        info.insert(b"name".to_vec(), BencodeValue::Str(b"file.txt".to_vec()));
+        info.insert(b"length".to_vec(), BencodeValue::Int(1024));
+        info.insert(b"files".to_vec(), BencodeValue::List(vec![]));
+
+        let mut expected = BTreeMap::new();
+        expected.insert(
+            b"announce".to_vec(),
+            BencodeValue::Str(b"http://tracker".to_vec()),
+        );
+        expected.insert(b"info".to_vec(), BencodeValue::Dict(info));
+        expected.insert(b"raw".to_vec(), BencodeValue::Str(vec![0xff]));
+
+        assert_eq!(value, BencodeValue::Dict(expected));
+    }
+}
@@ -0,0 +1,913 @@
+use crate::{
+    enums::{build_error::BuildError, info_hash::InfoHash, meta_version::MetaVersion},
+    hashing::{
+        merkle::{BLOCK_SIZE, Hash, MerkleTree, piece_layer_height, piece_node},
+        parallel::{HashingRun, ProgressCallback},
+    },
+    models::{file_attributes::FileAttributes, info::MAX_PIECE_LENGTH, progress::Progress},
+    storage::piece_reader::{PieceReader, SourceFile},
+};
+use bencode::{dispatcher::bencode::encode_bencode, enums::bencode::BencodeValue};
+use sha1::{Digest, Sha1};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, atomic::AtomicBool},
+    thread,
+};
+
+const MIN_PIECE_LENGTH: u64 = BLOCK_SIZE as u64;
+const MAX_AUTO_PIECE_LENGTH: u64 = 16 * 1024 * 1024;
+// Aim for roughly this many pieces when picking the piece length automatically
+const TARGET_PIECE_COUNT: u64 = 1500;
+
+/// Creates `.torrent` files from a file or a directory on disk
+pub struct TorrentBuilder {
+    path: PathBuf,
+    piece_length: Option<u64>,
+    version: MetaVersion,
+    pad_files: bool,
+    announce: Option<String>,
+    announce_list: Vec<Vec<String>>,
+    comment: Option<String>,
+    created_by: Option<String>,
+    creation_date: Option<i64>,
+    private: bool,
+    similar: Vec<InfoHash>,
+    collections: Vec<String>,
+    web_seeds: Vec<String>,
+    http_seeds: Vec<String>,
+    threads: usize,
+    progress: Option<ProgressCallback>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// What the v2 pass produces for each file
+struct V2Hashes {
+    pieces_root: Option<Hash>,
+    /// Only kept for files larger than one piece
+    piece_layer: Vec<Hash>,
+}
+
+impl TorrentBuilder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        TorrentBuilder {
+            path: path.into(),
+            piece_length: None,
+            version: MetaVersion::V1,
+            pad_files: false,
+            announce: None,
+            announce_list: Vec::new(),
+            comment: None,
+            created_by: None,
+            creation_date: None,
+            private: false,
+            similar: Vec::new(),
+            collections: Vec::new(),
+            web_seeds: Vec::new(),
+            http_seeds: Vec::new(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            progress: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Overrides the automatically chosen piece length
+    pub fn piece_length(mut self, piece_length: u64) -> Self {
+        self.piece_length = Some(piece_length);
+        self
+    }
+
+    /// Creates a v1 (BEP 3), v2 (BEP 52) or hybrid torrent; defaults to v1
+    pub fn version(mut self, version: MetaVersion) -> Self {
+        self.version = version;
+        self
+    }
+    /// Adds BEP 47 padding files so every file starts on a piece boundary;
+    /// hybrid torrents are always padded
+    pub fn pad_files(mut self, pad_files: bool) -> Self {
+        self.pad_files = pad_files;
+        self
+    }
+
+    pub fn announce(mut self, url: impl Into<String>) -> Self {
+        self.announce = Some(url.into());
+        self
+    }
+
+    /// Appends a tier to `announce-list` (BEP 12)
+    pub fn announce_tier<I, S>(mut self, tier: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.announce_list
+            .push(tier.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn created_by(mut self, created_by: impl Into<String>) -> Self {
+        self.created_by = Some(created_by.into());
+        self
+    }
+
+    /// Seconds since the UNIX epoch
+    pub fn creation_date(mut self, creation_date: i64) -> Self {
+        self.creation_date = Some(creation_date);
+        self
+    }
+
+    pub fn private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    /// Lists a torrent likely to share files with this one (BEP 38)
+    pub fn similar(mut self, info_hash: InfoHash) -> Self {
+        self.similar.push(info_hash);
+        self
+    }
+
+    /// Adds the torrent to a named collection of related torrents (BEP 38)
+    pub fn collection(mut self, name: impl Into<String>) -> Self {
+        self.collections.push(name.into());
+        self
+    }
+
+    /// Appends a web seed to `url-list` (BEP 19)
+    pub fn web_seed(mut self, url: impl Into<String>) -> Self {
+        self.web_seeds.push(url.into());
+        self
+    }
+
+    /// Appends a BEP 17 seed to `httpseeds`
+    pub fn http_seed(mut self, url: impl Into<String>) -> Self {
+        self.http_seeds.push(url.into());
+        self
+    }
+
+    /// Number of threads used to hash pieces
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Called after every hashed piece, possibly from several threads at once
+    pub fn on_progress(mut self, callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Raising this flag makes `build` stop hashing and return `BuildError::Cancelled`
+    pub fn cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Hashes the source files and returns the encoded `.torrent`
+    pub fn build(&self) -> Result<Vec<u8>, BuildError> {
+        let (name, files, is_directory) = collect_files(&self.path)?;
+        let total_length: u64 = files.iter().map(|file| file.length).sum();
+
+        let piece_length = match self.piece_length {
+            Some(length)
+                if !(MIN_PIECE_LENGTH..=MAX_PIECE_LENGTH).contains(&length)
+                    || !length.is_power_of_two() =>
+            {
+                return Err(BuildError::InvalidPieceLength(length));
+            }
+            Some(length) => length,
+            None => auto_piece_length(total_length),
+        };
+
+        // Hybrid torrents align every file to a piece boundary so both layouts share pieces
+        let v1_files = match self.pad_files || self.version == MetaVersion::Hybrid {
+            true => pad_to_piece_boundaries(&files, piece_length),
+            false => files.clone(),
+        };
+        let v1_length: u64 = v1_files.iter().map(|file| file.length).sum();
+
+        let mut piece_count = 0;
+        let mut total_bytes = 0;
+        if self.version.has_v1() {
+            piece_count += v1_length.div_ceil(piece_length) as usize;
+            total_bytes += v1_length;
+        }
+        if self.version.has_v2() {
+            piece_count += files
+                .iter()
+                .map(|file| file.length.div_ceil(piece_length) as usize)
+                .sum::<usize>();
+            total_bytes += total_length;
+        }
+        let run = HashingRun::new(
+            self.threads,
+            &self.cancel,
+            self.progress.as_ref(),
+            piece_count,
+            total_bytes,
+        );
+
+        let mut info = BTreeMap::new();
+        info.insert(b"name".to_vec(), BencodeValue::from(name.as_str()));
+        info.insert(
+            b"piece length".to_vec(),
+            BencodeValue::Int(piece_length as i64),
+        );
+        if self.private {
+            info.insert(b"private".to_vec(), BencodeValue::Int(1));
+        }
+        if !self.similar.is_empty() {
+            let hashes: Vec<Vec<u8>> = self.similar.iter().map(InfoHash::to_bytes).collect();
+            info.insert(b"similar".to_vec(), BencodeValue::from(hashes));
+        }
+        if !self.collections.is_empty() {
+            info.insert(
+                b"collections".to_vec(),
+                BencodeValue::from(self.collections.clone()),
+            );
+        }
+
+        if self.version.has_v1() {
+            let pieces = hash_v1_pieces(&run, &v1_files, piece_length)?;
+            info.insert(b"pieces".to_vec(), BencodeValue::Str(pieces));
+            if is_directory {
+                let entries = v1_files.iter().map(file_entry_value).collect();
+                info.insert(b"files".to_vec(), BencodeValue::List(entries));
+            } else {
+                info.insert(b"length".to_vec(), BencodeValue::Int(total_length as i64));
+            }
+        }
+
+        let mut piece_layers = BTreeMap::new();
+        if self.version.has_v2() {
+            let hashes = hash_v2_files(&run, &files, piece_length)?;
+
+            let mut tree = BTreeMap::new();
+            for (file, hashes) in files.iter().zip(&hashes) {
+                let mut properties = BTreeMap::new();
+                properties.insert(b"length".to_vec(), BencodeValue::Int(file.length as i64));
+                if let Some(root) = hashes.pieces_root {
+                    properties.insert(b"pieces root".to_vec(), BencodeValue::from(root.to_vec()));
+                    if !hashes.piece_layer.is_empty() {
+                        piece_layers.insert(
+                            root.to_vec(),
+                            BencodeValue::Str(hashes.piece_layer.concat()),
+                        );
+                    }
+                }
+
+                let path = match is_directory {
+                    true => file.components.clone(),
+                    false => vec![name.clone()],
+                };
+                insert_file_tree_entry(&mut tree, &path, properties);
+            }
+
+            info.insert(b"meta version".to_vec(), BencodeValue::Int(2));
+            info.insert(b"file tree".to_vec(), BencodeValue::Dict(tree));
+        }
+
+        let mut torrent = BTreeMap::new();
+        torrent.insert(b"info".to_vec(), BencodeValue::Dict(info));
+        if self.version.has_v2() {
+            torrent.insert(b"piece layers".to_vec(), BencodeValue::Dict(piece_layers));
+        }
+        // Trackers that only read `announce` fall back to the first tracker of the first tier
+        let announce = self
+            .announce
+            .clone()
+            .or_else(|| self.announce_list.iter().flatten().next().cloned());
+        if let Some(announce) = announce {
+            torrent.insert(b"announce".to_vec(), BencodeValue::from(announce));
+        }
+        if !self.announce_list.is_empty() {
+            torrent.insert(
+                b"announce-list".to_vec(),
+                BencodeValue::from(self.announce_list.clone()),
+            );
+        }
+        if let Some(comment) = &self.comment {
+            torrent.insert(b"comment".to_vec(), BencodeValue::from(comment.as_str()));
+        }
+        if let Some(created_by) = &self.created_by {
+            torrent.insert(
+                b"created by".to_vec(),
+                BencodeValue::from(created_by.as_str()),
+            );
+        }
+        if let Some(creation_date) = self.creation_date {
+            torrent.insert(b"creation date".to_vec(), BencodeValue::Int(creation_date));
+        }
+        if !self.web_seeds.is_empty() {
+            torrent.insert(
+                b"url-list".to_vec(),
+                BencodeValue::from(self.web_seeds.clone()),
+            );
+        }
+        if !self.http_seeds.is_empty() {
+            torrent.insert(
+                b"httpseeds".to_vec(),
+                BencodeValue::from(self.http_seeds.clone()),
+            );
+        }
+
+        Ok(encode_bencode(BencodeValue::Dict(torrent)).expect("encoding a value cannot fail"))
+    }
+}
+
+fn hash_v1_pieces(
+    run: &HashingRun,
+    files: &[SourceFile],
+    piece_length: u64,
+) -> Result<Vec<u8>, BuildError> {
+    let total_length: u64 = files.iter().map(|file| file.length).sum();
+    let piece_count = total_length.div_ceil(piece_length) as usize;
+
+    let hashes = run.run(
+        piece_count,
+        || (PieceReader::new(files), vec![0u8; piece_length as usize]),
+        |(reader, buffer), index| {
+            let offset = index as u64 * piece_length;
+            let length = piece_length.min(total_length - offset) as usize;
+            reader.read_at(offset, &mut buffer[..length])?;
+            let hash: [u8; 20] = Sha1::digest(&buffer[..length]).into();
+            Ok((hash, length as u64))
+        },
+        || BuildError::Cancelled,
+    )?;
+
+    Ok(hashes.concat())
+}
+
+fn hash_v2_files(
+    run: &HashingRun,
+    files: &[SourceFile],
+    piece_length: u64,
+) -> Result<Vec<V2Hashes>, BuildError> {
+    // v2 pieces never span files, so every (file, piece) pair is an independent job
+    let jobs: Vec<(usize, u64)> = files
+        .iter()
+        .enumerate()
+        .flat_map(|(index, file)| {
+            (0..file.length.div_ceil(piece_length)).map(move |piece| (index, piece))
+        })
+        .collect();
+
+    let nodes = run.run(
+        jobs.len(),
+        || (PieceReader::new(files), vec![0u8; piece_length as usize]),
+        |(reader, buffer), job| {
+            let (index, piece) = jobs[job];
+            let file = &files[index];
+            let offset = piece * piece_length;
+            let length = piece_length.min(file.length - offset) as usize;
+            reader.read_at(reader.file_start(index) + offset, &mut buffer[..length])?;
+
+            let node = piece_node(&buffer[..length], file.length, piece_length);
+            Ok((node, length as u64))
+        },
+        || BuildError::Cancelled,
+    )?;
+
+    let height = piece_layer_height(piece_length);
+    let mut nodes = nodes.into_iter();
+    Ok(files
+        .iter()
+        .map(|file| {
+            let count = file.length.div_ceil(piece_length) as usize;
+            let layer: Vec<Hash> = nodes.by_ref().take(count).collect();
+            match count {
+                0 => V2Hashes {
+                    pieces_root: None,
+                    piece_layer: Vec::new(),
+                },
+                _ if file.length <= piece_length => V2Hashes {
+                    pieces_root: Some(layer[0]),
+                    piece_layer: Vec::new(),
+                },
+                _ => V2Hashes {
+                    pieces_root: Some(MerkleTree::from_layer(&layer, height).root()),
+                    piece_layer: layer,
+                },
+            }
+        })
+        .collect())
+}
+
+/// Picks a power-of-two piece length between 16 KiB and 16 MiB giving about 1500 pieces
+pub fn auto_piece_length(total_length: u64) -> u64 {
+    (total_length / TARGET_PIECE_COUNT)
+        .next_power_of_two()
+        .clamp(MIN_PIECE_LENGTH, MAX_AUTO_PIECE_LENGTH)
+}
+
+fn file_entry_value(file: &SourceFile) -> BencodeValue {
+    let mut entry = BTreeMap::new();
+    entry.insert(b"length".to_vec(), BencodeValue::Int(file.length as i64));
+    entry.insert(
+        b"path".to_vec(),
+        BencodeValue::from(file.components.clone()),
+    );
+    if file.padding {
+        let attributes = FileAttributes {
+            padding: true,
+            ..Default::default()
+        };
+        entry.insert(
+            b"attr".to_vec(),
+            BencodeValue::from(attributes.to_attr_string()),
+        );
+    }
+    BencodeValue::Dict(entry)
+}
+
+fn insert_file_tree_entry(
+    tree: &mut BTreeMap<Vec<u8>, BencodeValue>,
+    path: &[String],
+    properties: BTreeMap<Vec<u8>, BencodeValue>,
+) {
+    let (name, parents) = path.split_last().expect("paths are never empty");
+    let mut directory = tree;
+    for parent in parents {
+        let node = directory
+            .entry(parent.as_bytes().to_vec())
+            .or_insert_with(|| BencodeValue::Dict(BTreeMap::new()));
+        let BencodeValue::Dict(child) = node else {
+            unreachable!("file tree directories are dictionaries");
+        };
+        directory = child;
+    }
+
+    let file = BTreeMap::from([(Vec::new(), BencodeValue::Dict(properties))]);
+    directory.insert(name.as_bytes().to_vec(), BencodeValue::Dict(file));
+}
+
+/// Inserts BEP 47 padding files so every file but the last starts on a piece boundary
+fn pad_to_piece_boundaries(files: &[SourceFile], piece_length: u64) -> Vec<SourceFile> {
+    let mut padded = Vec::with_capacity(files.len() * 2);
+    for (index, file) in files.iter().enumerate() {
+        padded.push(file.clone());
+
+        let remainder = file.length % piece_length;
+        if remainder != 0 && index + 1 < files.len() {
+            let length = piece_length - remainder;
+            padded.push(SourceFile {
+                path: PathBuf::new(),
+                length,
+                components: vec![".pad".to_string(), length.to_string()],
+                padding: true,
+            });
+        }
+    }
+    padded
+}
+
+fn collect_files(root: &Path) -> Result<(String, Vec<SourceFile>, bool), BuildError> {
+    let root_name = match root.file_name() {
+        Some(name) => name.to_owned(),
+        None => fs::canonicalize(root)?
+            .file_name()
+            .ok_or_else(|| BuildError::InvalidFileName(root.to_path_buf()))?
+            .to_owned(),
+    };
+    let name = root_name
+        .into_string()
+        .map_err(|_| BuildError::InvalidFileName(root.to_path_buf()))?;
+
+    let metadata = fs::metadata(root)?;
+    if metadata.is_file() {
+        let file = SourceFile {
+            path: root.to_path_buf(),
+            length: metadata.len(),
+            components: Vec::new(),
+            padding: false,
+        };
+        return Ok((name, vec![file], false));
+    }
+
+    let mut files = Vec::new();
+    walk_directory(root, &mut Vec::new(), &mut files)?;
+    if files.is_empty() {
+        return Err(BuildError::NoFiles);
+    }
+    Ok((name, files, true))
+}
+
+fn walk_directory(
+    directory: &Path,
+    prefix: &mut Vec<String>,
+    files: &mut Vec<SourceFile>,
+) -> Result<(), BuildError> {
+    let mut entries = fs::read_dir(directory)?.collect::<Result<Vec<_>, _>>()?;
+    // Sort so the same directory always produces the same torrent
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let component = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| BuildError::InvalidFileName(path.clone()))?;
+        let metadata = fs::metadata(&path)?;
+
+        prefix.push(component);
+        if metadata.is_dir() {
+            walk_directory(&path, prefix, files)?;
+        } else if metadata.is_file() {
+            files.push(SourceFile {
+                path,
+                length: metadata.len(),
+                components: prefix.clone(),
+                padding: false,
+            });
+        }
+        prefix.pop();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::{file_mode::FileMode, file_tree::flatten_file_tree},
+        hashing::merkle::block_hashes,
+        models::file_entry::FileEntry,
+        parsers::metainfo::decode_metainfo,
+    };
+    use bencode::{decoders::raw::decode_raw_dictionary, dispatcher::bdecode::decode_bencode};
+    use std::sync::{
+        Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    };
+
+    fn sha1(data: &[u8]) -> [u8; 20] {
+        Sha1::digest(data).into()
+    }
+
+    fn pattern(length: usize, seed: u8) -> Vec<u8> {
+        (0..length)
+            .map(|i| (i as u8).wrapping_mul(31) ^ seed)
+            .collect()
+    }
+
+    #[test]
+    fn test_single_file_torrent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        let data = pattern(40_000, 1);
+        fs::write(&path, &data).unwrap();
+
+        let torrent = TorrentBuilder::new(&path)
+            .piece_length(16384)
+            .announce("http://tracker.example/announce")
+            .comment("release")
+            .created_by("bittorrent-rust")
+            .creation_date(1700000000)
+            .threads(3)
+            .build()
+            .unwrap();
+        let metainfo = decode_metainfo(&torrent).unwrap();
+
+        assert_eq!(metainfo.info.name, "data.bin");
+        assert_eq!(metainfo.info.mode, FileMode::Single { length: 40_000 });
+        assert_eq!(
+            metainfo.info.pieces,
+            data.chunks(16384).map(sha1).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            metainfo.announce.as_deref(),
+            Some("http://tracker.example/announce")
+        );
+        assert_eq!(metainfo.comment.as_deref(), Some("release"));
+        assert_eq!(metainfo.created_by.as_deref(), Some("bittorrent-rust"));
+        assert_eq!(metainfo.creation_date, Some(1700000000));
+        assert!(!metainfo.info.private);
+    }
+
+    #[test]
+    fn test_directory_torrent_spans_file_boundaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("release");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        let a = pattern(10_000, 2);
+        let b = pattern(30_000, 3);
+        fs::write(root.join("b.bin"), &a).unwrap();
+        fs::write(root.join("empty"), b"").unwrap();
+        fs::write(root.join("sub").join("a.bin"), &b).unwrap();
+
+        let torrent = TorrentBuilder::new(&root)
+            .piece_length(16384)
+            .private(true)
+            .build()
+            .unwrap();
+        let metainfo = decode_metainfo(&torrent).unwrap();
+
+        assert_eq!(metainfo.info.name, "release");
+        assert!(metainfo.info.private);
+        assert_eq!(
+            metainfo.info.mode,
+            FileMode::Multiple {
+                files: vec![
+                    FileEntry {
+                        length: 10_000,
+                        path: vec!["b.bin".to_string()],
+                        ..Default::default()
+                    },
+                    FileEntry {
+                        length: 0,
+                        path: vec!["empty".to_string()],
+                        ..Default::default()
+                    },
+                    FileEntry {
+                        length: 30_000,
+                        path: vec!["sub".to_string(), "a.bin".to_string()],
+                        ..Default::default()
+                    },
+                ],
+            }
+        );
+
+        let concatenated = [a, b].concat();
+        assert_eq!(
+            metainfo.info.pieces,
+            concatenated.chunks(16384).map(sha1).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_announce_list_and_web_seeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, b"hello").unwrap();
+
+        let torrent = TorrentBuilder::new(&path)
+            .announce_tier(["udp://a.example:80", "udp://b.example:80"])
+            .announce_tier(["http://c.example/announce"])
+            .web_seed("http://mirror.example/file")
+            .http_seed("http://seed.example/seed")
+            .build()
+            .unwrap();
+
+        let (value, _) = decode_bencode(&torrent).unwrap();
+        let expected = bencode::bencode!({
+            "announce": "udp://a.example:80",
+            "announce-list": [
+                ["udp://a.example:80", "udp://b.example:80"],
+                ["http://c.example/announce"],
+            ],
+            "httpseeds": ["http://seed.example/seed"],
+            "url-list": ["http://mirror.example/file"],
+        });
+        let (BencodeValue::Dict(dict), BencodeValue::Dict(expected)) = (value, expected) else {
+            unreachable!()
+        };
+        for (key, value) in expected {
+            assert_eq!(dict.get(&key), Some(&value));
+        }
+    }
+
+    #[test]
+    fn test_similar_and_collections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, b"hello").unwrap();
+
+        let v1 = InfoHash::V1([1; 20]);
+        let hybrid = InfoHash::Hybrid {
+            v1: [2; 20],
+            v2: [3; 32],
+        };
+        let torrent = TorrentBuilder::new(&path)
+            .similar(v1)
+            .similar(hybrid)
+            .collection("series")
+            .build()
+            .unwrap();
+
+        let metainfo = decode_metainfo(&torrent).unwrap();
+        assert_eq!(metainfo.info.similar, vec![v1, InfoHash::V1([2; 20])]);
+        assert_eq!(metainfo.info.collections, vec!["series".to_string()]);
+    }
+
+    #[test]
+    fn test_output_is_canonical() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, pattern(100, 4)).unwrap();
+
+        let torrent = TorrentBuilder::new(&path).comment("x").build().unwrap();
+        let (value, _) = decode_bencode(&torrent).unwrap();
+        assert_eq!(encode_bencode(value).unwrap(), torrent);
+
+        let (dict, _) = decode_raw_dictionary(&torrent).unwrap();
+        assert!(dict.contains_key(b"info".as_slice()));
+    }
+
+    #[test]
+    fn test_progress_reports_every_piece() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, pattern(16384 * 5 + 1, 5)).unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let last_bytes = Arc::new(AtomicU64::new(0));
+        let (calls_seen, bytes_seen) = (calls.clone(), last_bytes.clone());
+        TorrentBuilder::new(&path)
+            .piece_length(16384)
+            .threads(2)
+            .on_progress(move |progress| {
+                calls_seen.fetch_add(1, Ordering::SeqCst);
+                bytes_seen.fetch_max(progress.bytes_done, Ordering::SeqCst);
+                assert_eq!(progress.piece_count, 6);
+                assert_eq!(progress.total_bytes, 16384 * 5 + 1);
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+        assert_eq!(last_bytes.load(Ordering::SeqCst), 16384 * 5 + 1);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, pattern(16384 * 4, 6)).unwrap();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        let result = TorrentBuilder::new(&path)
+            .piece_length(16384)
+            .threads(1)
+            .cancel_flag(cancel)
+            .on_progress(move |_| flag.store(true, Ordering::SeqCst))
+            .build();
+
+        assert!(matches!(result, Err(BuildError::Cancelled)));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            TorrentBuilder::new(dir.path()).build(),
+            Err(BuildError::NoFiles)
+        ));
+        assert!(matches!(
+            TorrentBuilder::new(dir.path().join("missing")).build(),
+            Err(BuildError::Io(_))
+        ));
+
+        let path = dir.path().join("file");
+        fs::write(&path, b"x").unwrap();
+        assert!(matches!(
+            TorrentBuilder::new(&path).piece_length(1000).build(),
+            Err(BuildError::InvalidPieceLength(1000))
+        ));
+        assert!(matches!(
+            TorrentBuilder::new(&path).piece_length(1 << 27).build(),
+            Err(BuildError::InvalidPieceLength(length)) if length == 1 << 27
+        ));
+    }
+
+    #[test]
+    fn test_v2_torrent() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("release");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        let large = pattern(100_000, 4);
+        let small = pattern(5_000, 5);
+        fs::write(root.join("large.bin"), &large).unwrap();
+        fs::write(root.join("sub").join("small.bin"), &small).unwrap();
+        fs::write(root.join("empty"), b"").unwrap();
+
+        let torrent = TorrentBuilder::new(&root)
+            .piece_length(32768)
+            .version(MetaVersion::V2)
+            .threads(2)
+            .build()
+            .unwrap();
+        let metainfo = decode_metainfo(&torrent).unwrap();
+
+        assert_eq!(metainfo.info.meta_version, MetaVersion::V2);
+        assert!(metainfo.info.pieces.is_empty());
+        assert!(matches!(metainfo.info_hash, InfoHash::V2(_)));
+
+        let files = flatten_file_tree(metainfo.info.file_tree.as_ref().unwrap());
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].0, vec!["empty"]);
+        assert_eq!(files[0].1.pieces_root, None);
+
+        let large_root = MerkleTree::from_leaves(&block_hashes(&large)).root();
+        assert_eq!(files[1].0, vec!["large.bin"]);
+        assert_eq!(files[1].1.pieces_root, Some(large_root));
+        assert_eq!(metainfo.piece_layers[&large_root].len(), 4);
+
+        let small_root = MerkleTree::from_leaves(&block_hashes(&small)).root();
+        assert_eq!(files[2].0, vec!["sub", "small.bin"]);
+        assert_eq!(files[2].1.pieces_root, Some(small_root));
+        assert_eq!(metainfo.piece_layers.len(), 1);
+    }
+
+    #[test]
+    fn test_hybrid_torrent_pads_v1_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("release");
+        fs::create_dir_all(&root).unwrap();
+        let a = pattern(20_000, 6);
+        let b = pattern(40_000, 7);
+        fs::write(root.join("a.bin"), &a).unwrap();
+        fs::write(root.join("b.bin"), &b).unwrap();
+
+        let torrent = TorrentBuilder::new(&root)
+            .piece_length(16384)
+            .version(MetaVersion::Hybrid)
+            .build()
+            .unwrap();
+        let metainfo = decode_metainfo(&torrent).unwrap();
+
+        assert_eq!(metainfo.info.meta_version, MetaVersion::Hybrid);
+        assert!(matches!(metainfo.info_hash, InfoHash::Hybrid { .. }));
+
+        let files = metainfo.info.all_files();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[1].path, vec![".pad", "12768"]);
+        assert!(files[1].is_padding());
+        assert_eq!(metainfo.info.files().len(), 2);
+
+        let mut v1_data = a.clone();
+        v1_data.resize(32768, 0);
+        v1_data.extend_from_slice(&b);
+        assert_eq!(
+            metainfo.info.pieces,
+            v1_data.chunks(16384).map(sha1).collect::<Vec<_>>()
+        );
+
+        let tree = flatten_file_tree(metainfo.info.file_tree.as_ref().unwrap());
+        assert_eq!(tree.len(), 2);
+        assert_eq!(
+            tree[1].1.pieces_root,
+            Some(MerkleTree::from_leaves(&block_hashes(&b)).root())
+        );
+    }
+
+    #[test]
+    fn test_padded_v1_torrent() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("release");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.bin"), pattern(100, 9)).unwrap();
+        fs::write(root.join("b.bin"), pattern(100, 10)).unwrap();
+
+        let torrent = TorrentBuilder::new(&root)
+            .piece_length(16384)
+            .pad_files(true)
+            .build()
+            .unwrap();
+        let metainfo = decode_metainfo(&torrent).unwrap();
+
+        assert_eq!(metainfo.info.meta_version, MetaVersion::V1);
+        let files = metainfo.info.all_files();
+        assert_eq!(files[1].path, vec![".pad", "16284"]);
+        assert!(files[1].is_padding());
+        assert_eq!(metainfo.info.pieces.len(), 2);
+    }
+
+    #[test]
+    fn test_v2_progress_counts_both_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        fs::write(&path, pattern(50_000, 8)).unwrap();
+
+        let last = Arc::new(Mutex::new(None));
+        let seen = Arc::clone(&last);
+        TorrentBuilder::new(&path)
+            .piece_length(16384)
+            .version(MetaVersion::Hybrid)
+            .on_progress(move |progress| *seen.lock().unwrap() = Some(progress))
+            .build()
+            .unwrap();
+
+        let last = last.lock().unwrap().unwrap();
+        assert_eq!(last.piece_count, 8);
+        assert_eq!(last.pieces_done, 8);
+        assert_eq!(last.total_bytes, 100_000);
+        assert_eq!(last.bytes_done, 100_000);
+    }
+
+    #[test]
+    fn test_auto_piece_length() {
+        assert_eq!(auto_piece_length(0), MIN_PIECE_LENGTH);
+        assert_eq!(auto_piece_length(1024 * 1024 * 1024), 1024 * 1024);
+        assert_eq!(auto_piece_length(u64::MAX / 2), MAX_AUTO_PIECE_LENGTH);
+    }
+}
@@ -0,0 +1,309 @@
+use crate::{
+    enums::{error::MetainfoError, info_hash::InfoHash},
+    hashing::info_hash::{hash_info_bytes, raw_info_bytes},
+    models::tracker_list::TrackerList,
+    parsers::fields::as_dict,
+};
+#[cfg(feature = "signatures")]
+use crate::{
+    enums::{signature_error::SignatureError, signing_key::SigningKey},
+    models::signature::Signature,
+};
+use bencode::{
+    decoders::raw::decode_raw_dictionary,
+    dispatcher::{
+        bdecode::decode_bencode,
+        bencode::{encode, encode_bencode},
+    },
+    encoders::string::write_string,
+    enums::bencode::BencodeValue,
+};
+use std::collections::BTreeMap;
+
+/// Edits the top level of an existing `.torrent` while copying its `info`
+/// dictionary byte for byte, so the info-hash survives non-canonical encodings
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TorrentEditor {
+    /// Every top-level value, `info` included, as encoded bytes
+    fields: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl TorrentEditor {
+    pub fn new(data: &[u8]) -> Result<Self, MetainfoError> {
+        // Checks the outer dictionary, the info dictionary and trailing data
+        raw_info_bytes(data)?;
+        let (dict, _) = decode_raw_dictionary(data)?;
+        Ok(TorrentEditor {
+            fields: dict
+                .into_iter()
+                .map(|(key, value)| (key.to_vec(), value.to_vec()))
+                .collect(),
+        })
+    }
+
+    pub fn info_hash(&self) -> InfoHash {
+        hash_info_bytes(self.info_bytes()).expect("the info dictionary was checked on load")
+    }
+
+    /// The info dictionary exactly as it will be written
+    pub fn info_bytes(&self) -> &[u8] {
+        &self.fields[b"info".as_slice()]
+    }
+
+    pub fn get(&self, key: &str) -> Option<BencodeValue> {
+        let raw = self.fields.get(key.as_bytes())?;
+        let (value, _) = decode_bencode(raw).expect("stored values are valid bencode");
+        Some(value)
+    }
+
+    /// Sets any top-level key except `info`
+    pub fn set(&mut self, key: &str, value: BencodeValue) -> Result<(), MetainfoError> {
+        if key == "info" {
+            return Err(MetainfoError::InvalidValue {
+                key: "info",
+                reason: "only changes through the info-changing operations",
+            });
+        }
+        let encoded = encode(&value).expect("encoding a value cannot fail");
+        self.fields.insert(key.as_bytes().to_vec(), encoded);
+        Ok(())
+    }
+
+    /// Removes any top-level key except `info`; returns whether it was present
+    pub fn remove(&mut self, key: &str) -> bool {
+        key != "info" && self.fields.remove(key.as_bytes()).is_some()
+    }
+
+    pub fn set_announce(&mut self, announce: Option<&str>) {
+        self.set_or_remove("announce", announce.map(BencodeValue::from));
+    }
+
+    /// Replaces `announce-list` and points `announce` at its first tracker;
+    /// an empty list removes both
+    pub fn set_trackers(&mut self, trackers: &TrackerList) {
+        let first = trackers.iter().next();
+        self.set_announce(first);
+        self.set_or_remove("announce-list", first.map(|_| trackers.to_bencode()));
+    }
+
+    pub fn set_comment(&mut self, comment: Option<&str>) {
+        self.set_or_remove("comment", comment.map(BencodeValue::from));
+    }
+
+    pub fn set_created_by(&mut self, created_by: Option<&str>) {
+        self.set_or_remove("created by", created_by.map(BencodeValue::from));
+    }
+
+    pub fn set_creation_date(&mut self, creation_date: Option<i64>) {
+        self.set_or_remove("creation date", creation_date.map(BencodeValue::Int));
+    }
+
+    /// Replaces the BEP 19 `url-list`; an empty list removes it
+    pub fn set_web_seeds(&mut self, web_seeds: &[String]) {
+        let value = (!web_seeds.is_empty()).then(|| BencodeValue::from(web_seeds.to_vec()));
+        self.set_or_remove("url-list", value);
+    }
+
+    /// Replaces the BEP 17 `httpseeds`; an empty list removes it
+    pub fn set_http_seeds(&mut self, http_seeds: &[String]) {
+        let value = (!http_seeds.is_empty()).then(|| BencodeValue::from(http_seeds.to_vec()));
+        self.set_or_remove("httpseeds", value);
+    }
+
+    /// Sets or clears the BEP 27 `private` flag. This rewrites `info`, drops any
+    /// signatures and returns the new info-hash; nothing changes when the flag
+    /// already has this value
+    pub fn set_private(&mut self, private: bool) -> Result<InfoHash, MetainfoError> {
+        self.edit_info(|info| match private {
+            true => {
+                info.insert(b"private".to_vec(), BencodeValue::Int(1)) != Some(BencodeValue::Int(1))
+            }
+            false => info.remove(b"private".as_slice()).is_some(),
+        })
+    }
+
+    /// Sets or removes the `source` tag private trackers use to make cross-seeded
+    /// torrents hash differently. This rewrites `info`, drops any signatures and
+    /// returns the new info-hash
+    pub fn set_source(&mut self, source: Option<&str>) -> Result<InfoHash, MetainfoError> {
+        self.edit_info(|info| match source {
+            Some(source) => {
+                let value = BencodeValue::from(source);
+                info.insert(b"source".to_vec(), value.clone()) != Some(value)
+            }
+            None => info.remove(b"source".as_slice()).is_some(),
+        })
+    }
+
+    /// Adds or replaces the BEP 35 signature of `signer` over the current info
+    /// dictionary, embedding `certificate` when given
+    #[cfg(feature = "signatures")]
+    pub fn sign(
+        &mut self,
+        signer: &str,
+        key: &SigningKey,
+        certificate: Option<&[u8]>,
+    ) -> Result<(), SignatureError> {
+        let signature = Signature::sign(signer, self.info_bytes(), key, certificate)?;
+        let mut signatures = self.signatures();
+        signatures.insert(signer.as_bytes().to_vec(), signature.to_bencode());
+        self.set_or_remove("signatures", Some(BencodeValue::Dict(signatures)));
+        Ok(())
+    }
+
+    /// Removes the signature of `signer`; returns whether it was present
+    pub fn remove_signature(&mut self, signer: &str) -> bool {
+        let mut signatures = self.signatures();
+        let removed = signatures.remove(signer.as_bytes()).is_some();
+        let signatures = (!signatures.is_empty()).then_some(BencodeValue::Dict(signatures));
+        self.set_or_remove("signatures", signatures);
+        removed
+    }
+
+    /// Encodes the torrent with keys in sorted order
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let length = self
+            .fields
+            .iter()
+            .map(|(key, value)| key.len() + value.len() + 8)
+            .sum::<usize>();
+        let mut out = Vec::with_capacity(length + 2);
+        out.push(b'd');
+        for (key, value) in &self.fields {
+            write_string(key, &mut out);
+            out.extend_from_slice(value);
+        }
+        out.push(b'e');
+        out
+    }
+
+    fn set_or_remove(&mut self, key: &str, value: Option<BencodeValue>) {
+        match value {
+            Some(value) => self.set(key, value).expect("key is not 'info'"),
+            None => {
+                self.remove(key);
+            }
+        }
+    }
+
+    fn signatures(&self) -> BTreeMap<Vec<u8>, BencodeValue> {
+        match self.get("signatures") {
+            Some(BencodeValue::Dict(signatures)) => signatures,
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Re-encodes `info` when `edit` reports a change, dropping the signatures
+    /// that covered the old dictionary
+    fn edit_info(
+        &mut self,
+        edit: impl FnOnce(&mut BTreeMap<Vec<u8>, BencodeValue>) -> bool,
+    ) -> Result<InfoHash, MetainfoError> {
+        let (info, _) = decode_bencode(self.info_bytes())?;
+        let mut info = as_dict(&info, "info")?.clone();
+        if edit(&mut info) {
+            let encoded = encode_bencode(BencodeValue::Dict(info))?;
+            self.fields.insert(b"info".to_vec(), encoded);
+            self.fields.remove(b"signatures".as_slice());
+        }
+        Ok(self.info_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hashing::info_hash::info_hash, parsers::metainfo::decode_metainfo};
+
+    // `name` before `length`: not canonical, so re-encoding would change the hash
+    const TORRENT: &[u8] = b"d8:announce3:old7:comment3:old4:infod4:name1:a6:lengthi1e\
+        12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaae1:zi1ee";
+
+    #[test]
+    fn test_top_level_edits_keep_info_bytes() {
+        let mut editor = TorrentEditor::new(TORRENT).unwrap();
+        let hash = info_hash(TORRENT).unwrap();
+
+        editor.set_trackers(&TrackerList::new(vec![
+            vec!["udp://a".to_string()],
+            vec!["udp://b".to_string()],
+        ]));
+        editor.set_comment(None);
+        editor.set_created_by(Some("editor"));
+        editor.set_creation_date(Some(1700000000));
+        editor.set_web_seeds(&["https://mirror/".to_string()]);
+        editor.set_http_seeds(&["https://seed/".to_string()]);
+        editor.set("x-custom", BencodeValue::from("value")).unwrap();
+        assert!(editor.remove("z"));
+
+        let edited = editor.to_bytes();
+        assert_eq!(info_hash(&edited).unwrap(), hash);
+        assert_eq!(
+            raw_info_bytes(&edited).unwrap(),
+            raw_info_bytes(TORRENT).unwrap()
+        );
+
+        let metainfo = decode_metainfo(&edited).unwrap();
+        assert_eq!(metainfo.announce.as_deref(), Some("udp://a"));
+        assert_eq!(metainfo.announce_list.len(), 2);
+        assert_eq!(metainfo.comment, None);
+        assert_eq!(metainfo.created_by.as_deref(), Some("editor"));
+        assert_eq!(metainfo.creation_date, Some(1700000000));
+        assert_eq!(metainfo.web_seeds, vec!["https://mirror/"]);
+        assert_eq!(metainfo.http_seeds, vec!["https://seed/"]);
+        assert_eq!(editor.get("x-custom"), Some(BencodeValue::from("value")));
+        assert_eq!(editor.get("z"), None);
+    }
+
+    #[test]
+    fn test_info_is_protected() {
+        let mut editor = TorrentEditor::new(TORRENT).unwrap();
+        assert!(editor.set("info", BencodeValue::from("x")).is_err());
+        assert!(!editor.remove("info"));
+
+        editor.set_trackers(&TrackerList::default());
+        assert_eq!(editor.get("announce"), None);
+        assert_eq!(
+            editor.to_bytes().len(),
+            TORRENT.len() - b"8:announce3:old".len()
+        );
+    }
+
+    #[test]
+    fn test_info_changing_operations() {
+        let mut editor = TorrentEditor::new(TORRENT).unwrap();
+        let original = editor.info_hash();
+
+        // Clearing a flag that is not set leaves the original bytes alone
+        assert_eq!(editor.set_private(false).unwrap(), original);
+
+        let private = editor.set_private(true).unwrap();
+        assert_ne!(private, original);
+        let sourced = editor.set_source(Some("TRACKER")).unwrap();
+        assert_ne!(sourced, private);
+
+        let edited = editor.to_bytes();
+        assert_eq!(info_hash(&edited).unwrap(), sourced);
+        let metainfo = decode_metainfo(&edited).unwrap();
+        assert!(metainfo.info.private);
+        assert_eq!(
+            TorrentEditor::new(&edited)
+                .unwrap()
+                .get("info")
+                .map(|info| { as_dict(&info, "info").unwrap()[b"source".as_slice()].clone() }),
+            Some(BencodeValue::from("TRACKER"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_torrents() {
+        assert_eq!(
+            TorrentEditor::new(b"de"),
+            Err(MetainfoError::MissingKey("info"))
+        );
+        assert_eq!(
+            TorrentEditor::new(b"d4:infodee1:x"),
+            Err(MetainfoError::TrailingData)
+        );
+    }
+}
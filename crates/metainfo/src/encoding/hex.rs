@@ -0,0 +1,50 @@
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+pub fn encode_hex(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len() * 2);
+    for &byte in data {
+        encoded.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+        encoded.push(HEX_DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    encoded
+}
+
+pub fn decode_hex(data: &str) -> Result<Vec<u8>, &'static str> {
+    if !data.len().is_multiple_of(2) {
+        return Err("Hex string has an odd length");
+    }
+
+    data.as_bytes()
+        .chunks_exact(2)
+        .map(|pair| Ok((hex_value(pair[0])? << 4) | hex_value(pair[1])?))
+        .collect()
+}
+
+fn hex_value(digit: u8) -> Result<u8, &'static str> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err("Invalid hex digit"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let data = [0x00, 0x01, 0xab, 0xff];
+        assert_eq!(encode_hex(&data), "0001abff");
+        assert_eq!(decode_hex("0001abff").unwrap(), data);
+        assert_eq!(decode_hex("0001ABFF").unwrap(), data);
+        assert_eq!(encode_hex(&[]), "");
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        assert!(decode_hex("abc").is_err());
+        assert!(decode_hex("zz").is_err());
+    }
+}
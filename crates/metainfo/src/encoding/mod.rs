@@ -0,0 +1,3 @@
+pub mod base32;
+pub mod hex;
+pub mod percent;
@@ -0,0 +1,127 @@
+/// Percent-encodes everything but RFC 3986 unreserved characters and `:`, `/`, `@`,
+/// which are all allowed unescaped in a query value
+pub fn encode_percent(data: &str) -> String {
+    encode_percent_bytes(data.as_bytes())
+}
+
+/// Same as `encode_percent`, for binary data such as a raw info-hash
+pub fn encode_percent_bytes(data: &[u8]) -> String {
+    encode_with(data, |byte| {
+        is_unreserved(byte) || matches!(byte, b':' | b'/' | b'@')
+    })
+}
+
+/// Percent-encodes a single URL path segment, keeping only RFC 3986 unreserved
+/// characters, so a `/` in the value cannot add a segment. `.` and `..` are
+/// encoded in full, as they would otherwise move up the path
+pub fn encode_path_segment(segment: &str) -> String {
+    match segment {
+        "." | ".." => encode_with(segment.as_bytes(), |_| false),
+        _ => encode_with(segment.as_bytes(), is_unreserved),
+    }
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+fn encode_with(data: &[u8], keep: impl Fn(u8) -> bool) -> String {
+    let mut encoded = String::with_capacity(data.len());
+    for &byte in data {
+        if keep(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push('%');
+            encoded.push_str(&format!("{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` escapes, and `+` as a space as in HTML form encoding
+pub fn decode_percent(data: &str) -> Result<String, &'static str> {
+    String::from_utf8(decode_percent_bytes(data)?)
+        .map_err(|_| "Percent-decoded value is not valid UTF-8")
+}
+
+/// Same as `decode_percent`, for binary values such as a raw info-hash
+pub fn decode_percent_bytes(data: &str) -> Result<Vec<u8>, &'static str> {
+    let bytes = data.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                let escape = bytes
+                    .get(index + 1..index + 3)
+                    .ok_or("Truncated percent escape")?;
+                if !escape.iter().all(u8::is_ascii_hexdigit) {
+                    return Err("Invalid percent escape");
+                }
+                let escape = std::str::from_utf8(escape).expect("hex digits are ASCII");
+                let byte = u8::from_str_radix(escape, 16).expect("escape is two hex digits");
+                decoded.push(byte);
+                index += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let value = "udp://tracker.example:80/announce?key=a b&x=ü";
+        let encoded = encode_percent(value);
+        assert_eq!(
+            encoded,
+            "udp://tracker.example:80/announce%3Fkey%3Da%20b%26x%3D%C3%BC"
+        );
+        assert_eq!(decode_percent(&encoded).unwrap(), value);
+    }
+
+    #[test]
+    fn test_encode_bytes() {
+        assert_eq!(encode_percent_bytes(&[0x00, b'a', 0xff]), "%00a%FF");
+    }
+
+    #[test]
+    fn test_encode_path_segment() {
+        assert_eq!(
+            encode_path_segment("a/b:c@d e.txt"),
+            "a%2Fb%3Ac%40d%20e.txt"
+        );
+        assert_eq!(encode_path_segment(".."), "%2E%2E");
+        assert_eq!(encode_path_segment("..."), "...");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(
+            decode_percent("Big+Buck%20Bunny").unwrap(),
+            "Big Buck Bunny"
+        );
+        assert_eq!(decode_percent("%2b%2B").unwrap(), "++");
+        assert!(decode_percent("abc%2").is_err());
+        assert!(decode_percent("%zz").is_err());
+        assert!(decode_percent("%+1").is_err());
+        assert!(decode_percent("%FF").is_err());
+    }
+
+    #[test]
+    fn test_decode_bytes() {
+        assert_eq!(decode_percent_bytes("%00a%FF").unwrap(), [0x00, b'a', 0xff]);
+        assert!(decode_percent_bytes("%F").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use std::{fmt, io, path::PathBuf};
+
+#[derive(Debug)]
+pub enum BuildError {
+    Io(io::Error),
+    /// The cancel flag was raised while hashing
+    Cancelled,
+    /// The source path holds no files
+    NoFiles,
+    /// A file or directory name is not valid UTF-8
+    InvalidFileName(PathBuf),
+    /// Piece lengths must be a power of two from 16 KiB to 64 MiB
+    InvalidPieceLength(u64),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Io(err) => write!(f, "I/O error: {err}"),
+            BuildError::Cancelled => write!(f, "torrent creation was cancelled"),
+            BuildError::NoFiles => write!(f, "no files to add to the torrent"),
+            BuildError::InvalidFileName(path) => {
+                write!(f, "file name is not valid UTF-8: {}", path.display())
+            }
+            BuildError::InvalidPieceLength(length) => write!(
+                f,
+                "piece length {length} is not a power of two from 16 KiB to 64 MiB"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BuildError {
+    fn from(err: io::Error) -> Self {
+        BuildError::Io(err)
+    }
+}
@@ -0,0 +1,92 @@
+use crate::encoding::hex::encode_hex;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MetainfoError {
+    /// The input is not valid bencode
+    Decode(&'static str),
+    /// Bytes remain after the top-level dictionary
+    TrailingData,
+    /// A value that must be a dictionary is something else; holds what was being parsed
+    NotADictionary(&'static str),
+    MissingKey(&'static str),
+    WrongType {
+        key: &'static str,
+        expected: &'static str,
+    },
+    InvalidUtf8(&'static str),
+    InvalidValue {
+        key: &'static str,
+        reason: &'static str,
+    },
+    /// `pieces` is not a multiple of 20 bytes; holds its actual length
+    InvalidPiecesLength(usize),
+    /// Both `length` and `files` are present in the info dictionary
+    AmbiguousFileMode,
+    /// Neither `length` nor `files` is present in the info dictionary
+    MissingFileMode,
+    /// A file larger than one piece has no entry in `piece layers`; holds its pieces root
+    MissingPieceLayer([u8; 32]),
+    /// A piece layer does not hash up to its pieces root
+    PieceLayerMismatch([u8; 32]),
+    /// A BEP 35 signature does not verify against its own certificate; holds the signer
+    InvalidSignature(String),
+    /// An entry of the `files` list is invalid
+    FileEntry {
+        index: usize,
+        source: Box<MetainfoError>,
+    },
+}
+
+impl fmt::Display for MetainfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetainfoError::Decode(reason) => write!(f, "invalid bencode: {reason}"),
+            MetainfoError::TrailingData => write!(f, "trailing data after metainfo dictionary"),
+            MetainfoError::NotADictionary(what) => write!(f, "{what} must be a dictionary"),
+            MetainfoError::MissingKey(key) => write!(f, "missing required key '{key}'"),
+            MetainfoError::WrongType { key, expected } => {
+                write!(f, "key '{key}' must be {expected}")
+            }
+            MetainfoError::InvalidUtf8(key) => write!(f, "key '{key}' is not valid UTF-8"),
+            MetainfoError::InvalidValue { key, reason } => {
+                write!(f, "invalid value for '{key}': {reason}")
+            }
+            MetainfoError::InvalidPiecesLength(len) => {
+                write!(f, "'pieces' length {len} is not a multiple of 20")
+            }
+            MetainfoError::AmbiguousFileMode => {
+                write!(f, "info dictionary has both 'length' and 'files'")
+            }
+            MetainfoError::MissingFileMode => {
+                write!(f, "info dictionary has neither 'length' nor 'files'")
+            }
+            MetainfoError::MissingPieceLayer(root) => {
+                write!(
+                    f,
+                    "missing piece layer for pieces root {}",
+                    encode_hex(root)
+                )
+            }
+            MetainfoError::PieceLayerMismatch(root) => write!(
+                f,
+                "piece layer does not match pieces root {}",
+                encode_hex(root)
+            ),
+            MetainfoError::InvalidSignature(signer) => {
+                write!(f, "signature of '{signer}' does not match its certificate")
+            }
+            MetainfoError::FileEntry { index, source } => {
+                write!(f, "files[{index}]: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MetainfoError {}
+
+impl From<&'static str> for MetainfoError {
+    fn from(reason: &'static str) -> Self {
+        MetainfoError::Decode(reason)
+    }
+}
@@ -0,0 +1,8 @@
+/// Why a local file is a reuse candidate, strongest evidence first
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum FileMatch {
+    /// Both torrents give the file the same v2 `pieces root`, so the data is identical
+    PiecesRoot,
+    /// Same length and file name; the data still has to be verified
+    LengthAndName,
+}
@@ -0,0 +1,9 @@
+use crate::models::file_entry::FileEntry;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FileMode {
+    /// The torrent holds one file named after `Info::name`
+    Single { length: u64 },
+    /// The torrent holds a directory named after `Info::name`
+    Multiple { files: Vec<FileEntry> },
+}
@@ -0,0 +1,61 @@
+use crate::models::v2_file::V2File;
+use std::collections::BTreeMap;
+
+/// Contents of a BEP 52 `file tree` directory, keyed by path component
+pub type FileTree = BTreeMap<String, FileTreeNode>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FileTreeNode {
+    File(V2File),
+    Directory(FileTree),
+}
+
+/// Every file of the tree with its path components, in tree (sorted) order
+pub fn flatten_file_tree(tree: &FileTree) -> Vec<(Vec<String>, V2File)> {
+    let mut files = Vec::new();
+    collect_files(tree, &mut Vec::new(), &mut files);
+    files
+}
+
+fn collect_files(
+    tree: &FileTree,
+    prefix: &mut Vec<String>,
+    files: &mut Vec<(Vec<String>, V2File)>,
+) {
+    for (name, node) in tree {
+        prefix.push(name.clone());
+        match node {
+            FileTreeNode::File(file) => files.push((prefix.clone(), *file)),
+            FileTreeNode::Directory(directory) => collect_files(directory, prefix, files),
+        }
+        prefix.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten_in_sorted_order() {
+        let file = |length| {
+            FileTreeNode::File(V2File {
+                length,
+                pieces_root: None,
+            })
+        };
+        let tree = FileTree::from([
+            ("b".to_string(), file(2)),
+            (
+                "a".to_string(),
+                FileTreeNode::Directory(FileTree::from([("c".to_string(), file(1))])),
+            ),
+        ]);
+
+        let files = flatten_file_tree(&tree);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].0, vec!["a".to_string(), "c".to_string()]);
+        assert_eq!(files[0].1.length, 1);
+        assert_eq!(files[1].0, vec!["b".to_string()]);
+    }
+}
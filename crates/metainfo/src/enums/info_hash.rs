@@ -0,0 +1,151 @@
+use crate::encoding::{
+    base32::{decode_base32, encode_base32},
+    hex::{decode_hex, encode_hex},
+};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum InfoHash {
+    /// SHA-1 of the info dictionary (BEP 3)
+    V1([u8; 20]),
+    /// SHA-256 of the info dictionary (BEP 52)
+    V2([u8; 32]),
+    /// A hybrid torrent is reachable through both swarms
+    Hybrid { v1: [u8; 20], v2: [u8; 32] },
+}
+
+impl InfoHash {
+    pub fn v1(&self) -> Option<[u8; 20]> {
+        match self {
+            InfoHash::V1(v1) | InfoHash::Hybrid { v1, .. } => Some(*v1),
+            InfoHash::V2(_) => None,
+        }
+    }
+
+    pub fn v2(&self) -> Option<[u8; 32]> {
+        match self {
+            InfoHash::V2(v2) | InfoHash::Hybrid { v2, .. } => Some(*v2),
+            InfoHash::V1(_) => None,
+        }
+    }
+
+    /// The hash sent to trackers and peers: the v1 hash when there is one,
+    /// otherwise the v2 hash truncated to 20 bytes
+    pub fn truncated(&self) -> [u8; 20] {
+        match self {
+            InfoHash::V1(v1) | InfoHash::Hybrid { v1, .. } => *v1,
+            InfoHash::V2(v2) => v2[..20].try_into().expect("slice is 20 bytes"),
+        }
+    }
+
+    /// Lowercase hex of the v1 hash, or of the v2 hash for v2-only torrents
+    pub fn to_hex(&self) -> String {
+        match self {
+            InfoHash::V1(v1) | InfoHash::Hybrid { v1, .. } => encode_hex(v1),
+            InfoHash::V2(v2) => encode_hex(v2),
+        }
+    }
+
+    /// Unpadded RFC 4648 base32 of the v1 hash, or of the v2 hash for v2-only torrents
+    pub fn to_base32(&self) -> String {
+        match self {
+            InfoHash::V1(v1) | InfoHash::Hybrid { v1, .. } => encode_base32(v1),
+            InfoHash::V2(v2) => encode_base32(v2),
+        }
+    }
+
+    /// Raw v1 hash, or the v2 hash for v2-only torrents
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            InfoHash::V1(v1) | InfoHash::Hybrid { v1, .. } => v1.to_vec(),
+            InfoHash::V2(v2) => v2.to_vec(),
+        }
+    }
+
+    /// Whether both hashes identify the same torrent, comparing whichever
+    /// versions they have in common
+    pub fn matches(&self, other: &InfoHash) -> bool {
+        let v1 = self.v1().is_some() && self.v1() == other.v1();
+        let v2 = self.v2().is_some() && self.v2() == other.v2();
+        v1 || v2
+    }
+
+    /// Parses 40/64 hex digits or 32/52 base32 digits into a v1/v2 hash
+    pub fn parse(data: &str) -> Result<Self, &'static str> {
+        let bytes = match data.len() {
+            40 | 64 => decode_hex(data)?,
+            32 | 52 => decode_base32(data)?,
+            _ => return Err("Info-hash must be 40/64 hex or 32/52 base32 digits"),
+        };
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        match bytes.len() {
+            20 => Ok(InfoHash::V1(bytes.try_into().expect("slice is 20 bytes"))),
+            32 => Ok(InfoHash::V2(bytes.try_into().expect("slice is 32 bytes"))),
+            _ => Err("Info-hash must be 20 or 32 bytes"),
+        }
+    }
+}
+
+impl fmt::Display for InfoHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl FromStr for InfoHash {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        InfoHash::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_HEX: &str = "c12fe1c06bba254a9dc9f519b335aa7c1367a88a";
+    const V1_BASE32: &str = "YEX6DQDLXISUVHOJ6UM3GNNKPQJWPKEK";
+
+    #[test]
+    fn test_v1_formatting_roundtrip() {
+        let hash = InfoHash::parse(V1_HEX).unwrap();
+        assert!(matches!(hash, InfoHash::V1(_)));
+        assert_eq!(hash.to_string(), V1_HEX);
+        assert_eq!(hash.to_base32(), V1_BASE32);
+        assert_eq!(InfoHash::parse(V1_BASE32), Ok(hash));
+        assert_eq!(V1_HEX.to_uppercase().parse::<InfoHash>(), Ok(hash));
+    }
+
+    #[test]
+    fn test_v2_formatting_roundtrip() {
+        let hash = InfoHash::V2([0xab; 32]);
+        assert_eq!(hash.to_hex().len(), 64);
+        assert_eq!(InfoHash::parse(&hash.to_hex()), Ok(hash));
+        assert_eq!(InfoHash::parse(&hash.to_base32()), Ok(hash));
+        assert_eq!(hash.truncated(), [0xab; 20]);
+        assert_eq!(hash.v1(), None);
+    }
+
+    #[test]
+    fn test_hybrid_accessors() {
+        let hash = InfoHash::Hybrid {
+            v1: [1; 20],
+            v2: [2; 32],
+        };
+        assert_eq!(hash.v1(), Some([1; 20]));
+        assert_eq!(hash.v2(), Some([2; 32]));
+        assert_eq!(hash.truncated(), [1; 20]);
+        assert_eq!(hash.to_hex(), "01".repeat(20));
+    }
+
+    #[test]
+    fn test_invalid_strings() {
+        assert!(InfoHash::parse("abc").is_err());
+        assert!(InfoHash::parse(&"zz".repeat(20)).is_err());
+        assert!(InfoHash::from_bytes(&[0; 21]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use super::{error::MetainfoError, lint_severity::LintSeverity};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum LintKind {
+    /// The bytes are not valid bencode
+    Malformed(&'static str),
+    /// Bytes remain after the top-level dictionary
+    TrailingData,
+    /// A string length has leading zeros, so re-encoding changes the bytes
+    NonCanonicalLength,
+    /// Dictionary keys are not in sorted order
+    UnsortedKeys,
+    /// A dictionary key appears more than once; decoders disagree on which value wins
+    DuplicateKey(String),
+    /// A name or path component escapes the download directory or is not a plain name
+    UnsafePath(String),
+    /// A name or path is not valid UTF-8 and has no `.utf-8` alternative
+    InvalidUtf8,
+    /// `pieces` does not hold one hash per piece of the total length
+    PieceCountMismatch {
+        expected: u64,
+        actual: u64,
+    },
+    /// `piece length` is zero
+    ZeroPieceLength,
+    /// `piece length` is not a power of two of at least 16 KiB
+    UnusualPieceLength(u64),
+    /// Two files share the same path
+    DuplicatePath(String),
+    /// The file lengths up to this one add up to more than a bencode integer holds
+    TotalLengthOverflow,
+    EmptyFile,
+    /// A tracker URL that clients are unlikely to reach; holds the reason
+    SuspiciousTracker(&'static str),
+    /// The parser rejects the torrent
+    Invalid(MetainfoError),
+}
+
+impl LintKind {
+    pub fn severity(&self) -> LintSeverity {
+        match self {
+            LintKind::NonCanonicalLength
+            | LintKind::UnsortedKeys
+            | LintKind::UnusualPieceLength(_)
+            | LintKind::EmptyFile
+            | LintKind::SuspiciousTracker(_) => LintSeverity::Warning,
+            LintKind::Malformed(_)
+            | LintKind::TrailingData
+            | LintKind::DuplicateKey(_)
+            | LintKind::UnsafePath(_)
+            | LintKind::InvalidUtf8
+            | LintKind::PieceCountMismatch { .. }
+            | LintKind::ZeroPieceLength
+            | LintKind::DuplicatePath(_)
+            | LintKind::TotalLengthOverflow
+            | LintKind::Invalid(_) => LintSeverity::Error,
+        }
+    }
+}
+
+impl fmt::Display for LintKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintKind::Malformed(reason) => write!(f, "invalid bencode: {reason}"),
+            LintKind::TrailingData => write!(f, "trailing data after metainfo dictionary"),
+            LintKind::NonCanonicalLength => write!(f, "string length has leading zeros"),
+            LintKind::UnsortedKeys => write!(f, "dictionary keys are not sorted"),
+            LintKind::DuplicateKey(key) => write!(f, "duplicate key '{key}'"),
+            LintKind::UnsafePath(component) => {
+                write!(f, "unsafe path component '{component}'")
+            }
+            LintKind::InvalidUtf8 => write!(f, "not valid UTF-8 and no UTF-8 alternative"),
+            LintKind::PieceCountMismatch { expected, actual } => {
+                write!(f, "expected {expected} piece hashes, found {actual}")
+            }
+            LintKind::ZeroPieceLength => write!(f, "piece length is zero"),
+            LintKind::UnusualPieceLength(length) => write!(
+                f,
+                "piece length {length} is not a power of two of at least 16 KiB"
+            ),
+            LintKind::DuplicatePath(path) => write!(f, "duplicate file path '{path}'"),
+            LintKind::TotalLengthOverflow => write!(f, "total length of the files overflows"),
+            LintKind::EmptyFile => write!(f, "file is empty"),
+            LintKind::SuspiciousTracker(reason) => write!(f, "suspicious tracker URL: {reason}"),
+            LintKind::Invalid(err) => write!(f, "{err}"),
+        }
+    }
+}
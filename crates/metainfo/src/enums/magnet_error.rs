@@ -0,0 +1,32 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MagnetError {
+    /// The URI does not start with `magnet:?`
+    NotAMagnet,
+    /// No `xt` parameter holds a BitTorrent info-hash
+    MissingInfoHash,
+    /// Two `xt` parameters of the same kind disagree
+    ConflictingInfoHash,
+    /// A parameter is malformed; holds its key
+    InvalidParameter { key: String, reason: &'static str },
+}
+
+impl fmt::Display for MagnetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MagnetError::NotAMagnet => write!(f, "not a magnet URI"),
+            MagnetError::MissingInfoHash => {
+                write!(f, "magnet URI has no 'urn:btih' or 'urn:btmh' topic")
+            }
+            MagnetError::ConflictingInfoHash => {
+                write!(f, "magnet URI has conflicting info-hashes")
+            }
+            MagnetError::InvalidParameter { key, reason } => {
+                write!(f, "invalid magnet parameter '{key}': {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MagnetError {}
@@ -0,0 +1,20 @@
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum MetaVersion {
+    /// BEP 3: `pieces` and `length`/`files`
+    #[default]
+    V1,
+    /// BEP 52: `meta version` 2 with a `file tree` only
+    V2,
+    /// Both the v1 and the v2 layout, describing the same files
+    Hybrid,
+}
+
+impl MetaVersion {
+    pub fn has_v1(&self) -> bool {
+        matches!(self, MetaVersion::V1 | MetaVersion::Hybrid)
+    }
+
+    pub fn has_v2(&self) -> bool {
+        matches!(self, MetaVersion::V2 | MetaVersion::Hybrid)
+    }
+}
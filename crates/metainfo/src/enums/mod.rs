@@ -0,0 +1,17 @@
+pub mod build_error;
+pub mod error;
+pub mod file_match;
+pub mod file_mode;
+pub mod file_tree;
+pub mod info_hash;
+pub mod lint_kind;
+pub mod lint_severity;
+pub mod magnet_error;
+pub mod meta_version;
+pub mod path_error;
+#[cfg(feature = "signatures")]
+pub mod public_key;
+pub mod signature_error;
+#[cfg(feature = "signatures")]
+pub mod signing_key;
+pub mod verify_error;
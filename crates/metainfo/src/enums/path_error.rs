@@ -0,0 +1,30 @@
+use std::{fmt, path::PathBuf};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PathError {
+    /// Two files map to the same path, or one file maps into another's path as if
+    /// it were a directory; holds their indexes in `Info::files()`
+    Collision {
+        first: usize,
+        second: usize,
+        path: PathBuf,
+    },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Collision {
+                first,
+                second,
+                path,
+            } => write!(
+                f,
+                "files {first} and {second} collide at '{}'",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
@@ -0,0 +1,55 @@
+use super::signature_error::SignatureError;
+use ed25519_dalek::{VerifyingKey, pkcs8::DecodePublicKey};
+use rsa::{RsaPublicKey, pkcs1v15, signature::Verifier};
+use sha2::Sha256;
+use x509_cert::{
+    Certificate,
+    der::{Decode, Encode},
+};
+
+/// A public key that BEP 35 signatures are checked against
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PublicKey {
+    Ed25519(VerifyingKey),
+    /// Checks PKCS#1 v1.5 signatures over SHA-256
+    Rsa(RsaPublicKey),
+}
+
+impl PublicKey {
+    /// Reads an Ed25519 or RSA public key in SPKI DER form
+    pub fn from_public_key_der(der: &[u8]) -> Result<Self, SignatureError> {
+        if let Ok(key) = VerifyingKey::from_public_key_der(der) {
+            return Ok(PublicKey::Ed25519(key));
+        }
+        RsaPublicKey::from_public_key_der(der)
+            .map(PublicKey::Rsa)
+            .map_err(|_| SignatureError::InvalidKey)
+    }
+
+    /// Extracts the key of a DER-encoded X.509 certificate
+    pub fn from_certificate(der: &[u8]) -> Result<Self, SignatureError> {
+        let certificate =
+            Certificate::from_der(der).map_err(|_| SignatureError::InvalidCertificate)?;
+        let key = certificate
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .map_err(|_| SignatureError::InvalidCertificate)?;
+        Self::from_public_key_der(&key).map_err(|_| SignatureError::InvalidCertificate)
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), SignatureError> {
+        let valid = match self {
+            PublicKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|signature| key.verify_strict(message, &signature).is_ok()),
+            PublicKey::Rsa(key) => {
+                pkcs1v15::Signature::try_from(signature).is_ok_and(|signature| {
+                    pkcs1v15::VerifyingKey::<Sha256>::new(key.clone())
+                        .verify(message, &signature)
+                        .is_ok()
+                })
+            }
+        };
+        valid.then_some(()).ok_or(SignatureError::BadSignature)
+    }
+}
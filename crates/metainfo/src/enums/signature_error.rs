@@ -0,0 +1,48 @@
+use super::error::MetainfoError;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SignatureError {
+    /// The key is neither an Ed25519 nor an RSA key in PKCS#8 or SPKI DER form
+    InvalidKey,
+    /// The certificate is missing or is not a DER-encoded X.509 certificate with
+    /// a supported key
+    InvalidCertificate,
+    /// The torrent has no signature from this signer
+    MissingSignature(String),
+    /// The signature does not match the signed data and key
+    BadSignature,
+    /// The key could not produce a signature
+    SigningFailed,
+    Metainfo(MetainfoError),
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::InvalidKey => write!(f, "not an Ed25519 or RSA key"),
+            SignatureError::InvalidCertificate => write!(f, "invalid certificate"),
+            SignatureError::MissingSignature(signer) => {
+                write!(f, "torrent is not signed by '{signer}'")
+            }
+            SignatureError::BadSignature => write!(f, "signature does not match"),
+            SignatureError::SigningFailed => write!(f, "signing failed"),
+            SignatureError::Metainfo(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SignatureError::Metainfo(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<MetainfoError> for SignatureError {
+    fn from(err: MetainfoError) -> Self {
+        SignatureError::Metainfo(err)
+    }
+}
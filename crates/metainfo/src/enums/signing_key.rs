@@ -0,0 +1,41 @@
+use super::{public_key::PublicKey, signature_error::SignatureError};
+use ed25519_dalek::{Signer, pkcs8::DecodePrivateKey};
+use rsa::{RsaPrivateKey, pkcs1v15, signature::SignatureEncoding};
+use sha2::Sha256;
+
+/// A private key for BEP 35 signatures
+#[derive(Debug, Clone)]
+pub enum SigningKey {
+    Ed25519(ed25519_dalek::SigningKey),
+    /// Signs with PKCS#1 v1.5 over SHA-256
+    Rsa(RsaPrivateKey),
+}
+
+impl SigningKey {
+    /// Reads an Ed25519 or RSA private key in PKCS#8 DER form
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, SignatureError> {
+        if let Ok(key) = ed25519_dalek::SigningKey::from_pkcs8_der(der) {
+            return Ok(SigningKey::Ed25519(key));
+        }
+        RsaPrivateKey::from_pkcs8_der(der)
+            .map(SigningKey::Rsa)
+            .map_err(|_| SignatureError::InvalidKey)
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        match self {
+            SigningKey::Ed25519(key) => PublicKey::Ed25519(key.verifying_key()),
+            SigningKey::Rsa(key) => PublicKey::Rsa(key.to_public_key()),
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignatureError> {
+        match self {
+            SigningKey::Ed25519(key) => Ok(key.sign(message).to_vec()),
+            SigningKey::Rsa(key) => pkcs1v15::SigningKey::<Sha256>::new(key.clone())
+                .try_sign(message)
+                .map(|signature| signature.to_vec())
+                .map_err(|_| SignatureError::SigningFailed),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use super::error::MetainfoError;
+use std::{fmt, io};
+
+/// Missing and short files are not errors: their pieces are reported as incomplete
+#[derive(Debug)]
+pub enum VerifyError {
+    Io(io::Error),
+    /// The cancel flag was raised while hashing
+    Cancelled,
+    /// The torrent's piece length exceeds `MAX_PIECE_LENGTH`, so its pieces
+    /// cannot be buffered
+    PieceLengthTooLarge(u64),
+    /// The torrent's pieces cannot be mapped to its files on this platform
+    Layout(MetainfoError),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Io(err) => write!(f, "I/O error: {err}"),
+            VerifyError::Cancelled => write!(f, "verification was cancelled"),
+            VerifyError::PieceLengthTooLarge(length) => {
+                write!(f, "piece length {length} is too large to verify")
+            }
+            VerifyError::Layout(err) => write!(f, "cannot map pieces to files: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifyError::Io(err) => Some(err),
+            VerifyError::Layout(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for VerifyError {
+    fn from(err: io::Error) -> Self {
+        VerifyError::Io(err)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::enums::{error::MetainfoError, info_hash::InfoHash};
+use bencode::decoders::raw::decode_raw_dictionary;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+/// Computes the info-hash of a `.torrent` file from the exact bytes of its info dictionary
+pub fn info_hash(data: &[u8]) -> Result<InfoHash, MetainfoError> {
+    hash_info_bytes(raw_info_bytes(data)?)
+}
+
+/// Returns the info dictionary exactly as it appears in a `.torrent` file
+pub fn raw_info_bytes(data: &[u8]) -> Result<&[u8], MetainfoError> {
+    if !data.starts_with(b"d") {
+        return Err(MetainfoError::NotADictionary("metainfo"));
+    }
+    let (dict, rest) = decode_raw_dictionary(data)?;
+    if !rest.is_empty() {
+        return Err(MetainfoError::TrailingData);
+    }
+
+    let info = dict
+        .get(b"info".as_slice())
+        .ok_or(MetainfoError::MissingKey("info"))?;
+    if !info.starts_with(b"d") {
+        return Err(MetainfoError::NotADictionary("info"));
+    }
+    Ok(info)
+}
+
+/// Hashes an encoded info dictionary, picking v1, v2 or hybrid from its `meta version` and `pieces` keys
+pub fn hash_info_bytes(info: &[u8]) -> Result<InfoHash, MetainfoError> {
+    if !info.starts_with(b"d") {
+        return Err(MetainfoError::NotADictionary("info"));
+    }
+    let (dict, _) = decode_raw_dictionary(info)?;
+
+    let is_v2 = dict.get(b"meta version".as_slice()) == Some(&b"i2e".as_slice());
+    let has_v1_pieces = dict.contains_key(b"pieces".as_slice());
+
+    Ok(match (is_v2, has_v1_pieces) {
+        (false, _) => InfoHash::V1(sha1_digest(info)),
+        (true, false) => InfoHash::V2(sha256_digest(info)),
+        (true, true) => InfoHash::Hybrid {
+            v1: sha1_digest(info),
+            v2: sha256_digest(info),
+        },
+    })
+}
+
+fn sha1_digest(data: &[u8]) -> [u8; 20] {
+    Sha1::digest(data).into()
+}
+
+fn sha256_digest(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::hex::encode_hex;
+
+    #[test]
+    fn test_v1_info_hash() {
+        let data = b"d8:announce3:url4:infod6:lengthi1e4:name1:a12:piece lengthi1e6:pieces0:ee";
+        let hash = info_hash(data).unwrap();
+        // sha1(b"d6:lengthi1e4:name1:a12:piece lengthi1e6:pieces0:e")
+        assert_eq!(
+            hash.to_hex(),
+            encode_hex(&sha1_digest(
+                b"d6:lengthi1e4:name1:a12:piece lengthi1e6:pieces0:e"
+            ))
+        );
+        assert!(matches!(hash, InfoHash::V1(_)));
+    }
+
+    #[test]
+    fn test_known_digest() {
+        assert_eq!(
+            encode_hex(&sha1_digest(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            encode_hex(&sha256_digest(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_hash_uses_original_bytes() {
+        // Unsorted keys would be reordered by a decode/encode roundtrip
+        let data = b"d4:infod4:name1:a6:lengthi1eee";
+        let hash = info_hash(data).unwrap();
+        assert_eq!(hash.v1(), Some(sha1_digest(b"d4:name1:a6:lengthi1ee")));
+    }
+
+    #[test]
+    fn test_v2_and_hybrid_detection() {
+        let v2 = b"d12:meta versioni2e4:name1:ae";
+        assert_eq!(hash_info_bytes(v2), Ok(InfoHash::V2(sha256_digest(v2))));
+
+        let hybrid = b"d12:meta versioni2e4:name1:a6:pieces0:e";
+        assert_eq!(
+            hash_info_bytes(hybrid),
+            Ok(InfoHash::Hybrid {
+                v1: sha1_digest(hybrid),
+                v2: sha256_digest(hybrid),
+            })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(info_hash(b"de"), Err(MetainfoError::MissingKey("info")));
+        assert_eq!(
+            info_hash(b"d4:infoi1ee"),
+            Err(MetainfoError::NotADictionary("info"))
+        );
+        assert_eq!(
+            info_hash(b"le"),
+            Err(MetainfoError::NotADictionary("metainfo"))
+        );
+        assert_eq!(info_hash(b"d4:infodeexx"), Err(MetainfoError::TrailingData));
+    }
+}
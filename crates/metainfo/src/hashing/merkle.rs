@@ -0,0 +1,219 @@
+use sha2::{Digest, Sha256};
+
+/// Leaves of a v2 file tree each hash one block of this many bytes (BEP 52)
+pub const BLOCK_SIZE: usize = 16 * 1024;
+
+pub type Hash = [u8; 32];
+
+/// Merkle tree over SHA-256 hashes, padded with zero leaves up to a power of two
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MerkleTree {
+    /// `layers[0]` holds the padded leaves and the last layer holds the root
+    layers: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn from_leaves(leaves: &[Hash]) -> Self {
+        Self::from_layer(leaves, 0)
+    }
+
+    /// Builds the tree above `nodes`, where each node roots a subtree of `height`;
+    /// missing nodes are filled with the root of an all-zero subtree of that height
+    pub fn from_layer(nodes: &[Hash], height: u32) -> Self {
+        Self::from_padded_layer(nodes, height, nodes.len().max(1).next_power_of_two())
+    }
+
+    /// Like `from_layer`, but pads to `width` nodes, which must be a power of two
+    pub fn from_padded_layer(nodes: &[Hash], height: u32, width: usize) -> Self {
+        debug_assert!(width.is_power_of_two() && width >= nodes.len());
+        let mut layer = nodes.to_vec();
+        layer.resize(width, zero_root(height));
+
+        let mut layers = vec![layer];
+        while layers.last().expect("at least one layer").len() > 1 {
+            let next = layers
+                .last()
+                .expect("at least one layer")
+                .chunks_exact(2)
+                .map(|pair| hash_pair(&pair[0], &pair[1]))
+                .collect();
+            layers.push(next);
+        }
+
+        MerkleTree { layers }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.layers.last().expect("at least one layer")[0]
+    }
+
+    /// Nodes `height` levels above the leaves, including padding
+    pub fn layer(&self, height: usize) -> Option<&[Hash]> {
+        self.layers.get(height).map(Vec::as_slice)
+    }
+
+    /// Sibling hashes from the leaf at `index` up to (but excluding) the root
+    pub fn proof(&self, mut index: usize) -> Option<Vec<Hash>> {
+        if index >= self.layers[0].len() {
+            return None;
+        }
+
+        let mut proof = Vec::with_capacity(self.layers.len() - 1);
+        for layer in &self.layers[..self.layers.len() - 1] {
+            proof.push(layer[index ^ 1]);
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+pub fn hash_block(block: &[u8]) -> Hash {
+    Sha256::digest(block).into()
+}
+
+pub fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Root of a subtree of `height` whose leaves are all zero hashes
+pub fn zero_root(height: u32) -> Hash {
+    (0..height).fold([0; 32], |node, _| hash_pair(&node, &node))
+}
+
+/// Leaf hashes of `data`, one per 16 KiB block; the last block may be shorter
+pub fn block_hashes(data: &[u8]) -> Vec<Hash> {
+    data.chunks(BLOCK_SIZE).map(hash_block).collect()
+}
+
+/// Checks that `leaf` sits at `index` of the tree rooted at `root`
+pub fn verify_proof(leaf: &Hash, mut index: usize, proof: &[Hash], root: &Hash) -> bool {
+    let mut node = *leaf;
+    for sibling in proof {
+        node = if index.is_multiple_of(2) {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+        index /= 2;
+    }
+    index == 0 && node == *root
+}
+
+/// Checks a block of data against `root` using the sibling hashes from `proof`
+pub fn verify_block(block: &[u8], index: usize, proof: &[Hash], root: &Hash) -> bool {
+    verify_proof(&hash_block(block), index, proof, root)
+}
+
+/// Hash of one piece of a file: the file's own root when the file fits in one piece,
+/// otherwise its node in the piece layer, padded to a full piece of blocks
+pub fn piece_node(data: &[u8], file_length: u64, piece_length: u64) -> Hash {
+    let leaves = block_hashes(data);
+    if file_length <= piece_length {
+        return MerkleTree::from_leaves(&leaves).root();
+    }
+    let blocks_per_piece = (piece_length / BLOCK_SIZE as u64) as usize;
+    MerkleTree::from_padded_layer(&leaves, 0, blocks_per_piece).root()
+}
+
+/// Number of tree levels between a leaf and a piece-layer node
+pub fn piece_layer_height(piece_length: u64) -> u32 {
+    (piece_length / BLOCK_SIZE as u64).max(1).ilog2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(n: u8) -> Hash {
+        hash_block(&[n])
+    }
+
+    #[test]
+    fn test_single_leaf_is_root() {
+        let tree = MerkleTree::from_leaves(&[leaf(1)]);
+        assert_eq!(tree.root(), leaf(1));
+        assert_eq!(tree.proof(0), Some(vec![]));
+    }
+
+    #[test]
+    fn test_root_pads_with_zero_leaves() {
+        let tree = MerkleTree::from_leaves(&[leaf(1), leaf(2), leaf(3)]);
+        let expected = hash_pair(
+            &hash_pair(&leaf(1), &leaf(2)),
+            &hash_pair(&leaf(3), &[0; 32]),
+        );
+        assert_eq!(tree.root(), expected);
+        assert_eq!(tree.layer(0).unwrap().len(), 4);
+        assert_eq!(tree.layer(1).unwrap().len(), 2);
+        assert_eq!(tree.layer(3), None);
+    }
+
+    #[test]
+    fn test_layer_padding_uses_zero_subtrees() {
+        let leaves = [leaf(1), leaf(2), leaf(3), leaf(4), leaf(5)];
+        let tree = MerkleTree::from_leaves(&leaves);
+        let layer = &tree.layer(1).unwrap()[..3];
+        assert_eq!(MerkleTree::from_layer(layer, 1).root(), tree.root());
+        assert_eq!(tree.layer(1).unwrap()[3], zero_root(1));
+    }
+
+    #[test]
+    fn test_padded_layer() {
+        let tree = MerkleTree::from_padded_layer(&[leaf(1)], 0, 4);
+        assert_eq!(tree.layer(0).unwrap().len(), 4);
+        assert_eq!(
+            tree.root(),
+            hash_pair(&hash_pair(&leaf(1), &[0; 32]), &zero_root(1))
+        );
+    }
+
+    #[test]
+    fn test_proofs_verify() {
+        let leaves: Vec<Hash> = (0..7).map(leaf).collect();
+        let tree = MerkleTree::from_leaves(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            assert_eq!(proof.len(), 3);
+            assert!(verify_proof(leaf, index, &proof, &tree.root()));
+            assert!(!verify_proof(leaf, index ^ 1, &proof, &tree.root()));
+        }
+        assert_eq!(tree.proof(8), None);
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let data = vec![7u8; BLOCK_SIZE * 2 + 100];
+        let leaves = block_hashes(&data);
+        assert_eq!(leaves.len(), 3);
+
+        let tree = MerkleTree::from_leaves(&leaves);
+        let block = &data[BLOCK_SIZE * 2..];
+        let proof = tree.proof(2).unwrap();
+        assert!(verify_block(block, 2, &proof, &tree.root()));
+        assert!(!verify_block(&block[1..], 2, &proof, &tree.root()));
+    }
+
+    #[test]
+    fn test_piece_node() {
+        let data = vec![1u8; BLOCK_SIZE + 1];
+        let leaves = block_hashes(&data);
+        assert_eq!(
+            piece_node(&data, data.len() as u64, 4 * BLOCK_SIZE as u64),
+            hash_pair(&leaves[0], &leaves[1])
+        );
+        assert_eq!(
+            piece_node(&data, 10 * BLOCK_SIZE as u64, 4 * BLOCK_SIZE as u64),
+            hash_pair(&hash_pair(&leaves[0], &leaves[1]), &zero_root(1))
+        );
+    }
+
+    #[test]
+    fn test_piece_layer_height() {
+        assert_eq!(piece_layer_height(16 * 1024), 0);
+        assert_eq!(piece_layer_height(64 * 1024), 2);
+        assert_eq!(zero_root(0), [0; 32]);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod info_hash;
+pub mod merkle;
+pub mod parallel;
@@ -0,0 +1,109 @@
+use crate::models::progress::Progress;
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+pub(crate) type ProgressCallback = Box<dyn Fn(Progress) + Send + Sync>;
+
+/// Threads, cancellation and progress shared by every pass of one hashing run,
+/// so a run made of several passes reports one combined total
+pub(crate) struct HashingRun<'a> {
+    threads: usize,
+    cancel: &'a AtomicBool,
+    progress: Option<&'a ProgressCallback>,
+    piece_count: usize,
+    total_bytes: u64,
+    pieces_done: AtomicUsize,
+    bytes_done: AtomicU64,
+}
+
+impl<'a> HashingRun<'a> {
+    pub(crate) fn new(
+        threads: usize,
+        cancel: &'a AtomicBool,
+        progress: Option<&'a ProgressCallback>,
+        piece_count: usize,
+        total_bytes: u64,
+    ) -> Self {
+        HashingRun {
+            threads,
+            cancel,
+            progress,
+            piece_count,
+            total_bytes,
+            pieces_done: AtomicUsize::new(0),
+            bytes_done: AtomicU64::new(0),
+        }
+    }
+
+    /// Runs `job` for every index in `0..jobs` across the run's threads, returning
+    /// results in index order; `job` also returns how many bytes it hashed
+    pub(crate) fn run<T, W, E>(
+        &self,
+        jobs: usize,
+        worker_state: impl Fn() -> W + Sync,
+        job: impl Fn(&mut W, usize) -> Result<(T, u64), E> + Sync,
+        cancelled: impl Fn() -> E + Sync,
+    ) -> Result<Vec<T>, E>
+    where
+        T: Send,
+        E: Send,
+    {
+        let results = Mutex::new((0..jobs).map(|_| None).collect::<Vec<Option<T>>>());
+        let next_job = AtomicUsize::new(0);
+        // Stops the remaining workers once one of them has failed
+        let failed = AtomicBool::new(false);
+
+        let worker = || -> Result<(), E> {
+            let mut local = worker_state();
+
+            loop {
+                if self.cancel.load(Ordering::Relaxed) {
+                    return Err(cancelled());
+                }
+                if failed.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+
+                let index = next_job.fetch_add(1, Ordering::Relaxed);
+                if index >= jobs {
+                    return Ok(());
+                }
+
+                let (result, length) = job(&mut local, index).inspect_err(|_| {
+                    failed.store(true, Ordering::Relaxed);
+                })?;
+                results.lock().expect("result table poisoned")[index] = Some(result);
+
+                let progress = Progress {
+                    pieces_done: self.pieces_done.fetch_add(1, Ordering::Relaxed) + 1,
+                    piece_count: self.piece_count,
+                    bytes_done: self.bytes_done.fetch_add(length, Ordering::Relaxed) + length,
+                    total_bytes: self.total_bytes,
+                };
+                if let Some(callback) = self.progress {
+                    callback(progress);
+                }
+            }
+        };
+
+        let threads = self.threads.min(jobs).max(1);
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads).map(|_| scope.spawn(worker)).collect();
+            handles
+                .into_iter()
+                .try_for_each(|handle| handle.join().expect("hashing thread panicked"))
+        })?;
+
+        Ok(results
+            .into_inner()
+            .expect("result table poisoned")
+            .into_iter()
+            .map(|result| result.expect("every job ran"))
+            .collect())
+    }
+}
@@ -0,0 +1,12 @@
+pub mod builders;
+pub mod editors;
+pub mod encoding;
+pub mod enums;
+pub mod hashing;
+pub mod linters;
+pub mod models;
+pub mod parsers;
+pub mod reuse;
+pub mod storage;
+pub mod verifiers;
+pub mod web_seeds;
@@ -0,0 +1,99 @@
+/// One bit per piece, most significant bit first, as in the BEP 3 `bitfield` message
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Bitfield {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bitfield {
+    pub fn new(len: usize) -> Self {
+        Bitfield {
+            bytes: vec![0; len.div_ceil(8)],
+            len,
+        }
+    }
+
+    /// Rejects inputs of the wrong size or with spare bits set after the last piece
+    pub fn from_bytes(bytes: &[u8], len: usize) -> Result<Self, &'static str> {
+        if bytes.len() != len.div_ceil(8) {
+            return Err("Bitfield has the wrong number of bytes");
+        }
+        let spare = bytes.len() * 8 - len;
+        if spare > 0 && bytes[bytes.len() - 1] & ((1 << spare) - 1) != 0 {
+            return Err("Bitfield has spare bits set");
+        }
+        Ok(Bitfield {
+            bytes: bytes.to_vec(),
+            len,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        index < self.len && self.bytes[index / 8] & (0x80 >> (index % 8)) != 0
+    }
+
+    /// Panics if `index` is out of range
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(index < self.len, "bit index out of range");
+        match value {
+            true => self.bytes[index / 8] |= 0x80 >> (index % 8),
+            false => self.bytes[index / 8] &= !(0x80 >> (index % 8)),
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bytes
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn all(&self) -> bool {
+        self.count_ones() == self.len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|index| self.get(index))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get() {
+        let mut bitfield = Bitfield::new(10);
+        bitfield.set(0, true);
+        bitfield.set(9, true);
+        assert_eq!(bitfield.as_bytes(), &[0x80, 0x40]);
+        assert!(bitfield.get(9));
+        assert!(!bitfield.get(10));
+        assert_eq!(bitfield.count_ones(), 2);
+
+        bitfield.set(0, false);
+        assert_eq!(bitfield.iter().filter(|bit| *bit).count(), 1);
+        assert!(!bitfield.all());
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let bitfield = Bitfield::from_bytes(&[0xff, 0xc0], 10).unwrap();
+        assert!(bitfield.all());
+        assert!(Bitfield::from_bytes(&[0xff, 0xe0], 10).is_err());
+        assert!(Bitfield::from_bytes(&[0xff], 10).is_err());
+        assert!(Bitfield::new(0).all());
+    }
+}
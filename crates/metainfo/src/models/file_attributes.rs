@@ -0,0 +1,55 @@
+/// BEP 47 `attr` flags of a file
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct FileAttributes {
+    /// `p`: alignment padding whose content is all zeros
+    pub padding: bool,
+    /// `x`
+    pub executable: bool,
+    /// `h`
+    pub hidden: bool,
+    /// `l`: the file is a symlink to its `symlink path`
+    pub symlink: bool,
+}
+
+impl FileAttributes {
+    /// Unknown flags are ignored, as BEP 47 requires
+    pub fn parse(attr: &[u8]) -> Self {
+        FileAttributes {
+            padding: attr.contains(&b'p'),
+            executable: attr.contains(&b'x'),
+            hidden: attr.contains(&b'h'),
+            symlink: attr.contains(&b'l'),
+        }
+    }
+
+    /// The `attr` string, empty when no flag is set
+    pub fn to_attr_string(&self) -> String {
+        [
+            (self.padding, 'p'),
+            (self.executable, 'x'),
+            (self.hidden, 'h'),
+            (self.symlink, 'l'),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == FileAttributes::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let attributes = FileAttributes::parse(b"lxz");
+        assert!(attributes.symlink && attributes.executable);
+        assert!(!attributes.padding && !attributes.hidden);
+        assert_eq!(attributes.to_attr_string(), "xl");
+        assert!(FileAttributes::parse(b"").is_empty());
+    }
+}
@@ -0,0 +1,17 @@
+use super::file_attributes::FileAttributes;
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FileEntry {
+    pub length: u64,
+    /// Path components relative to the torrent's root directory
+    pub path: Vec<String>,
+    pub attributes: FileAttributes,
+    /// BEP 47 link target relative to the torrent's root directory, for symlinks
+    pub symlink_path: Option<Vec<String>>,
+}
+
+impl FileEntry {
+    pub fn is_padding(&self) -> bool {
+        self.attributes.padding
+    }
+}
@@ -0,0 +1,409 @@
+use super::{file_entry::FileEntry, file_range::FileRange, info::Info, metainfo::Metainfo};
+use crate::{
+    enums::{error::MetainfoError, meta_version::MetaVersion},
+    hashing::merkle::BLOCK_SIZE,
+};
+use std::ops::Range;
+
+/// Maps between pieces and the files they cover.
+///
+/// v1 and hybrid torrents lay their files, padding included, end to end. v2-only
+/// torrents start every file on a piece boundary, so their pieces never span files
+/// and the last piece of a file may be short.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FileLayout {
+    piece_length: u64,
+    files: Vec<FileEntry>,
+    /// Bytes of each file in the torrent's byte space
+    spans: Vec<Range<u64>>,
+    /// End of the last file in the torrent's byte space
+    end: u64,
+    piece_count: usize,
+}
+
+impl FileLayout {
+    pub fn new(metainfo: &Metainfo) -> Result<Self, MetainfoError> {
+        Self::from_info(&metainfo.info)
+    }
+
+    /// Parsed torrents always fit: their total length fits in an `i64`. Offsets
+    /// of hand-built `Info`s, and of v2 files aligned past `u64::MAX`, saturate
+    /// rather than overflow. Fails when pieces or the blocks of a piece cannot
+    /// be counted in a `usize`, as on 32-bit targets with huge torrents
+    pub fn from_info(info: &Info) -> Result<Self, MetainfoError> {
+        let piece_length = info.piece_length.max(1);
+        let aligned = info.meta_version == MetaVersion::V2;
+        let files = info.all_files();
+
+        let mut spans = Vec::with_capacity(files.len());
+        let mut end: u64 = 0;
+        for file in &files {
+            let offset = match aligned && file.length > 0 {
+                true => end
+                    .checked_next_multiple_of(piece_length)
+                    .unwrap_or(u64::MAX),
+                false => end,
+            };
+            end = offset.saturating_add(file.length);
+            spans.push(offset..end);
+        }
+
+        let piece_count = usize::try_from(end.div_ceil(piece_length)).map_err(|_| {
+            MetainfoError::InvalidValue {
+                key: "files",
+                reason: "too many pieces for this platform",
+            }
+        })?;
+        usize::try_from(piece_length.div_ceil(BLOCK_SIZE as u64)).map_err(|_| {
+            MetainfoError::InvalidValue {
+                key: "piece length",
+                reason: "too many blocks per piece for this platform",
+            }
+        })?;
+
+        Ok(FileLayout {
+            piece_length,
+            files,
+            spans,
+            end,
+            piece_count,
+        })
+    }
+
+    pub fn piece_length(&self) -> u64 {
+        self.piece_length
+    }
+
+    pub fn piece_count(&self) -> usize {
+        self.piece_count
+    }
+
+    /// Files in torrent order, including BEP 47 padding
+    pub fn files(&self) -> &[FileEntry] {
+        &self.files
+    }
+
+    /// Where `file` starts in the torrent's byte space
+    pub fn file_offset(&self, file: usize) -> Option<u64> {
+        self.spans.get(file).map(|span| span.start)
+    }
+
+    /// Bytes of data in `piece`: the piece length except for a short last piece,
+    /// and zero for out-of-range pieces
+    pub fn piece_size(&self, piece: usize) -> u64 {
+        self.ranges(piece)
+            .map(|file_range| file_range.range.end - file_range.range.start)
+            .sum()
+    }
+
+    /// The parts of files that make up `piece`, in order. Zero-length files are
+    /// left out; padding files are included
+    pub fn piece_to_file_ranges(&self, piece: usize) -> Vec<FileRange> {
+        self.ranges(piece).collect()
+    }
+
+    /// Pieces holding bytes of `file`; empty for zero-length and unknown files
+    pub fn file_to_piece_range(&self, file: usize) -> Range<usize> {
+        let Some(span) = self.spans.get(file) else {
+            return 0..0;
+        };
+        let first = to_usize(span.start / self.piece_length);
+        match span.is_empty() {
+            true => first..first,
+            false => first..to_usize(span.end.div_ceil(self.piece_length)),
+        }
+    }
+
+    /// Bytes of 16 KiB `block` within `piece`, relative to the start of the piece;
+    /// the last block of a piece may be short
+    pub fn byte_range(&self, piece: usize, block: usize) -> Option<Range<u64>> {
+        let size = self.piece_size(piece);
+        let start = (block as u64).saturating_mul(BLOCK_SIZE as u64);
+        (start < size).then(|| start..size.min(start + BLOCK_SIZE as u64))
+    }
+
+    pub fn block_count(&self, piece: usize) -> usize {
+        to_usize(self.piece_size(piece).div_ceil(BLOCK_SIZE as u64))
+    }
+
+    fn ranges(&self, piece: usize) -> impl Iterator<Item = FileRange> + '_ {
+        let start = (piece as u64).saturating_mul(self.piece_length);
+        let end = start.saturating_add(self.piece_length).min(self.end);
+
+        // File ends never decrease, so the first file reaching into the piece can be
+        // found by binary search
+        let first = self.spans.partition_point(|span| span.end <= start);
+
+        self.spans[first..]
+            .iter()
+            .enumerate()
+            .take_while(move |(_, span)| span.start < end)
+            .filter(|(_, span)| !span.is_empty())
+            .map(move |(index, span)| {
+                let from = start.max(span.start);
+                let to = end.min(span.end);
+                FileRange {
+                    file: first + index,
+                    range: from - span.start..to - span.start,
+                    piece_offset: from - start,
+                }
+            })
+    }
+}
+
+/// Converts a piece or block count, which `from_info` has checked fits
+fn to_usize(count: u64) -> usize {
+    usize::try_from(count).unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::file_mode::FileMode, hashing::merkle::BLOCK_SIZE,
+        models::file_attributes::FileAttributes,
+    };
+
+    const PADDING: u64 = u64::MAX;
+
+    /// Multi-file info with one file per length; `PADDING` marks a 6-byte pad file
+    fn info(piece_length: u64, lengths: &[u64], meta_version: MetaVersion) -> Info {
+        let files = lengths
+            .iter()
+            .enumerate()
+            .map(|(index, &length)| FileEntry {
+                length: if length == PADDING { 6 } else { length },
+                path: vec![index.to_string()],
+                attributes: FileAttributes {
+                    padding: length == PADDING,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect();
+        Info {
+            name: "dir".to_string(),
+            piece_length,
+            pieces: Vec::new(),
+            private: false,
+            mode: FileMode::Multiple { files },
+            meta_version,
+            file_tree: None,
+            similar: Vec::new(),
+            collections: Vec::new(),
+        }
+    }
+
+    fn range(file: usize, range: Range<u64>, piece_offset: u64) -> FileRange {
+        FileRange {
+            file,
+            range,
+            piece_offset,
+        }
+    }
+
+    #[test]
+    fn test_pieces_spanning_files() {
+        let layout = FileLayout::from_info(&info(16, &[10, 20, 5], MetaVersion::V1)).unwrap();
+        assert_eq!(layout.piece_count(), 3);
+        assert_eq!(
+            layout.piece_to_file_ranges(0),
+            vec![range(0, 0..10, 0), range(1, 0..6, 10)]
+        );
+        assert_eq!(
+            layout.piece_to_file_ranges(1),
+            vec![range(1, 6..20, 0), range(2, 0..2, 14)]
+        );
+        assert_eq!(layout.piece_to_file_ranges(2), vec![range(2, 2..5, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(3), vec![]);
+
+        assert_eq!(layout.file_to_piece_range(0), 0..1);
+        assert_eq!(layout.file_to_piece_range(1), 0..2);
+        assert_eq!(layout.file_to_piece_range(2), 1..3);
+        assert_eq!(layout.file_to_piece_range(3), 0..0);
+        assert_eq!(layout.file_offset(2), Some(30));
+
+        assert_eq!(layout.piece_size(0), 16);
+        assert_eq!(layout.piece_size(2), 3);
+        assert_eq!(layout.piece_size(3), 0);
+    }
+
+    #[test]
+    fn test_files_on_piece_boundaries() {
+        let layout = FileLayout::from_info(&info(16, &[16, 32, 16], MetaVersion::V1)).unwrap();
+        assert_eq!(layout.piece_count(), 4);
+        assert_eq!(layout.piece_to_file_ranges(0), vec![range(0, 0..16, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(1), vec![range(1, 0..16, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(2), vec![range(1, 16..32, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(3), vec![range(2, 0..16, 0)]);
+        assert_eq!(layout.file_to_piece_range(0), 0..1);
+        assert_eq!(layout.file_to_piece_range(1), 1..3);
+        assert_eq!(layout.file_to_piece_range(2), 3..4);
+    }
+
+    #[test]
+    fn test_zero_length_files() {
+        let layout =
+            FileLayout::from_info(&info(16, &[0, 16, 0, 0, 8, 0], MetaVersion::V1)).unwrap();
+        assert_eq!(layout.piece_count(), 2);
+        assert_eq!(layout.piece_to_file_ranges(0), vec![range(1, 0..16, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(1), vec![range(4, 0..8, 0)]);
+        for file in [0, 2, 3] {
+            assert!(layout.file_to_piece_range(file).is_empty());
+        }
+        assert_eq!(layout.file_to_piece_range(0), 0..0);
+        assert_eq!(layout.file_to_piece_range(2), 1..1);
+        assert_eq!(layout.file_to_piece_range(5), 1..1);
+
+        let empty = FileLayout::from_info(&info(16, &[0, 0], MetaVersion::V1)).unwrap();
+        assert_eq!(empty.piece_count(), 0);
+        assert_eq!(empty.piece_to_file_ranges(0), vec![]);
+        assert_eq!(empty.byte_range(0, 0), None);
+    }
+
+    #[test]
+    fn test_padding_is_included() {
+        let layout =
+            FileLayout::from_info(&info(16, &[10, PADDING, 3], MetaVersion::Hybrid)).unwrap();
+        assert_eq!(
+            layout.piece_to_file_ranges(0),
+            vec![range(0, 0..10, 0), range(1, 0..6, 10)]
+        );
+        assert!(layout.files()[1].is_padding());
+        assert_eq!(layout.piece_to_file_ranges(1), vec![range(2, 0..3, 0)]);
+        assert_eq!(layout.file_to_piece_range(2), 1..2);
+    }
+
+    #[test]
+    fn test_v2_files_start_new_pieces() {
+        let layout = FileLayout::from_info(&info(16, &[10, 0, 20, 16], MetaVersion::V2)).unwrap();
+        assert_eq!(layout.piece_count(), 4);
+        assert_eq!(layout.piece_to_file_ranges(0), vec![range(0, 0..10, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(1), vec![range(2, 0..16, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(2), vec![range(2, 16..20, 0)]);
+        assert_eq!(layout.piece_to_file_ranges(3), vec![range(3, 0..16, 0)]);
+        assert_eq!(layout.piece_size(0), 10);
+        assert_eq!(layout.piece_size(2), 4);
+        assert_eq!(layout.file_offset(2), Some(16));
+        assert_eq!(layout.file_to_piece_range(2), 1..3);
+        assert_eq!(layout.file_to_piece_range(3), 3..4);
+    }
+
+    #[test]
+    fn test_overflowing_lengths_saturate() {
+        let half = u64::MAX / 2;
+        let layout = FileLayout::from_info(&info(1, &[half, half, 10], MetaVersion::V1)).unwrap();
+        assert_eq!(layout.file_offset(2), Some(u64::MAX - 1));
+        assert_eq!(layout.end, u64::MAX);
+
+        let layout = FileLayout::from_info(&info(
+            1 << 62,
+            &[1, 1 << 62, 1 << 62, 1 << 62, 1],
+            MetaVersion::V2,
+        ))
+        .unwrap();
+        assert_eq!(layout.file_offset(4), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_counts_must_fit_usize() {
+        let narrow = cfg!(target_pointer_width = "32");
+        let many_pieces = FileLayout::from_info(&info(16, &[1 << 40], MetaVersion::V1));
+        assert_eq!(
+            many_pieces.map(|layout| layout.piece_count()).ok(),
+            (!narrow).then_some(1 << 36)
+        );
+
+        let many_blocks = FileLayout::from_info(&info(1 << 50, &[1], MetaVersion::V1));
+        assert_eq!(
+            many_blocks.err(),
+            narrow.then_some(MetainfoError::InvalidValue {
+                key: "piece length",
+                reason: "too many blocks per piece for this platform",
+            })
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let piece_length = 4 * BLOCK_SIZE as u64;
+        let total = piece_length + BLOCK_SIZE as u64 + 100;
+        let layout = FileLayout::from_info(&info(piece_length, &[total], MetaVersion::V1)).unwrap();
+        let block = BLOCK_SIZE as u64;
+
+        assert_eq!(layout.block_count(0), 4);
+        assert_eq!(layout.byte_range(0, 0), Some(0..block));
+        assert_eq!(layout.byte_range(0, 3), Some(3 * block..4 * block));
+        assert_eq!(layout.byte_range(0, 4), None);
+
+        assert_eq!(layout.block_count(1), 2);
+        assert_eq!(layout.byte_range(1, 1), Some(block..block + 100));
+        assert_eq!(layout.byte_range(1, 2), None);
+        assert_eq!(layout.byte_range(2, 0), None);
+        assert_eq!(layout.block_count(2), 0);
+    }
+
+    #[test]
+    fn test_single_file() {
+        let info = Info {
+            mode: FileMode::Single { length: 40 },
+            ..info(16, &[], MetaVersion::V1)
+        };
+        let layout = FileLayout::from_info(&info).unwrap();
+        assert_eq!(layout.files()[0].path, vec!["dir".to_string()]);
+        assert_eq!(layout.piece_to_file_ranges(2), vec![range(0, 32..40, 0)]);
+        assert_eq!(layout.file_to_piece_range(0), 0..3);
+    }
+
+    /// Compares every piece and file against a byte-by-byte mapping
+    #[test]
+    fn test_matches_byte_by_byte_mapping() {
+        let layouts: &[(&[u64], u64)] = &[
+            (&[1, 2, 3, 4, 5, 6, 7, 8], 4),
+            (&[0, 7, 0, 9, 0, 0, 16, 1, 0], 8),
+            (&[33], 32),
+            (&[31, 1, 0, 32], 32),
+            (&[5, PADDING, 4, 0, PADDING, 12], 6),
+        ];
+        for &(lengths, piece_length) in layouts {
+            let layout =
+                FileLayout::from_info(&info(piece_length, lengths, MetaVersion::V1)).unwrap();
+
+            let mut owners = Vec::new();
+            for (file, entry) in layout.files().iter().enumerate() {
+                owners.extend((0..entry.length).map(|byte| (file, byte)));
+            }
+            assert_eq!(
+                layout.piece_count(),
+                owners.len().div_ceil(piece_length as usize)
+            );
+
+            let mut pieces_of = vec![Vec::new(); layout.files().len()];
+            for (piece, chunk) in owners.chunks(piece_length as usize).enumerate() {
+                let mut expected: Vec<FileRange> = Vec::new();
+                for (offset, &(file, byte)) in chunk.iter().enumerate() {
+                    match expected.last_mut() {
+                        Some(last) if last.file == file => last.range.end = byte + 1,
+                        _ => expected.push(range(file, byte..byte + 1, offset as u64)),
+                    }
+                    if pieces_of[file].last() != Some(&piece) {
+                        pieces_of[file].push(piece);
+                    }
+                }
+                assert_eq!(
+                    layout.piece_to_file_ranges(piece),
+                    expected,
+                    "{lengths:?} piece {piece}"
+                );
+                assert_eq!(layout.piece_size(piece), chunk.len() as u64);
+            }
+            for (file, pieces) in pieces_of.iter().enumerate() {
+                if let (Some(first), Some(last)) = (pieces.first(), pieces.last()) {
+                    assert_eq!(layout.file_to_piece_range(file), *first..last + 1);
+                } else {
+                    assert!(layout.file_to_piece_range(file).is_empty());
+                }
+            }
+        }
+    }
+}
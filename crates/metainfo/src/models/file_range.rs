@@ -0,0 +1,12 @@
+use std::ops::Range;
+
+/// The part of one file that falls inside a piece
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FileRange {
+    /// Index into `FileLayout::files()`, which includes padding files
+    pub file: usize,
+    /// Bytes of the file
+    pub range: Range<u64>,
+    /// Where the bytes start within the piece
+    pub piece_offset: u64,
+}
@@ -0,0 +1,146 @@
+use super::file_entry::FileEntry;
+use crate::enums::{
+    file_mode::FileMode, file_tree::FileTree, info_hash::InfoHash, meta_version::MetaVersion,
+};
+
+/// Largest piece length the builder produces and the verifier accepts; each
+/// hashing thread holds a buffer of one piece
+pub const MAX_PIECE_LENGTH: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Info {
+    pub name: String,
+    pub piece_length: u64,
+    /// SHA-1 hash of every piece, in order; empty for v2-only torrents
+    pub pieces: Vec<[u8; 20]>,
+    pub private: bool,
+    /// The v1 file layout, derived from `file_tree` for v2-only torrents
+    pub mode: FileMode,
+    pub meta_version: MetaVersion,
+    /// BEP 52 `file tree`, present for v2 and hybrid torrents
+    pub file_tree: Option<FileTree>,
+    /// BEP 38 torrents likely to share files with this one
+    pub similar: Vec<InfoHash>,
+    /// BEP 38 collection names grouping related torrents
+    pub collections: Vec<String>,
+}
+
+impl Info {
+    /// Sum of all file lengths; parsing guarantees it fits in an `i64`
+    pub fn total_length(&self) -> u64 {
+        match &self.mode {
+            FileMode::Single { length } => *length,
+            FileMode::Multiple { files } => files.iter().map(|file| file.length).sum(),
+        }
+    }
+
+    pub fn piece_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    pub fn is_multi_file(&self) -> bool {
+        matches!(self.mode, FileMode::Multiple { .. })
+    }
+
+    /// Files in torrent order without padding files; a single-file torrent yields
+    /// one entry whose path is `name`
+    pub fn files(&self) -> Vec<FileEntry> {
+        let mut files = self.all_files();
+        files.retain(|file| !file.is_padding());
+        files
+    }
+
+    /// Files in torrent order including BEP 47 padding, as laid out in the v1 pieces
+    pub fn all_files(&self) -> Vec<FileEntry> {
+        match &self.mode {
+            FileMode::Single { length } => vec![FileEntry {
+                length: *length,
+                path: vec![self.name.clone()],
+                ..Default::default()
+            }],
+            FileMode::Multiple { files } => files.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::file_attributes::FileAttributes;
+
+    fn multi_file_info() -> Info {
+        Info {
+            name: "dir".to_string(),
+            piece_length: 16,
+            pieces: vec![[0; 20]; 2],
+            private: false,
+            mode: FileMode::Multiple {
+                files: vec![
+                    FileEntry {
+                        length: 10,
+                        path: vec!["a".to_string()],
+                        ..Default::default()
+                    },
+                    FileEntry {
+                        length: 12,
+                        path: vec!["sub".to_string(), "b".to_string()],
+                        ..Default::default()
+                    },
+                ],
+            },
+            meta_version: MetaVersion::V1,
+            file_tree: None,
+            similar: Vec::new(),
+            collections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_multi_file_helpers() {
+        let info = multi_file_info();
+        assert!(info.is_multi_file());
+        assert_eq!(info.total_length(), 22);
+        assert_eq!(info.piece_count(), 2);
+        assert_eq!(info.files().len(), 2);
+    }
+
+    #[test]
+    fn test_padding_is_hidden() {
+        let mut info = multi_file_info();
+        if let FileMode::Multiple { files } = &mut info.mode {
+            files.insert(
+                1,
+                FileEntry {
+                    length: 6,
+                    path: vec![".pad".to_string(), "6".to_string()],
+                    attributes: FileAttributes {
+                        padding: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            );
+        }
+        assert_eq!(info.files(), multi_file_info().files());
+        assert_eq!(info.all_files().len(), 3);
+        assert_eq!(info.total_length(), 28);
+    }
+
+    #[test]
+    fn test_single_file_helpers() {
+        let info = Info {
+            mode: FileMode::Single { length: 5 },
+            ..multi_file_info()
+        };
+        assert!(!info.is_multi_file());
+        assert_eq!(info.total_length(), 5);
+        assert_eq!(
+            info.files(),
+            vec![FileEntry {
+                length: 5,
+                path: vec!["dir".to_string()],
+                ..Default::default()
+            }]
+        );
+    }
+}
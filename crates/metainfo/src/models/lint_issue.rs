@@ -0,0 +1,30 @@
+use crate::enums::{lint_kind::LintKind, lint_severity::LintSeverity};
+use std::fmt;
+
+/// One problem found by `lint`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LintIssue {
+    pub severity: LintSeverity,
+    /// Where the problem is, such as `info.files[2].path`; empty for the whole file
+    pub location: String,
+    pub kind: LintKind,
+}
+
+impl LintIssue {
+    pub fn new(location: impl Into<String>, kind: LintKind) -> Self {
+        LintIssue {
+            severity: kind.severity(),
+            location: location.into(),
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location.is_empty() {
+            true => write!(f, "{}: {}", self.severity, self.kind),
+            false => write!(f, "{} at {}: {}", self.severity, self.location, self.kind),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use super::metainfo::Metainfo;
+use std::path::PathBuf;
+
+/// A torrent already on disk, with the download directory holding its data
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LocalTorrent {
+    pub metainfo: Metainfo,
+    pub root: PathBuf,
+}
@@ -0,0 +1,115 @@
+use super::{metainfo::Metainfo, tracker_list::TrackerList};
+use crate::{
+    encoding::{hex::encode_hex, percent::encode_percent},
+    enums::{info_hash::InfoHash, magnet_error::MagnetError},
+    parsers::magnet::parse_magnet,
+};
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+/// Multihash prefix of a SHA-256 digest: function code 0x12, length 32
+pub const SHA256_MULTIHASH_PREFIX: &str = "1220";
+
+/// A BEP 9 magnet URI
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MagnetLink {
+    /// From `xt=urn:btih:` and/or `xt=urn:btmh:`
+    pub info_hash: InfoHash,
+    /// `dn`
+    pub display_name: Option<String>,
+    /// `tr`, in order
+    pub trackers: Vec<String>,
+    /// `ws`
+    pub web_seeds: Vec<String>,
+    /// `x.pe`: `host:port`, `ipv4:port` or `[ipv6]:port`
+    pub peers: Vec<String>,
+    /// `so`: indices of the files to download
+    pub select_only: Vec<RangeInclusive<usize>>,
+    /// Parameters this type does not know, kept in their original order
+    pub extra: Vec<(String, String)>,
+}
+
+impl MagnetLink {
+    pub fn new(info_hash: InfoHash) -> Self {
+        MagnetLink {
+            info_hash,
+            display_name: None,
+            trackers: Vec::new(),
+            web_seeds: Vec::new(),
+            peers: Vec::new(),
+            select_only: Vec::new(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Takes the trackers of every tier, without duplicates
+    pub fn from_metainfo(metainfo: &Metainfo) -> Self {
+        let mut trackers: Vec<String> = Vec::new();
+        for tracker in TrackerList::from_metainfo(metainfo).iter() {
+            if !trackers.iter().any(|known| known == tracker) {
+                trackers.push(tracker.to_string());
+            }
+        }
+
+        MagnetLink {
+            display_name: Some(metainfo.info.name.clone()),
+            trackers,
+            web_seeds: metainfo.web_seeds.clone(),
+            ..MagnetLink::new(metainfo.info_hash)
+        }
+    }
+
+    pub fn to_uri(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(v1) = self.info_hash.v1() {
+            params.push(format!("xt=urn:btih:{}", encode_hex(&v1)));
+        }
+        if let Some(v2) = self.info_hash.v2() {
+            params.push(format!(
+                "xt=urn:btmh:{SHA256_MULTIHASH_PREFIX}{}",
+                encode_hex(&v2)
+            ));
+        }
+        if let Some(name) = &self.display_name {
+            params.push(format!("dn={}", encode_percent(name)));
+        }
+        for tracker in &self.trackers {
+            params.push(format!("tr={}", encode_percent(tracker)));
+        }
+        for web_seed in &self.web_seeds {
+            params.push(format!("ws={}", encode_percent(web_seed)));
+        }
+        for peer in &self.peers {
+            params.push(format!("x.pe={}", encode_percent(peer)));
+        }
+        if !self.select_only.is_empty() {
+            let ranges: Vec<String> = self
+                .select_only
+                .iter()
+                .map(|range| match range.start() == range.end() {
+                    true => range.start().to_string(),
+                    false => format!("{}-{}", range.start(), range.end()),
+                })
+                .collect();
+            params.push(format!("so={}", ranges.join(",")));
+        }
+        for (key, value) in &self.extra {
+            params.push(format!("{}={}", encode_percent(key), encode_percent(value)));
+        }
+
+        format!("magnet:?{}", params.join("&"))
+    }
+}
+
+impl fmt::Display for MagnetLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+impl FromStr for MagnetLink {
+    type Err = MagnetError;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        parse_magnet(uri)
+    }
+}
@@ -0,0 +1,36 @@
+use super::{info::Info, signature::Signature};
+use crate::enums::info_hash::InfoHash;
+use std::collections::BTreeMap;
+
+/// BEP 52 `piece layers`: pieces root to the hashes of the file's piece layer
+pub type PieceLayers = BTreeMap<[u8; 32], Vec<[u8; 32]>>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Metainfo {
+    pub announce: Option<String>,
+    /// BEP 12 tracker tiers; clients use these instead of `announce` when present
+    pub announce_list: Vec<Vec<String>>,
+    pub info: Info,
+    pub info_hash: InfoHash,
+    /// Empty for v1 torrents and for files no larger than one piece
+    pub piece_layers: PieceLayers,
+    /// BEP 19 `url-list`
+    pub web_seeds: Vec<String>,
+    /// BEP 17 `httpseeds`
+    pub http_seeds: Vec<String>,
+    pub comment: Option<String>,
+    pub created_by: Option<String>,
+    /// Seconds since the UNIX epoch
+    pub creation_date: Option<i64>,
+    /// BEP 35 signatures of the info dictionary, sorted by signer
+    pub signatures: Vec<Signature>,
+}
+
+impl Metainfo {
+    /// Identities of everyone who signed the torrent
+    pub fn signers(&self) -> impl Iterator<Item = &str> {
+        self.signatures
+            .iter()
+            .map(|signature| signature.signer.as_str())
+    }
+}
@@ -0,0 +1,17 @@
+pub mod bitfield;
+pub mod file_attributes;
+pub mod file_entry;
+pub mod file_layout;
+pub mod file_range;
+pub mod info;
+pub mod lint_issue;
+pub mod local_torrent;
+pub mod magnet;
+pub mod metainfo;
+pub mod progress;
+pub mod reuse_candidate;
+pub mod signature;
+pub mod tracker_list;
+pub mod v2_file;
+pub mod verification;
+pub mod web_seed_request;
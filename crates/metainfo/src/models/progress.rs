@@ -0,0 +1,7 @@
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Progress {
+    pub pieces_done: usize,
+    pub piece_count: usize,
+    pub bytes_done: u64,
+    pub total_bytes: u64,
+}
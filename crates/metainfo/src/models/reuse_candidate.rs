@@ -0,0 +1,13 @@
+use crate::enums::{file_match::FileMatch, info_hash::InfoHash};
+use std::path::PathBuf;
+
+/// A local file that may already hold the data of a file in a new torrent
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ReuseCandidate {
+    /// Index into the new torrent's `Info::files()`
+    pub file_index: usize,
+    /// The local torrent the file belongs to
+    pub source: InfoHash,
+    pub source_path: PathBuf,
+    pub matched_by: FileMatch,
+}
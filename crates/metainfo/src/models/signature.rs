@@ -0,0 +1,74 @@
+#[cfg(feature = "signatures")]
+use crate::enums::{
+    public_key::PublicKey, signature_error::SignatureError, signing_key::SigningKey,
+};
+use bencode::{dispatcher::bdecode::decode_bencode, enums::bencode::BencodeValue};
+use std::collections::BTreeMap;
+
+/// One BEP 35 entry of the top-level `signatures` dictionary
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Signature {
+    /// Identity of the signer, the key of the entry
+    pub signer: String,
+    /// DER-encoded X.509 certificate of the signer's key
+    pub certificate: Option<Vec<u8>>,
+    /// Bencoded `info` dictionary of the entry, signed along with the torrent's
+    pub info: Option<Vec<u8>>,
+    pub signature: Vec<u8>,
+}
+
+impl Signature {
+    /// The signed bytes: the torrent's info dictionary followed by the entry's own
+    /// `info` dictionary, if any
+    pub fn signed_message(info_bytes: &[u8], signature_info: Option<&[u8]>) -> Vec<u8> {
+        [info_bytes, signature_info.unwrap_or_default()].concat()
+    }
+
+    /// Signs `info_bytes`, the torrent's info dictionary exactly as encoded
+    #[cfg(feature = "signatures")]
+    pub fn sign(
+        signer: impl Into<String>,
+        info_bytes: &[u8],
+        key: &SigningKey,
+        certificate: Option<&[u8]>,
+    ) -> Result<Self, SignatureError> {
+        Ok(Signature {
+            signer: signer.into(),
+            certificate: certificate.map(<[u8]>::to_vec),
+            info: None,
+            signature: key.sign(&Self::signed_message(info_bytes, None))?,
+        })
+    }
+
+    /// Checks the signature over `info_bytes` against a trusted key
+    #[cfg(feature = "signatures")]
+    pub fn verify(&self, info_bytes: &[u8], key: &PublicKey) -> Result<(), SignatureError> {
+        let message = Self::signed_message(info_bytes, self.info.as_deref());
+        key.verify(&message, &self.signature)
+    }
+
+    /// Checks the signature against the key of its own certificate. This proves the
+    /// certificate's owner signed the torrent, not that the certificate is trusted
+    #[cfg(feature = "signatures")]
+    pub fn verify_certificate(&self, info_bytes: &[u8]) -> Result<(), SignatureError> {
+        let certificate = self
+            .certificate
+            .as_deref()
+            .ok_or(SignatureError::InvalidCertificate)?;
+        self.verify(info_bytes, &PublicKey::from_certificate(certificate)?)
+    }
+
+    /// The entry's value in the `signatures` dictionary
+    pub fn to_bencode(&self) -> BencodeValue {
+        let mut entry = BTreeMap::new();
+        if let Some(certificate) = &self.certificate {
+            entry.insert(b"certificate".to_vec(), certificate.clone().into());
+        }
+        if let Some(info) = &self.info {
+            let (info, _) = decode_bencode(info).expect("signature info is valid bencode");
+            entry.insert(b"info".to_vec(), info);
+        }
+        entry.insert(b"signature".to_vec(), self.signature.clone().into());
+        BencodeValue::Dict(entry)
+    }
+}
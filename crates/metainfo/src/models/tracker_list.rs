@@ -0,0 +1,172 @@
+use super::metainfo::Metainfo;
+use crate::{enums::error::MetainfoError, parsers::announce_list::parse_announce_list};
+use bencode::enums::bencode::BencodeValue;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// Trackers in BEP 12 tiers, kept in the order they should be tried
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TrackerList {
+    tiers: Vec<Vec<String>>,
+}
+
+impl TrackerList {
+    /// Keeps the given order; empty tiers are dropped
+    pub fn new(tiers: Vec<Vec<String>>) -> Self {
+        TrackerList {
+            tiers: tiers.into_iter().filter(|tier| !tier.is_empty()).collect(),
+        }
+    }
+
+    /// Uses `announce-list` when present and falls back to a single tier holding `announce`
+    pub fn from_metainfo(metainfo: &Metainfo) -> Self {
+        match metainfo.announce_list.is_empty() {
+            false => TrackerList::new(metainfo.announce_list.clone()),
+            true => TrackerList::new(
+                metainfo
+                    .announce
+                    .iter()
+                    .map(|url| vec![url.clone()])
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Reads back a list written by `to_bencode`
+    pub fn from_bencode(value: &BencodeValue) -> Result<Self, MetainfoError> {
+        parse_announce_list(value).map(TrackerList::new)
+    }
+
+    /// The tiers as an `announce-list` value, in the current order
+    pub fn to_bencode(&self) -> BencodeValue {
+        BencodeValue::from(self.tiers.clone())
+    }
+
+    pub fn tiers(&self) -> &[Vec<String>] {
+        &self.tiers
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiers.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
+    }
+
+    /// Every tracker in the order to try them: tier by tier, front to back
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.tiers.iter().flatten().map(String::as_str)
+    }
+
+    /// Shuffles each tier; BEP 12 asks for this once, when the torrent is loaded
+    pub fn shuffle(&mut self) {
+        let state = RandomState::new();
+        let mut counter = 0u64;
+        self.shuffle_with(|bound| {
+            counter += 1;
+            let mut hasher = state.build_hasher();
+            hasher.write_u64(counter);
+            (hasher.finish() % bound as u64) as usize
+        });
+    }
+
+    /// Fisher-Yates shuffle of each tier; `random(n)` must return a value below `n`
+    pub fn shuffle_with(&mut self, mut random: impl FnMut(usize) -> usize) {
+        for tier in &mut self.tiers {
+            for index in (1..tier.len()).rev() {
+                tier.swap(index, random(index + 1));
+            }
+        }
+    }
+
+    /// Moves a tracker that answered to the front of its tier, keeping the
+    /// relative order of the others; returns false for unknown trackers
+    pub fn promote(&mut self, url: &str) -> bool {
+        for tier in &mut self.tiers {
+            if let Some(position) = tier.iter().position(|tracker| tracker == url) {
+                tier[..=position].rotate_right(1);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::metainfo::parse_metainfo;
+    use bencode::bencode;
+
+    fn tiers() -> TrackerList {
+        TrackerList::new(vec![
+            vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            vec![],
+            vec!["d".to_string()],
+        ])
+    }
+
+    #[test]
+    fn test_order_and_promotion() {
+        let mut trackers = tiers();
+        assert_eq!(trackers.tiers().len(), 2);
+        assert_eq!(trackers.len(), 4);
+        assert_eq!(trackers.iter().collect::<Vec<_>>(), ["a", "b", "c", "d"]);
+
+        assert!(trackers.promote("c"));
+        assert_eq!(trackers.iter().collect::<Vec<_>>(), ["c", "a", "b", "d"]);
+        assert!(trackers.promote("d"));
+        assert!(!trackers.promote("e"));
+        assert_eq!(trackers.iter().collect::<Vec<_>>(), ["c", "a", "b", "d"]);
+    }
+
+    #[test]
+    fn test_shuffle_stays_within_tiers() {
+        let mut trackers = tiers();
+        // Always picking index 0 rotates each tier left by one
+        trackers.shuffle_with(|_| 0);
+        assert_eq!(trackers.iter().collect::<Vec<_>>(), ["b", "c", "a", "d"]);
+
+        let mut shuffled = tiers();
+        shuffled.shuffle();
+        let mut first = shuffled.tiers()[0].clone();
+        first.sort();
+        assert_eq!(first, ["a", "b", "c"]);
+        assert_eq!(shuffled.tiers()[1], ["d"]);
+    }
+
+    #[test]
+    fn test_bencode_roundtrip() {
+        let mut trackers = tiers();
+        trackers.promote("b");
+        let value = trackers.to_bencode();
+        assert_eq!(value, bencode!([["b", "a", "c"], ["d"]]));
+        assert_eq!(TrackerList::from_bencode(&value).unwrap(), trackers);
+    }
+
+    #[test]
+    fn test_from_metainfo() {
+        let info = bencode!({ "name": "x", "piece length": 1, "pieces": "", "length": 0 });
+        let with_list = parse_metainfo(&bencode!({
+            "announce": "a",
+            "announce-list": [["b"], ["c"]],
+            "info": (info.clone()),
+        }))
+        .unwrap();
+        assert_eq!(
+            TrackerList::from_metainfo(&with_list)
+                .iter()
+                .collect::<Vec<_>>(),
+            ["b", "c"]
+        );
+
+        let announce_only = parse_metainfo(&bencode!({ "announce": "a", "info": info })).unwrap();
+        assert_eq!(
+            TrackerList::from_metainfo(&announce_only).tiers(),
+            [vec!["a".to_string()]]
+        );
+    }
+}
@@ -0,0 +1,6 @@
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct V2File {
+    pub length: u64,
+    /// Merkle root of the file's 16 KiB blocks, absent for empty files
+    pub pieces_root: Option<[u8; 32]>,
+}
@@ -0,0 +1,30 @@
+use super::bitfield::Bitfield;
+
+/// How much of one file is backed by verified pieces
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FileCompletion {
+    pub path: Vec<String>,
+    pub length: u64,
+    pub bytes_complete: u64,
+}
+
+impl FileCompletion {
+    pub fn is_complete(&self) -> bool {
+        self.bytes_complete == self.length
+    }
+}
+
+/// Result of checking data on disk against a torrent
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Verification {
+    /// Set for every piece whose data matched its hash
+    pub pieces: Bitfield,
+    /// Every file except padding files, in torrent order
+    pub files: Vec<FileCompletion>,
+}
+
+impl Verification {
+    pub fn is_complete(&self) -> bool {
+        self.pieces.all()
+    }
+}
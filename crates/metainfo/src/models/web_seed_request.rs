@@ -0,0 +1,22 @@
+use std::ops::Range;
+
+/// One HTTP range request fetching part of a piece from a BEP 19 web seed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WebSeedRequest {
+    pub url: String,
+    /// Bytes to fetch from the file at `url`
+    pub range: Range<u64>,
+    /// Where the fetched bytes start within the piece
+    pub piece_offset: u64,
+}
+
+impl WebSeedRequest {
+    /// Value of the `Range` header, whose end is inclusive; `None` for an empty
+    /// range, which HTTP cannot express
+    pub fn range_header(&self) -> Option<String> {
+        match self.range.is_empty() {
+            true => None,
+            false => Some(format!("bytes={}-{}", self.range.start, self.range.end - 1)),
+        }
+    }
+}
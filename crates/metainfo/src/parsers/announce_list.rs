@@ -0,0 +1,65 @@
+use crate::enums::error::MetainfoError;
+use bencode::enums::bencode::BencodeValue;
+
+const KEY: &str = "announce-list";
+const EXPECTED: &str = "a list of lists of strings";
+
+/// Parses BEP 12 `announce-list` tiers; empty tiers are dropped
+pub fn parse_announce_list(value: &BencodeValue) -> Result<Vec<Vec<String>>, MetainfoError> {
+    let wrong_type = || MetainfoError::WrongType {
+        key: KEY,
+        expected: EXPECTED,
+    };
+    let BencodeValue::List(tiers) = value else {
+        return Err(wrong_type());
+    };
+
+    let mut parsed = Vec::with_capacity(tiers.len());
+    for tier in tiers {
+        let BencodeValue::List(urls) = tier else {
+            return Err(wrong_type());
+        };
+        let urls =
+            urls.iter()
+                .map(|url| match url {
+                    BencodeValue::Str(bytes) => String::from_utf8(bytes.clone())
+                        .map_err(|_| MetainfoError::InvalidUtf8(KEY)),
+                    _ => Err(wrong_type()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+        if !urls.is_empty() {
+            parsed.push(urls);
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_parse_tiers() {
+        let value = bencode!([["udp://a", "udp://b"], [], ["http://c"]]);
+        assert_eq!(
+            parse_announce_list(&value).unwrap(),
+            vec![vec!["udp://a", "udp://b"], vec!["http://c"]]
+        );
+    }
+
+    #[test]
+    fn test_invalid_tiers() {
+        let wrong_type = Err(MetainfoError::WrongType {
+            key: KEY,
+            expected: EXPECTED,
+        });
+        assert_eq!(parse_announce_list(&bencode!("udp://a")), wrong_type);
+        assert_eq!(parse_announce_list(&bencode!(["udp://a"])), wrong_type);
+        assert_eq!(parse_announce_list(&bencode!([[1]])), wrong_type);
+        assert_eq!(
+            parse_announce_list(&bencode!([[(vec![0xffu8])]])),
+            Err(MetainfoError::InvalidUtf8(KEY))
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use crate::enums::error::MetainfoError;
+use bencode::enums::bencode::BencodeValue;
+use std::collections::BTreeMap;
+
+pub type Dict = BTreeMap<Vec<u8>, BencodeValue>;
+
+pub fn as_dict<'a>(
+    value: &'a BencodeValue,
+    context: &'static str,
+) -> Result<&'a Dict, MetainfoError> {
+    match value {
+        BencodeValue::Dict(dict) => Ok(dict),
+        _ => Err(MetainfoError::NotADictionary(context)),
+    }
+}
+
+pub fn get_int(dict: &Dict, key: &'static str) -> Result<Option<i64>, MetainfoError> {
+    match dict.get(key.as_bytes()) {
+        None => Ok(None),
+        Some(BencodeValue::Int(n)) => Ok(Some(*n)),
+        Some(_) => Err(MetainfoError::WrongType {
+            key,
+            expected: "an integer",
+        }),
+    }
+}
+
+pub fn get_u64(dict: &Dict, key: &'static str) -> Result<Option<u64>, MetainfoError> {
+    match get_int(dict, key)? {
+        None => Ok(None),
+        Some(n) => u64::try_from(n)
+            .map(Some)
+            .map_err(|_| MetainfoError::InvalidValue {
+                key,
+                reason: "must not be negative",
+            }),
+    }
+}
+
+pub fn get_bytes<'a>(dict: &'a Dict, key: &'static str) -> Result<Option<&'a [u8]>, MetainfoError> {
+    match dict.get(key.as_bytes()) {
+        None => Ok(None),
+        Some(BencodeValue::Str(bytes)) => Ok(Some(bytes)),
+        Some(_) => Err(MetainfoError::WrongType {
+            key,
+            expected: "a string",
+        }),
+    }
+}
+
+pub fn get_string(dict: &Dict, key: &'static str) -> Result<Option<String>, MetainfoError> {
+    match get_bytes(dict, key)? {
+        None => Ok(None),
+        Some(bytes) => String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .map_err(|_| MetainfoError::InvalidUtf8(key)),
+    }
+}
+
+pub fn get_list<'a>(
+    dict: &'a Dict,
+    key: &'static str,
+) -> Result<Option<&'a [BencodeValue]>, MetainfoError> {
+    match dict.get(key.as_bytes()) {
+        None => Ok(None),
+        Some(BencodeValue::List(list)) => Ok(Some(list)),
+        Some(_) => Err(MetainfoError::WrongType {
+            key,
+            expected: "a list",
+        }),
+    }
+}
+
+pub fn get_string_list(
+    dict: &Dict,
+    key: &'static str,
+) -> Result<Option<Vec<String>>, MetainfoError> {
+    let Some(list) = get_list(dict, key)? else {
+        return Ok(None);
+    };
+    list.iter()
+        .map(|item| match item {
+            BencodeValue::Str(bytes) => {
+                String::from_utf8(bytes.clone()).map_err(|_| MetainfoError::InvalidUtf8(key))
+            }
+            _ => Err(MetainfoError::WrongType {
+                key,
+                expected: "a list of strings",
+            }),
+        })
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+pub fn require<T>(value: Option<T>, key: &'static str) -> Result<T, MetainfoError> {
+    value.ok_or(MetainfoError::MissingKey(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_typed_getters() {
+        let value = bencode!({ "n": -1, "s": "text", "l": [], "raw": b"\xff" });
+        let dict = as_dict(&value, "test").unwrap();
+
+        assert_eq!(get_int(dict, "n"), Ok(Some(-1)));
+        assert_eq!(get_string(dict, "s"), Ok(Some("text".to_string())));
+        assert_eq!(get_list(dict, "l").unwrap().map(<[_]>::len), Some(0));
+        assert_eq!(get_int(dict, "absent"), Ok(None));
+        assert_eq!(
+            get_u64(dict, "n"),
+            Err(MetainfoError::InvalidValue {
+                key: "n",
+                reason: "must not be negative"
+            })
+        );
+        assert_eq!(
+            get_string(dict, "raw"),
+            Err(MetainfoError::InvalidUtf8("raw"))
+        );
+        assert_eq!(
+            get_list(dict, "s"),
+            Err(MetainfoError::WrongType {
+                key: "s",
+                expected: "a list"
+            })
+        );
+        assert_eq!(
+            require(get_int(dict, "absent").unwrap(), "absent"),
+            Err(MetainfoError::MissingKey("absent"))
+        );
+    }
+
+    #[test]
+    fn test_as_dict_rejects_other_types() {
+        assert_eq!(
+            as_dict(&bencode!([]), "info"),
+            Err(MetainfoError::NotADictionary("info"))
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use super::fields::{as_dict, get_bytes, get_list, get_u64, require};
+use crate::{
+    enums::error::MetainfoError,
+    models::{file_attributes::FileAttributes, file_entry::FileEntry},
+};
+use bencode::enums::bencode::BencodeValue;
+
+pub fn parse_file_entry(value: &BencodeValue) -> Result<FileEntry, MetainfoError> {
+    let dict = as_dict(value, "file entry")?;
+
+    let length = require(get_u64(dict, "length")?, "length")?;
+
+    // Prefer the explicit UTF-8 path when the torrent provides one
+    let (key, components) = match get_list(dict, "path.utf-8")? {
+        Some(components) => ("path.utf-8", components),
+        None => ("path", require(get_list(dict, "path")?, "path")?),
+    };
+    let path = parse_path(key, components)?;
+
+    let attributes = FileAttributes::parse(get_bytes(dict, "attr")?.unwrap_or_default());
+    let symlink_path = match attributes.symlink {
+        true => Some(parse_path(
+            "symlink path",
+            require(get_list(dict, "symlink path")?, "symlink path")?,
+        )?),
+        false => None,
+    };
+
+    Ok(FileEntry {
+        length,
+        path,
+        attributes,
+        symlink_path,
+    })
+}
+
+pub(crate) fn parse_path(
+    key: &'static str,
+    components: &[BencodeValue],
+) -> Result<Vec<String>, MetainfoError> {
+    if components.is_empty() {
+        return Err(MetainfoError::InvalidValue {
+            key,
+            reason: "must contain at least one component",
+        });
+    }
+
+    components
+        .iter()
+        .map(|component| match component {
+            BencodeValue::Str(bytes) => {
+                String::from_utf8(bytes.clone()).map_err(|_| MetainfoError::InvalidUtf8(key))
+            }
+            _ => Err(MetainfoError::WrongType {
+                key,
+                expected: "a list of strings",
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_valid_entry() {
+        let entry = parse_file_entry(&bencode!({ "length": 42, "path": ["dir", "file.txt"] }));
+        assert_eq!(
+            entry,
+            Ok(FileEntry {
+                length: 42,
+                path: vec!["dir".to_string(), "file.txt".to_string()],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_attributes() {
+        let padding = parse_file_entry(&bencode!({
+            "attr": "p",
+            "length": 100,
+            "path": [".pad", "100"],
+        }))
+        .unwrap();
+        assert!(padding.is_padding());
+
+        let link = parse_file_entry(&bencode!({
+            "attr": "lx",
+            "length": 0,
+            "path": ["bin", "tool"],
+            "symlink path": ["lib", "tool-1.0"],
+        }))
+        .unwrap();
+        assert!(link.attributes.symlink && link.attributes.executable);
+        assert_eq!(
+            link.symlink_path,
+            Some(vec!["lib".to_string(), "tool-1.0".to_string()])
+        );
+
+        assert_eq!(
+            parse_file_entry(&bencode!({ "attr": "l", "length": 0, "path": ["a"] })),
+            Err(MetainfoError::MissingKey("symlink path"))
+        );
+    }
+
+    #[test]
+    fn test_prefers_utf8_path() {
+        let entry = parse_file_entry(&bencode!({
+            "length": 1,
+            "path": [b"\xe9t\xe9"],
+            "path.utf-8": ["été"],
+        }))
+        .unwrap();
+        assert_eq!(entry.path, vec!["été".to_string()]);
+    }
+
+    #[test]
+    fn test_invalid_entries() {
+        assert_eq!(
+            parse_file_entry(&bencode!({ "path": ["a"] })),
+            Err(MetainfoError::MissingKey("length"))
+        );
+        assert_eq!(
+            parse_file_entry(&bencode!({ "length": 1 })),
+            Err(MetainfoError::MissingKey("path"))
+        );
+        assert_eq!(
+            parse_file_entry(&bencode!({ "length": 1, "path": [] })),
+            Err(MetainfoError::InvalidValue {
+                key: "path",
+                reason: "must contain at least one component"
+            })
+        );
+        assert_eq!(
+            parse_file_entry(&bencode!({ "length": 1, "path": [1] })),
+            Err(MetainfoError::WrongType {
+                key: "path",
+                expected: "a list of strings"
+            })
+        );
+        assert_eq!(
+            parse_file_entry(&bencode!({ "length": 1, "path": [b"\xff"] })),
+            Err(MetainfoError::InvalidUtf8("path"))
+        );
+        assert_eq!(
+            parse_file_entry(&bencode!("file")),
+            Err(MetainfoError::NotADictionary("file entry"))
+        );
+    }
+}
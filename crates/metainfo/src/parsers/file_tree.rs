@@ -0,0 +1,139 @@
+use super::fields::{as_dict, get_bytes, get_u64, require};
+use crate::{
+    enums::{
+        error::MetainfoError,
+        file_tree::{FileTree, FileTreeNode},
+    },
+    models::v2_file::V2File,
+};
+use bencode::enums::bencode::BencodeValue;
+
+pub fn parse_file_tree(value: &BencodeValue) -> Result<FileTree, MetainfoError> {
+    let dict = as_dict(value, "file tree")?;
+    if dict.is_empty() {
+        return Err(MetainfoError::InvalidValue {
+            key: "file tree",
+            reason: "directories must not be empty",
+        });
+    }
+
+    dict.iter()
+        .map(|(name, node)| {
+            let name = String::from_utf8(name.clone())
+                .map_err(|_| MetainfoError::InvalidUtf8("file tree"))?;
+            if name.is_empty() {
+                return Err(MetainfoError::InvalidValue {
+                    key: "file tree",
+                    reason: "path components must not be empty",
+                });
+            }
+            Ok((name, parse_node(node)?))
+        })
+        .collect()
+}
+
+fn parse_node(value: &BencodeValue) -> Result<FileTreeNode, MetainfoError> {
+    let dict = as_dict(value, "file tree")?;
+
+    // A file is a dictionary holding nothing but its properties under the empty key
+    match dict.get(b"".as_slice()) {
+        Some(_) if dict.len() != 1 => Err(MetainfoError::InvalidValue {
+            key: "file tree",
+            reason: "a file entry must not have siblings",
+        }),
+        Some(file) => Ok(FileTreeNode::File(parse_v2_file(file)?)),
+        None => Ok(FileTreeNode::Directory(parse_file_tree(value)?)),
+    }
+}
+
+fn parse_v2_file(value: &BencodeValue) -> Result<V2File, MetainfoError> {
+    let dict = as_dict(value, "file tree entry")?;
+
+    let length = require(get_u64(dict, "length")?, "length")?;
+
+    let pieces_root = match get_bytes(dict, "pieces root")? {
+        Some(root) => {
+            Some(
+                <[u8; 32]>::try_from(root).map_err(|_| MetainfoError::InvalidValue {
+                    key: "pieces root",
+                    reason: "must be 32 bytes",
+                })?,
+            )
+        }
+        None if length > 0 => return Err(MetainfoError::MissingKey("pieces root")),
+        None => None,
+    };
+
+    Ok(V2File {
+        length,
+        pieces_root,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_nested_tree() {
+        let tree = parse_file_tree(&bencode!({
+            "dir": {
+                "a.txt": { "": { "length": 5, "pieces root": vec![1u8; 32] } },
+            },
+            "empty": { "": { "length": 0 } },
+        }))
+        .unwrap();
+
+        let FileTreeNode::Directory(dir) = &tree["dir"] else {
+            panic!("expected a directory");
+        };
+        assert_eq!(
+            dir["a.txt"],
+            FileTreeNode::File(V2File {
+                length: 5,
+                pieces_root: Some([1; 32]),
+            })
+        );
+        assert_eq!(
+            tree["empty"],
+            FileTreeNode::File(V2File {
+                length: 0,
+                pieces_root: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_trees() {
+        assert_eq!(
+            parse_file_tree(&bencode!({})),
+            Err(MetainfoError::InvalidValue {
+                key: "file tree",
+                reason: "directories must not be empty"
+            })
+        );
+        assert_eq!(
+            parse_file_tree(&bencode!({ "f": { "": { "length": 1 } } })),
+            Err(MetainfoError::MissingKey("pieces root"))
+        );
+        assert_eq!(
+            parse_file_tree(&bencode!({ "f": { "": { "length": 1, "pieces root": "short" } } })),
+            Err(MetainfoError::InvalidValue {
+                key: "pieces root",
+                reason: "must be 32 bytes"
+            })
+        );
+        assert_eq!(
+            parse_file_tree(&bencode!({ "f": { "": { "length": 0 }, "x": {} } })),
+            Err(MetainfoError::InvalidValue {
+                key: "file tree",
+                reason: "a file entry must not have siblings"
+            })
+        );
+        assert_eq!(
+            parse_file_tree(&bencode!({ "f": 1 })),
+            Err(MetainfoError::NotADictionary("file tree"))
+        );
+    }
+}
@@ -0,0 +1,463 @@
+use super::{
+    fields::{
+        as_dict, get_bytes, get_int, get_list, get_string, get_string_list, get_u64, require,
+    },
+    file_entry::parse_file_entry,
+    file_tree::parse_file_tree,
+};
+use crate::{
+    enums::{
+        error::MetainfoError,
+        file_mode::FileMode,
+        file_tree::{FileTree, FileTreeNode, flatten_file_tree},
+        info_hash::InfoHash,
+        meta_version::MetaVersion,
+    },
+    hashing::merkle::BLOCK_SIZE,
+    models::{file_entry::FileEntry, info::Info},
+};
+use bencode::enums::bencode::BencodeValue;
+
+pub fn parse_info(value: &BencodeValue) -> Result<Info, MetainfoError> {
+    let dict = as_dict(value, "info")?;
+
+    // Prefer the explicit UTF-8 name when the torrent provides one
+    let name = match get_string(dict, "name.utf-8")? {
+        Some(name) => name,
+        None => require(get_string(dict, "name")?, "name")?,
+    };
+
+    let piece_length = require(get_u64(dict, "piece length")?, "piece length")?;
+    if piece_length == 0 {
+        return Err(MetainfoError::InvalidValue {
+            key: "piece length",
+            reason: "must be greater than zero",
+        });
+    }
+
+    let meta_version = match (get_int(dict, "meta version")?, get_bytes(dict, "pieces")?) {
+        (None | Some(1), _) => MetaVersion::V1,
+        (Some(2), None) => MetaVersion::V2,
+        (Some(2), Some(_)) => MetaVersion::Hybrid,
+        (Some(_), _) => {
+            return Err(MetainfoError::InvalidValue {
+                key: "meta version",
+                reason: "must be 1 or 2",
+            });
+        }
+    };
+
+    let file_tree = match meta_version {
+        MetaVersion::V1 => None,
+        MetaVersion::V2 | MetaVersion::Hybrid => {
+            // v2 pieces are aligned to the 16 KiB blocks of the merkle trees
+            if piece_length < BLOCK_SIZE as u64 || !piece_length.is_power_of_two() {
+                return Err(MetainfoError::InvalidValue {
+                    key: "piece length",
+                    reason: "must be a power of two of at least 16 KiB",
+                });
+            }
+            let tree = require(dict.get(b"file tree".as_slice()), "file tree")?;
+            Some(parse_file_tree(tree)?)
+        }
+    };
+
+    let pieces = match get_bytes(dict, "pieces")? {
+        Some(pieces) => parse_pieces(pieces)?,
+        None if meta_version == MetaVersion::V2 => Vec::new(),
+        None => return Err(MetainfoError::MissingKey("pieces")),
+    };
+
+    let private = match get_int(dict, "private")? {
+        None | Some(0) => false,
+        Some(1) => true,
+        Some(_) => {
+            return Err(MetainfoError::InvalidValue {
+                key: "private",
+                reason: "must be 0 or 1",
+            });
+        }
+    };
+
+    let mode = match (get_u64(dict, "length")?, get_list(dict, "files")?) {
+        (Some(_), Some(_)) => return Err(MetainfoError::AmbiguousFileMode),
+        (None, None) => match &file_tree {
+            Some(tree) if meta_version == MetaVersion::V2 => file_mode_from_tree(tree),
+            _ => return Err(MetainfoError::MissingFileMode),
+        },
+        (Some(length), None) => FileMode::Single { length },
+        (None, Some(files)) => FileMode::Multiple {
+            files: parse_files(files)?,
+        },
+    };
+    // `Info::total_length` and everything built on it rely on the sum fitting
+    // a bencode integer
+    if let FileMode::Multiple { files } = &mode {
+        files
+            .iter()
+            .try_fold(0u64, |total, file| total.checked_add(file.length))
+            .filter(|&total| i64::try_from(total).is_ok())
+            .ok_or(MetainfoError::InvalidValue {
+                key: "files",
+                reason: "total length is too large",
+            })?;
+    }
+
+    let similar = match get_list(dict, "similar")? {
+        Some(hashes) => parse_similar(hashes)?,
+        None => Vec::new(),
+    };
+    let collections = get_string_list(dict, "collections")?.unwrap_or_default();
+
+    Ok(Info {
+        name,
+        piece_length,
+        pieces,
+        private,
+        mode,
+        meta_version,
+        file_tree,
+        similar,
+        collections,
+    })
+}
+
+fn parse_similar(hashes: &[BencodeValue]) -> Result<Vec<InfoHash>, MetainfoError> {
+    hashes
+        .iter()
+        .map(|hash| match hash {
+            BencodeValue::Str(bytes) => {
+                InfoHash::from_bytes(bytes).map_err(|_| MetainfoError::InvalidValue {
+                    key: "similar",
+                    reason: "info-hashes must be 20 or 32 bytes",
+                })
+            }
+            _ => Err(MetainfoError::WrongType {
+                key: "similar",
+                expected: "a list of strings",
+            }),
+        })
+        .collect()
+}
+
+/// A tree holding one file at its root is a single-file torrent (BEP 52)
+fn file_mode_from_tree(tree: &FileTree) -> FileMode {
+    if let [(_, FileTreeNode::File(file))] = tree.iter().collect::<Vec<_>>().as_slice() {
+        return FileMode::Single {
+            length: file.length,
+        };
+    }
+
+    FileMode::Multiple {
+        files: flatten_file_tree(tree)
+            .into_iter()
+            .map(|(path, file)| FileEntry {
+                length: file.length,
+                path,
+                ..Default::default()
+            })
+            .collect(),
+    }
+}
+
+fn parse_pieces(pieces: &[u8]) -> Result<Vec<[u8; 20]>, MetainfoError> {
+    if !pieces.len().is_multiple_of(20) {
+        return Err(MetainfoError::InvalidPiecesLength(pieces.len()));
+    }
+
+    Ok(pieces
+        .chunks_exact(20)
+        .map(|hash| hash.try_into().expect("chunk is 20 bytes"))
+        .collect())
+}
+
+fn parse_files(files: &[BencodeValue]) -> Result<Vec<FileEntry>, MetainfoError> {
+    if files.is_empty() {
+        return Err(MetainfoError::InvalidValue {
+            key: "files",
+            reason: "must contain at least one file",
+        });
+    }
+
+    files
+        .iter()
+        .enumerate()
+        .map(|(index, file)| {
+            parse_file_entry(file).map_err(|source| MetainfoError::FileEntry {
+                index,
+                source: Box::new(source),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_similar_and_collections() {
+        let info = parse_info(&bencode!({
+            "collections": ["series", "season 1"],
+            "length": 1,
+            "name": "a",
+            "piece length": 16384,
+            "pieces": "",
+            "similar": [(vec![1u8; 20]), (vec![2u8; 32])],
+        }))
+        .unwrap();
+        assert_eq!(info.collections, vec!["series", "season 1"]);
+        assert_eq!(
+            info.similar,
+            vec![InfoHash::V1([1; 20]), InfoHash::V2([2; 32])]
+        );
+
+        let invalid = bencode!({
+            "length": 1, "name": "a", "piece length": 1, "pieces": "", "similar": ["short"],
+        });
+        assert_eq!(
+            parse_info(&invalid),
+            Err(MetainfoError::InvalidValue {
+                key: "similar",
+                reason: "info-hashes must be 20 or 32 bytes",
+            })
+        );
+    }
+
+    #[test]
+    fn test_single_file() {
+        let info = parse_info(&bencode!({
+            "name": "file.txt",
+            "piece length": 16384,
+            "pieces": vec![7u8; 40],
+            "length": 20000,
+        }))
+        .unwrap();
+
+        assert_eq!(info.name, "file.txt");
+        assert_eq!(info.piece_length, 16384);
+        assert_eq!(info.pieces, vec![[7u8; 20]; 2]);
+        assert!(!info.private);
+        assert_eq!(info.mode, FileMode::Single { length: 20000 });
+    }
+
+    #[test]
+    fn test_multi_file() {
+        let info = parse_info(&bencode!({
+            "name": "dir",
+            "piece length": 32,
+            "pieces": vec![0u8; 20],
+            "private": 1,
+            "files": [
+                { "length": 10, "path": ["a"] },
+                { "length": 5, "path": ["b", "c"] },
+            ],
+        }))
+        .unwrap();
+
+        assert!(info.private);
+        assert_eq!(
+            info.mode,
+            FileMode::Multiple {
+                files: vec![
+                    FileEntry {
+                        length: 10,
+                        path: vec!["a".to_string()],
+                        ..Default::default()
+                    },
+                    FileEntry {
+                        length: 5,
+                        path: vec!["b".to_string(), "c".to_string()],
+                        ..Default::default()
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_pieces_and_piece_length() {
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "x", "piece length": 16, "pieces": vec![0u8; 19], "length": 1,
+            })),
+            Err(MetainfoError::InvalidPiecesLength(19))
+        );
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "x", "piece length": 0, "pieces": "", "length": 1,
+            })),
+            Err(MetainfoError::InvalidValue {
+                key: "piece length",
+                reason: "must be greater than zero"
+            })
+        );
+        assert_eq!(
+            parse_info(&bencode!({ "name": "x", "pieces": "", "length": 1 })),
+            Err(MetainfoError::MissingKey("piece length"))
+        );
+    }
+
+    #[test]
+    fn test_file_mode_errors() {
+        assert_eq!(
+            parse_info(&bencode!({ "name": "x", "piece length": 1, "pieces": "" })),
+            Err(MetainfoError::MissingFileMode)
+        );
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "x", "piece length": 1, "pieces": "", "length": 1, "files": [],
+            })),
+            Err(MetainfoError::AmbiguousFileMode)
+        );
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "x", "piece length": 1, "pieces": "", "files": [],
+            })),
+            Err(MetainfoError::InvalidValue {
+                key: "files",
+                reason: "must contain at least one file"
+            })
+        );
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "x", "piece length": 1, "pieces": "",
+                "files": [{ "length": 1, "path": ["ok"] }, { "length": -1, "path": ["bad"] }],
+            })),
+            Err(MetainfoError::FileEntry {
+                index: 1,
+                source: Box::new(MetainfoError::InvalidValue {
+                    key: "length",
+                    reason: "must not be negative"
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn test_total_length_overflow() {
+        let huge = bencode!({ "length": (i64::MAX), "path": ["a"] });
+        let info = |files: Vec<BencodeValue>| {
+            parse_info(&bencode!({
+                "name": "x", "piece length": 1, "pieces": "", "files": (files),
+            }))
+        };
+        let invalid = Err(MetainfoError::InvalidValue {
+            key: "files",
+            reason: "total length is too large",
+        });
+        assert_eq!(
+            info(vec![huge.clone(), huge.clone(), huge.clone()]),
+            invalid
+        );
+        assert_eq!(
+            info(vec![huge.clone(), bencode!({ "length": 1, "path": ["b"] })]),
+            invalid
+        );
+        assert_eq!(info(vec![huge]).unwrap().total_length(), i64::MAX as u64);
+    }
+
+    #[test]
+    fn test_v2_only() {
+        let info = parse_info(&bencode!({
+            "name": "dir",
+            "piece length": 16384,
+            "meta version": 2,
+            "file tree": {
+                "a": { "": { "length": 3, "pieces root": vec![1u8; 32] } },
+                "sub": { "b": { "": { "length": 0 } } },
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(info.meta_version, MetaVersion::V2);
+        assert!(info.pieces.is_empty());
+        assert_eq!(
+            info.mode,
+            FileMode::Multiple {
+                files: vec![
+                    FileEntry {
+                        length: 3,
+                        path: vec!["a".to_string()],
+                        ..Default::default()
+                    },
+                    FileEntry {
+                        length: 0,
+                        path: vec!["sub".to_string(), "b".to_string()],
+                        ..Default::default()
+                    },
+                ],
+            }
+        );
+        assert_eq!(info.file_tree.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_v2_single_file_and_hybrid() {
+        let v2 = parse_info(&bencode!({
+            "name": "a",
+            "piece length": 16384,
+            "meta version": 2,
+            "file tree": { "a": { "": { "length": 3, "pieces root": vec![1u8; 32] } } },
+        }))
+        .unwrap();
+        assert_eq!(v2.mode, FileMode::Single { length: 3 });
+
+        let hybrid = parse_info(&bencode!({
+            "name": "a",
+            "piece length": 16384,
+            "meta version": 2,
+            "pieces": vec![0u8; 20],
+            "length": 3,
+            "file tree": { "a": { "": { "length": 3, "pieces root": vec![1u8; 32] } } },
+        }))
+        .unwrap();
+        assert_eq!(hybrid.meta_version, MetaVersion::Hybrid);
+        assert_eq!(hybrid.pieces.len(), 1);
+        assert!(hybrid.file_tree.is_some());
+    }
+
+    #[test]
+    fn test_v2_errors() {
+        assert_eq!(
+            parse_info(&bencode!({ "name": "a", "piece length": 16384, "meta version": 2 })),
+            Err(MetainfoError::MissingKey("file tree"))
+        );
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "a", "piece length": 20000, "meta version": 2, "file tree": {},
+            })),
+            Err(MetainfoError::InvalidValue {
+                key: "piece length",
+                reason: "must be a power of two of at least 16 KiB"
+            })
+        );
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "a", "piece length": 16384, "meta version": 3, "file tree": {},
+            })),
+            Err(MetainfoError::InvalidValue {
+                key: "meta version",
+                reason: "must be 1 or 2"
+            })
+        );
+        assert_eq!(
+            parse_info(&bencode!({ "name": "a", "piece length": 16384, "length": 1 })),
+            Err(MetainfoError::MissingKey("pieces"))
+        );
+    }
+
+    #[test]
+    fn test_private_flag_values() {
+        assert_eq!(
+            parse_info(&bencode!({
+                "name": "x", "piece length": 1, "pieces": "", "length": 0, "private": 2,
+            })),
+            Err(MetainfoError::InvalidValue {
+                key: "private",
+                reason: "must be 0 or 1"
+            })
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::{
+    encoding::{hex::decode_hex, percent::decode_percent},
+    enums::{info_hash::InfoHash, magnet_error::MagnetError},
+    models::magnet::{MagnetLink, SHA256_MULTIHASH_PREFIX},
+};
+use std::ops::RangeInclusive;
+
+/// Parses a `magnet:?` URI; `xt`, `tr` and `x.pe` may carry a numeric suffix (`tr.1`)
+pub fn parse_magnet(uri: &str) -> Result<MagnetLink, MagnetError> {
+    let query = uri
+        .strip_prefix("magnet:?")
+        .ok_or(MagnetError::NotAMagnet)?;
+
+    let mut v1 = None;
+    let mut v2 = None;
+    let mut magnet = MagnetLink::new(InfoHash::V1([0; 20]));
+
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (raw_key, raw_value) = param.split_once('=').unwrap_or((param, ""));
+        let invalid = |reason| MagnetError::InvalidParameter {
+            key: raw_key.to_string(),
+            reason,
+        };
+        let key = decode_percent(raw_key).map_err(invalid)?;
+        let value = decode_percent(raw_value).map_err(invalid)?;
+
+        match base_key(&key) {
+            "xt" => {
+                if let Some(hash) = value.strip_prefix("urn:btih:") {
+                    let hash = match InfoHash::parse(hash).map_err(invalid)? {
+                        InfoHash::V1(hash) => hash,
+                        _ => return Err(invalid("'urn:btih' must be a 20-byte hash")),
+                    };
+                    set_once(&mut v1, hash)?;
+                } else if let Some(hash) = value.strip_prefix("urn:btmh:") {
+                    let digest = hash
+                        .strip_prefix(SHA256_MULTIHASH_PREFIX)
+                        .ok_or_else(|| invalid("'urn:btmh' must be a SHA-256 multihash"))?;
+                    let hash: [u8; 32] = decode_hex(digest)
+                        .map_err(invalid)?
+                        .try_into()
+                        .map_err(|_| invalid("'urn:btmh' must be a SHA-256 multihash"))?;
+                    set_once(&mut v2, hash)?;
+                } else {
+                    // Topics for other networks are kept as-is
+                    magnet.extra.push((key, value));
+                }
+            }
+            "dn" => magnet.display_name = Some(value),
+            "tr" => magnet.trackers.push(value),
+            "ws" => magnet.web_seeds.push(value),
+            "x.pe" => magnet.peers.push(value),
+            "so" => magnet.select_only = parse_select_only(&value).map_err(invalid)?,
+            _ => magnet.extra.push((key, value)),
+        }
+    }
+
+    magnet.info_hash = match (v1, v2) {
+        (Some(v1), Some(v2)) => InfoHash::Hybrid { v1, v2 },
+        (Some(v1), None) => InfoHash::V1(v1),
+        (None, Some(v2)) => InfoHash::V2(v2),
+        (None, None) => return Err(MagnetError::MissingInfoHash),
+    };
+    Ok(magnet)
+}
+
+/// Strips the numeric suffix clients use to number repeated parameters
+fn base_key(key: &str) -> &str {
+    match key.rsplit_once('.') {
+        Some((base @ ("xt" | "tr" | "x.pe"), index))
+            if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            base
+        }
+        _ => key,
+    }
+}
+
+fn set_once<const N: usize>(slot: &mut Option<[u8; N]>, hash: [u8; N]) -> Result<(), MagnetError> {
+    match slot {
+        Some(existing) if *existing != hash => Err(MagnetError::ConflictingInfoHash),
+        _ => {
+            *slot = Some(hash);
+            Ok(())
+        }
+    }
+}
+
+/// Parses a BEP 53 `so` value such as `0,2,4-6`
+fn parse_select_only(value: &str) -> Result<Vec<RangeInclusive<usize>>, &'static str> {
+    value
+        .split(',')
+        .map(|item| {
+            let (start, end) = item.split_once('-').unwrap_or((item, item));
+            let start: usize = start.parse().map_err(|_| "file indices must be integers")?;
+            let end: usize = end.parse().map_err(|_| "file indices must be integers")?;
+            if start > end {
+                return Err("ranges must not be reversed");
+            }
+            Ok(start..=end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encoding::hex::encode_hex, parsers::metainfo::decode_metainfo};
+
+    const V1_HEX: &str = "c12fe1c06bba254a9dc9f519b335aa7c1367a88a";
+    const V1_BASE32: &str = "YEX6DQDLXISUVHOJ6UM3GNNKPQJWPKEK";
+
+    #[test]
+    fn test_parse_full_magnet() {
+        let uri = format!(
+            "magnet:?xt=urn:btih:{V1_HEX}&dn=Big+Buck%20Bunny\
+             &tr=udp%3A%2F%2Ftracker.example%3A1337&tr.1=http://other.example/announce\
+             &ws=https://mirror.example/bbb&x.pe=10.0.0.1:6881&x.pe=%5B::1%5D:6881\
+             &so=0,2,4-6&x.custom=value"
+        );
+        let magnet = parse_magnet(&uri).unwrap();
+
+        assert_eq!(encode_hex(&magnet.info_hash.v1().unwrap()), V1_HEX);
+        assert_eq!(magnet.display_name.as_deref(), Some("Big Buck Bunny"));
+        assert_eq!(
+            magnet.trackers,
+            vec![
+                "udp://tracker.example:1337",
+                "http://other.example/announce"
+            ]
+        );
+        assert_eq!(magnet.web_seeds, vec!["https://mirror.example/bbb"]);
+        assert_eq!(magnet.peers, vec!["10.0.0.1:6881", "[::1]:6881"]);
+        assert_eq!(magnet.select_only, vec![0..=0, 2..=2, 4..=6]);
+        assert_eq!(
+            magnet.extra,
+            vec![("x.custom".to_string(), "value".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let uri = format!(
+            "magnet:?xt=urn:btih:{V1_HEX}&dn=Big%20Buck%20Bunny\
+             &tr=udp://tracker.example:1337/announce%3Fkey%3D1&x.pe=%5B::1%5D:6881&so=0,4-6&x.custom=1"
+        );
+        let magnet = parse_magnet(&uri).unwrap();
+        assert_eq!(magnet.to_uri(), uri);
+        assert_eq!(uri.parse::<MagnetLink>().unwrap(), magnet);
+    }
+
+    #[test]
+    fn test_base32_and_v2_hashes() {
+        let v2 = [0xab; 32];
+        let uri = format!(
+            "magnet:?xt=urn:btih:{V1_BASE32}&xt=urn:btmh:1220{}",
+            encode_hex(&v2)
+        );
+        let magnet = parse_magnet(&uri).unwrap();
+        let InfoHash::Hybrid { v1, v2: parsed } = magnet.info_hash else {
+            panic!("expected a hybrid info-hash");
+        };
+        assert_eq!(encode_hex(&v1), V1_HEX);
+        assert_eq!(parsed, v2);
+
+        let v2_only = parse_magnet(&format!("magnet:?xt=urn:btmh:1220{}", encode_hex(&v2)));
+        assert_eq!(v2_only.unwrap().info_hash, InfoHash::V2(v2));
+    }
+
+    #[test]
+    fn test_invalid_magnets() {
+        assert_eq!(
+            parse_magnet("http://example.com"),
+            Err(MagnetError::NotAMagnet)
+        );
+        assert_eq!(
+            parse_magnet("magnet:?dn=name"),
+            Err(MagnetError::MissingInfoHash)
+        );
+        assert_eq!(
+            parse_magnet(&format!(
+                "magnet:?xt=urn:btih:{V1_HEX}&xt=urn:btih:{}",
+                "0".repeat(40)
+            )),
+            Err(MagnetError::ConflictingInfoHash)
+        );
+        assert!(matches!(
+            parse_magnet("magnet:?xt=urn:btih:abc"),
+            Err(MagnetError::InvalidParameter { .. })
+        ));
+        assert!(matches!(
+            parse_magnet(&format!("magnet:?xt=urn:btmh:1114{}", "0".repeat(40))),
+            Err(MagnetError::InvalidParameter { .. })
+        ));
+        assert!(matches!(
+            parse_magnet(&format!("magnet:?xt=urn:btih:{V1_HEX}&so=3-1")),
+            Err(MagnetError::InvalidParameter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_metainfo() {
+        let torrent = b"d8:announce23:http://tracker/announce4:infod6:lengthi1e4:name5:a b.c\
+            12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+        let metainfo = decode_metainfo(torrent).unwrap();
+        let magnet = MagnetLink::from_metainfo(&metainfo);
+
+        assert_eq!(magnet.info_hash, metainfo.info_hash);
+        assert_eq!(
+            magnet.to_uri(),
+            format!(
+                "magnet:?xt=urn:btih:{}&dn=a%20b.c&tr=http://tracker/announce",
+                metainfo.info_hash.to_hex()
+            )
+        );
+
+        let torrent = b"d8:announce1:a13:announce-listll1:a1:bel1:aee4:infod6:lengthi1e\
+            4:name1:a12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+        let metainfo = decode_metainfo(torrent).unwrap();
+        assert_eq!(MagnetLink::from_metainfo(&metainfo).trackers, ["a", "b"]);
+    }
+}
@@ -0,0 +1,238 @@
+use super::{
+    announce_list::parse_announce_list,
+    fields::{as_dict, get_int, get_string, require},
+    info::parse_info,
+    piece_layers::{parse_piece_layers, validate_piece_layers},
+    signatures::parse_signatures,
+    web_seeds::{parse_http_seeds, parse_url_list},
+};
+use crate::{
+    enums::{error::MetainfoError, info_hash::InfoHash},
+    hashing::info_hash::{hash_info_bytes, raw_info_bytes},
+    models::metainfo::{Metainfo, PieceLayers},
+};
+use bencode::{
+    decoders::raw::decode_raw_dictionary,
+    dispatcher::{bdecode::decode_bencode, bencode::encode},
+    enums::bencode::BencodeValue,
+};
+
+/// Decodes and parses the raw contents of a `.torrent` file
+pub fn decode_metainfo(data: &[u8]) -> Result<Metainfo, MetainfoError> {
+    let (value, rest) = decode_bencode(data)?;
+    if !rest.is_empty() {
+        return Err(MetainfoError::TrailingData);
+    }
+    let info_bytes = raw_info_bytes(data)?;
+    // Signed `info` dictionaries must also be checked over their original bytes
+    let (raw, _) = decode_raw_dictionary(data)?;
+    let raw_signatures = raw.get(b"signatures".as_slice()).copied();
+    parse_with_hash(
+        &value,
+        info_bytes,
+        raw_signatures,
+        hash_info_bytes(info_bytes)?,
+    )
+}
+
+/// Parses an already decoded `.torrent` file.
+///
+/// The info-hash is computed over the re-encoded info dictionary, which only
+/// matches the original torrent when it was canonically encoded; prefer
+/// [`decode_metainfo`] when the original bytes are available.
+pub fn parse_metainfo(value: &BencodeValue) -> Result<Metainfo, MetainfoError> {
+    let dict = as_dict(value, "metainfo")?;
+    let info = require(dict.get(b"info".as_slice()), "info")?;
+    as_dict(info, "info")?;
+    let info_bytes = encode(info)?;
+    parse_with_hash(value, &info_bytes, None, hash_info_bytes(&info_bytes)?)
+}
+
+fn parse_with_hash(
+    value: &BencodeValue,
+    info_bytes: &[u8],
+    raw_signatures: Option<&[u8]>,
+    info_hash: InfoHash,
+) -> Result<Metainfo, MetainfoError> {
+    let dict = as_dict(value, "metainfo")?;
+
+    let info = parse_info(require(dict.get(b"info".as_slice()), "info")?)?;
+
+    let piece_layers = match dict.get(b"piece layers".as_slice()) {
+        Some(layers) if info.meta_version.has_v2() => parse_piece_layers(layers)?,
+        _ => PieceLayers::new(),
+    };
+    validate_piece_layers(&info, &piece_layers)?;
+
+    let announce_list = match dict.get(b"announce-list".as_slice()) {
+        Some(tiers) => parse_announce_list(tiers)?,
+        None => Vec::new(),
+    };
+    let web_seeds = match dict.get(b"url-list".as_slice()) {
+        Some(urls) => parse_url_list(urls)?,
+        None => Vec::new(),
+    };
+    let http_seeds = match dict.get(b"httpseeds".as_slice()) {
+        Some(urls) => parse_http_seeds(urls)?,
+        None => Vec::new(),
+    };
+
+    let signatures = match dict.get(b"signatures".as_slice()) {
+        Some(signatures) => parse_signatures(signatures, raw_signatures)?,
+        None => Vec::new(),
+    };
+    // Signatures carrying a certificate can be checked without trusting anyone
+    #[cfg(feature = "signatures")]
+    for signature in &signatures {
+        if signature.certificate.is_some() && signature.verify_certificate(info_bytes).is_err() {
+            return Err(MetainfoError::InvalidSignature(signature.signer.clone()));
+        }
+    }
+    #[cfg(not(feature = "signatures"))]
+    let _ = info_bytes;
+
+    Ok(Metainfo {
+        announce: get_string(dict, "announce")?,
+        announce_list,
+        info,
+        info_hash,
+        piece_layers,
+        web_seeds,
+        http_seeds,
+        comment: get_string(dict, "comment")?,
+        created_by: get_string(dict, "created by")?,
+        creation_date: get_int(dict, "creation date")?,
+        signatures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::file_mode::FileMode;
+    use bencode::bencode;
+
+    fn sample_torrent() -> BencodeValue {
+        bencode!({
+            "announce": "http://tracker.example/announce",
+            "announce-list": [
+                ["http://tracker.example/announce", "http://backup.example/announce"],
+                ["udp://tracker.example:1337"],
+            ],
+            "comment": "sample",
+            "httpseeds": ["http://seed.example/seed"],
+            "created by": "bittorrent-rust",
+            "creation date": 1700000000,
+            "info": {
+                "name": "file.txt",
+                "piece length": 262144,
+                "pieces": vec![1u8; 20],
+                "length": 1000,
+            },
+            "url-list": "http://mirror.example/file.txt",
+        })
+    }
+
+    #[test]
+    fn test_decode_metainfo() {
+        let data = encode(&sample_torrent()).unwrap();
+        let metainfo = decode_metainfo(&data).unwrap();
+
+        assert_eq!(
+            metainfo.announce.as_deref(),
+            Some("http://tracker.example/announce")
+        );
+        assert_eq!(metainfo.announce_list.len(), 2);
+        assert_eq!(
+            metainfo.announce_list[1],
+            vec!["udp://tracker.example:1337"]
+        );
+        assert_eq!(metainfo.web_seeds, vec!["http://mirror.example/file.txt"]);
+        assert_eq!(metainfo.http_seeds, vec!["http://seed.example/seed"]);
+        assert_eq!(metainfo.comment.as_deref(), Some("sample"));
+        assert_eq!(metainfo.created_by.as_deref(), Some("bittorrent-rust"));
+        assert_eq!(metainfo.creation_date, Some(1700000000));
+        assert_eq!(metainfo.info.name, "file.txt");
+        assert_eq!(metainfo.info.mode, FileMode::Single { length: 1000 });
+    }
+
+    #[test]
+    fn test_info_hash_matches_raw_bytes() {
+        let data = encode(&sample_torrent()).unwrap();
+        let decoded = decode_metainfo(&data).unwrap();
+        let parsed = parse_metainfo(&sample_torrent()).unwrap();
+
+        assert_eq!(
+            decoded.info_hash,
+            crate::hashing::info_hash::info_hash(&data).unwrap()
+        );
+        // Canonical input hashes the same either way
+        assert_eq!(decoded.info_hash, parsed.info_hash);
+    }
+
+    #[test]
+    fn test_signature_info_keeps_raw_bytes() {
+        let mut data = encode(&sample_torrent()).unwrap();
+        data.pop();
+        // The signed `info` has its keys out of order
+        data.extend_from_slice(b"10:signaturesd1:sd4:infod1:bi1e1:ai2ee9:signature3:sigeee");
+        let metainfo = decode_metainfo(&data).unwrap();
+        assert_eq!(
+            metainfo.signatures[0].info.as_deref(),
+            Some(b"d1:bi1e1:ai2ee".as_slice())
+        );
+    }
+
+    #[test]
+    fn test_optional_fields_absent() {
+        let metainfo = parse_metainfo(&bencode!({
+            "info": { "name": "x", "piece length": 1, "pieces": "", "length": 0 },
+        }))
+        .unwrap();
+        assert_eq!(metainfo.announce, None);
+        assert!(metainfo.announce_list.is_empty());
+        assert_eq!(metainfo.comment, None);
+        assert_eq!(metainfo.created_by, None);
+        assert_eq!(metainfo.creation_date, None);
+    }
+
+    #[test]
+    fn test_structural_errors() {
+        assert_eq!(
+            decode_metainfo(b"i1e"),
+            Err(MetainfoError::NotADictionary("metainfo"))
+        );
+        assert_eq!(
+            decode_metainfo(b"de"),
+            Err(MetainfoError::MissingKey("info"))
+        );
+        assert_eq!(
+            decode_metainfo(b"d4:infoi1ee"),
+            Err(MetainfoError::NotADictionary("info"))
+        );
+        assert_eq!(
+            decode_metainfo(b"d8:announ"),
+            Err(MetainfoError::Decode("Not enough bytes for string"))
+        );
+
+        let mut data = encode(&sample_torrent()).unwrap();
+        data.extend_from_slice(b"junk");
+        assert_eq!(decode_metainfo(&data), Err(MetainfoError::TrailingData));
+    }
+
+    #[test]
+    fn test_error_messages() {
+        assert_eq!(
+            MetainfoError::MissingKey("info").to_string(),
+            "missing required key 'info'"
+        );
+        assert_eq!(
+            MetainfoError::FileEntry {
+                index: 3,
+                source: Box::new(MetainfoError::MissingKey("path")),
+            }
+            .to_string(),
+            "files[3]: missing required key 'path'"
+        );
+    }
+}
@@ -0,0 +1,10 @@
+pub mod announce_list;
+pub mod fields;
+pub mod file_entry;
+pub mod file_tree;
+pub mod info;
+pub mod magnet;
+pub mod metainfo;
+pub mod piece_layers;
+pub mod signatures;
+pub mod web_seeds;
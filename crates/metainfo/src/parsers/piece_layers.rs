@@ -0,0 +1,157 @@
+use super::fields::as_dict;
+use crate::{
+    enums::{error::MetainfoError, file_tree::flatten_file_tree},
+    hashing::merkle::{MerkleTree, piece_layer_height},
+    models::{info::Info, metainfo::PieceLayers},
+};
+use bencode::enums::bencode::BencodeValue;
+
+pub fn parse_piece_layers(value: &BencodeValue) -> Result<PieceLayers, MetainfoError> {
+    let dict = as_dict(value, "piece layers")?;
+
+    dict.iter()
+        .map(|(root, layer)| {
+            let root =
+                <[u8; 32]>::try_from(root.as_slice()).map_err(|_| MetainfoError::InvalidValue {
+                    key: "piece layers",
+                    reason: "keys must be 32-byte pieces roots",
+                })?;
+            let BencodeValue::Str(hashes) = layer else {
+                return Err(MetainfoError::WrongType {
+                    key: "piece layers",
+                    expected: "a dictionary of strings",
+                });
+            };
+            if !hashes.len().is_multiple_of(32) {
+                return Err(MetainfoError::InvalidValue {
+                    key: "piece layers",
+                    reason: "layers must be a multiple of 32 bytes",
+                });
+            }
+            let hashes = hashes
+                .chunks_exact(32)
+                .map(|hash| hash.try_into().expect("chunk is 32 bytes"))
+                .collect();
+            Ok((root, hashes))
+        })
+        .collect()
+}
+
+/// Checks that every file larger than a piece has a piece layer hashing up to its pieces root
+pub fn validate_piece_layers(info: &Info, layers: &PieceLayers) -> Result<(), MetainfoError> {
+    let Some(tree) = &info.file_tree else {
+        return Ok(());
+    };
+    let height = piece_layer_height(info.piece_length);
+
+    for (_, file) in flatten_file_tree(tree) {
+        let Some(root) = file.pieces_root else {
+            continue;
+        };
+        if file.length <= info.piece_length {
+            continue;
+        }
+
+        let layer = layers
+            .get(&root)
+            .ok_or(MetainfoError::MissingPieceLayer(root))?;
+        let piece_count = file.length.div_ceil(info.piece_length) as usize;
+        if layer.len() != piece_count || MerkleTree::from_layer(layer, height).root() != root {
+            return Err(MetainfoError::PieceLayerMismatch(root));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::{
+            file_mode::FileMode,
+            file_tree::{FileTree, FileTreeNode},
+            meta_version::MetaVersion,
+        },
+        hashing::merkle::{BLOCK_SIZE, block_hashes},
+        models::v2_file::V2File,
+    };
+    use bencode::bencode;
+
+    const PIECE_LENGTH: u64 = 2 * BLOCK_SIZE as u64;
+
+    fn info_with_file(length: u64, pieces_root: [u8; 32]) -> Info {
+        Info {
+            name: "f".to_string(),
+            piece_length: PIECE_LENGTH,
+            pieces: vec![],
+            private: false,
+            mode: FileMode::Single { length },
+            meta_version: MetaVersion::V2,
+            file_tree: Some(FileTree::from([(
+                "f".to_string(),
+                FileTreeNode::File(V2File {
+                    length,
+                    pieces_root: Some(pieces_root),
+                }),
+            )])),
+            similar: vec![],
+            collections: vec![],
+        }
+    }
+
+    #[test]
+    fn test_parse_and_validate() {
+        let data = vec![3u8; BLOCK_SIZE * 5];
+        let tree = MerkleTree::from_leaves(&block_hashes(&data));
+        let layer = &tree.layer(1).unwrap()[..3];
+        let info = info_with_file(data.len() as u64, tree.root());
+
+        let value = bencode!({ (tree.root().to_vec()): layer.concat() });
+        let layers = parse_piece_layers(&value).unwrap();
+        assert_eq!(layers[&tree.root()], layer);
+        assert_eq!(validate_piece_layers(&info, &layers), Ok(()));
+    }
+
+    #[test]
+    fn test_validation_failures() {
+        let data = vec![3u8; BLOCK_SIZE * 5];
+        let tree = MerkleTree::from_leaves(&block_hashes(&data));
+        let info = info_with_file(data.len() as u64, tree.root());
+
+        assert_eq!(
+            validate_piece_layers(&info, &PieceLayers::new()),
+            Err(MetainfoError::MissingPieceLayer(tree.root()))
+        );
+
+        let mut tampered = tree.layer(1).unwrap()[..3].to_vec();
+        tampered[1][0] ^= 1;
+        let layers = PieceLayers::from([(tree.root(), tampered)]);
+        assert_eq!(
+            validate_piece_layers(&info, &layers),
+            Err(MetainfoError::PieceLayerMismatch(tree.root()))
+        );
+
+        // Files no larger than a piece carry no layer
+        let small = info_with_file(PIECE_LENGTH, [9; 32]);
+        assert_eq!(validate_piece_layers(&small, &PieceLayers::new()), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_layers() {
+        assert_eq!(
+            parse_piece_layers(&bencode!({ "short": "" })),
+            Err(MetainfoError::InvalidValue {
+                key: "piece layers",
+                reason: "keys must be 32-byte pieces roots"
+            })
+        );
+        assert_eq!(
+            parse_piece_layers(&bencode!({ (vec![0u8; 32]): vec![0u8; 31] })),
+            Err(MetainfoError::InvalidValue {
+                key: "piece layers",
+                reason: "layers must be a multiple of 32 bytes"
+            })
+        );
+    }
+}
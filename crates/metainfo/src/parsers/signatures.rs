@@ -0,0 +1,100 @@
+use super::fields::{as_dict, get_bytes, require};
+use crate::{enums::error::MetainfoError, models::signature::Signature};
+use bencode::{
+    decoders::raw::decode_raw_dictionary, dispatcher::bencode::encode, enums::bencode::BencodeValue,
+};
+
+/// Parses the BEP 35 `signatures` dictionary, keyed by signer identity. With
+/// `raw`, the dictionary as it appears in the file, each `info` keeps the exact
+/// bytes that were signed; otherwise it is re-encoded from `value`
+pub fn parse_signatures(
+    value: &BencodeValue,
+    raw: Option<&[u8]>,
+) -> Result<Vec<Signature>, MetainfoError> {
+    let dict = as_dict(value, "signatures")?;
+    let raw = match raw {
+        Some(raw) => Some(decode_raw_dictionary(raw)?.0),
+        None => None,
+    };
+
+    dict.iter()
+        .map(|(signer, entry)| {
+            let signer = String::from_utf8(signer.clone())
+                .map_err(|_| MetainfoError::InvalidUtf8("signatures"))?;
+            let entry = as_dict(entry, "signature entry")?;
+
+            let info = match entry.get(b"info".as_slice()) {
+                Some(info) => {
+                    as_dict(info, "signature info")?;
+                    let raw_entry = raw.as_ref().and_then(|raw| raw.get(signer.as_bytes()));
+                    match raw_entry {
+                        Some(raw_entry) => decode_raw_dictionary(raw_entry)?
+                            .0
+                            .get(b"info".as_slice())
+                            .map(|info| info.to_vec()),
+                        None => Some(encode(info)?),
+                    }
+                }
+                None => None,
+            };
+
+            Ok(Signature {
+                signer,
+                certificate: get_bytes(entry, "certificate")?.map(<[u8]>::to_vec),
+                info,
+                signature: require(get_bytes(entry, "signature")?, "signature")?.to_vec(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_parse_signatures() {
+        let value = bencode!({
+            "com.example": {
+                "certificate": "cert",
+                "info": { "expires": 10 },
+                "signature": "sig",
+            },
+            "org.example": { "signature": "other" },
+        });
+        assert_eq!(
+            parse_signatures(&value, None).unwrap(),
+            vec![
+                Signature {
+                    signer: "com.example".to_string(),
+                    certificate: Some(b"cert".to_vec()),
+                    info: Some(b"d7:expiresi10ee".to_vec()),
+                    signature: b"sig".to_vec(),
+                },
+                Signature {
+                    signer: "org.example".to_string(),
+                    certificate: None,
+                    info: None,
+                    signature: b"other".to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_signatures() {
+        assert_eq!(
+            parse_signatures(&bencode!({ "a": {} }), None),
+            Err(MetainfoError::MissingKey("signature"))
+        );
+        assert_eq!(
+            parse_signatures(&bencode!({ "a": { "signature": "s", "info": 1 } }), None),
+            Err(MetainfoError::NotADictionary("signature info"))
+        );
+        assert_eq!(
+            parse_signatures(&bencode!([]), None),
+            Err(MetainfoError::NotADictionary("signatures"))
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use crate::enums::error::MetainfoError;
+use bencode::enums::bencode::BencodeValue;
+
+/// Parses BEP 19 `url-list`, which may be a single URL or a list; empty URLs are dropped
+pub fn parse_url_list(value: &BencodeValue) -> Result<Vec<String>, MetainfoError> {
+    match value {
+        BencodeValue::Str(_) => parse_urls("url-list", std::slice::from_ref(value)),
+        BencodeValue::List(urls) => parse_urls("url-list", urls),
+        _ => Err(MetainfoError::WrongType {
+            key: "url-list",
+            expected: "a string or a list of strings",
+        }),
+    }
+}
+
+/// Parses BEP 17 `httpseeds`
+pub fn parse_http_seeds(value: &BencodeValue) -> Result<Vec<String>, MetainfoError> {
+    match value {
+        BencodeValue::List(urls) => parse_urls("httpseeds", urls),
+        _ => Err(MetainfoError::WrongType {
+            key: "httpseeds",
+            expected: "a list of strings",
+        }),
+    }
+}
+
+fn parse_urls(key: &'static str, urls: &[BencodeValue]) -> Result<Vec<String>, MetainfoError> {
+    let mut parsed = Vec::with_capacity(urls.len());
+    for url in urls {
+        let BencodeValue::Str(bytes) = url else {
+            return Err(MetainfoError::WrongType {
+                key,
+                expected: "a list of strings",
+            });
+        };
+        let url = String::from_utf8(bytes.clone()).map_err(|_| MetainfoError::InvalidUtf8(key))?;
+        if !url.is_empty() {
+            parsed.push(url);
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_url_list_forms() {
+        assert_eq!(
+            parse_url_list(&bencode!("http://a/")).unwrap(),
+            vec!["http://a/"]
+        );
+        assert_eq!(
+            parse_url_list(&bencode!(["http://a/", "", "http://b/"])).unwrap(),
+            vec!["http://a/", "http://b/"]
+        );
+        assert!(parse_url_list(&bencode!("")).unwrap().is_empty());
+        assert!(parse_url_list(&bencode!(1)).is_err());
+        assert!(parse_url_list(&bencode!([1])).is_err());
+    }
+
+    #[test]
+    fn test_http_seeds() {
+        assert_eq!(
+            parse_http_seeds(&bencode!(["http://seed/"])).unwrap(),
+            vec!["http://seed/"]
+        );
+        assert_eq!(
+            parse_http_seeds(&bencode!("http://seed/")),
+            Err(MetainfoError::WrongType {
+                key: "httpseeds",
+                expected: "a list of strings"
+            })
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{
+    enums::{file_match::FileMatch, file_tree::flatten_file_tree},
+    models::{
+        info::Info, local_torrent::LocalTorrent, metainfo::Metainfo,
+        reuse_candidate::ReuseCandidate,
+    },
+    storage::paths::disk_path,
+};
+use std::collections::HashMap;
+
+/// Finds files of the local torrents related to `metainfo` through BEP 38 `similar`
+/// or `collections` that may already hold data of its files. Candidates are sorted
+/// by file, strongest match first; padding and empty files are never matched
+pub fn find_reusable_files(metainfo: &Metainfo, local: &[LocalTorrent]) -> Vec<ReuseCandidate> {
+    let files = metainfo.info.files();
+    let roots = pieces_roots(&metainfo.info);
+
+    let mut candidates = Vec::new();
+    for torrent in local
+        .iter()
+        .filter(|torrent| is_related(metainfo, &torrent.metainfo))
+    {
+        let info = &torrent.metainfo.info;
+        let local_roots = pieces_roots(info);
+
+        for local_file in info.files().iter().filter(|file| file.length > 0) {
+            let local_root = local_roots.get(&local_file.path);
+
+            for (file_index, file) in files.iter().enumerate() {
+                if file.length != local_file.length {
+                    continue;
+                }
+                let matched_by = match (roots.get(&file.path), local_root) {
+                    (Some(root), Some(local_root)) if root == local_root => FileMatch::PiecesRoot,
+                    // Different roots mean different data
+                    (Some(_), Some(_)) => continue,
+                    _ if file.path.last() == local_file.path.last() => FileMatch::LengthAndName,
+                    _ => continue,
+                };
+
+                candidates.push(ReuseCandidate {
+                    file_index,
+                    source: torrent.metainfo.info_hash,
+                    source_path: disk_path(info, &torrent.root, &local_file.path),
+                    matched_by,
+                });
+            }
+        }
+    }
+
+    candidates.sort_by_key(|candidate| (candidate.file_index, candidate.matched_by));
+    candidates
+}
+
+fn is_related(metainfo: &Metainfo, other: &Metainfo) -> bool {
+    if metainfo.info_hash.matches(&other.info_hash) {
+        return false;
+    }
+
+    let lists =
+        |a: &Metainfo, b: &Metainfo| a.info.similar.iter().any(|hash| hash.matches(&b.info_hash));
+    let shares_collection = metainfo
+        .info
+        .collections
+        .iter()
+        .any(|collection| other.info.collections.contains(collection));
+
+    lists(metainfo, other) || lists(other, metainfo) || shares_collection
+}
+
+/// v2 `pieces root` of each file, keyed by its path in `Info::files()`
+fn pieces_roots(info: &Info) -> HashMap<Vec<String>, [u8; 32]> {
+    let Some(tree) = &info.file_tree else {
+        return HashMap::new();
+    };
+    let files = flatten_file_tree(tree).into_iter();
+    if info.is_multi_file() {
+        files
+            .filter_map(|(path, file)| Some((path, file.pieces_root?)))
+            .collect()
+    } else {
+        // A single-file torrent lists its file under `name`, whatever the tree calls it
+        files
+            .filter_map(|(_, file)| Some((vec![info.name.clone()], file.pieces_root?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builders::torrent::TorrentBuilder,
+        enums::{info_hash::InfoHash, meta_version::MetaVersion},
+        parsers::metainfo::decode_metainfo,
+    };
+    use std::{fs, path::Path};
+
+    fn release(dir: &Path, name: &str, files: &[(&str, &[u8])]) -> TorrentBuilder {
+        let root = dir.join(name);
+        fs::create_dir_all(&root).unwrap();
+        for (file, data) in files {
+            fs::write(root.join(file), data).unwrap();
+        }
+        TorrentBuilder::new(root).piece_length(16384)
+    }
+
+    fn build(builder: TorrentBuilder) -> Metainfo {
+        decode_metainfo(&builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_similar_torrents_match_by_length_and_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = build(release(
+            dir.path(),
+            "v1.0",
+            &[("movie.mkv", b"0123456789"), ("extras.txt", b"old")],
+        ));
+        let unrelated = build(release(
+            dir.path(),
+            "other",
+            &[("movie.mkv", b"0123456789")],
+        ));
+        let new = build(
+            release(
+                dir.path(),
+                "v1.1",
+                &[("movie.mkv", b"0123456789"), ("extras.txt", b"new!")],
+            )
+            .similar(old.info_hash),
+        );
+
+        let local = [
+            LocalTorrent {
+                metainfo: unrelated,
+                root: dir.path().to_path_buf(),
+            },
+            LocalTorrent {
+                metainfo: old.clone(),
+                root: dir.path().to_path_buf(),
+            },
+        ];
+        let candidates = find_reusable_files(&new, &local);
+        assert_eq!(
+            candidates,
+            vec![ReuseCandidate {
+                file_index: 1,
+                source: old.info_hash,
+                source_path: dir.path().join("v1.0").join("movie.mkv"),
+                matched_by: FileMatch::LengthAndName,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_collections_match_by_pieces_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = vec![7u8; 40_000];
+        let old = build(
+            release(
+                dir.path(),
+                "episode-1",
+                &[("intro.bin", &data), ("y", b"y")],
+            )
+            .version(MetaVersion::V2)
+            .collection("series"),
+        );
+        let new = build(
+            release(
+                dir.path(),
+                "episode-2",
+                &[("opening.bin", &data), ("x", b"x")],
+            )
+            .version(MetaVersion::V2)
+            .collection("series"),
+        );
+
+        let local = [LocalTorrent {
+            metainfo: old.clone(),
+            root: dir.path().to_path_buf(),
+        }];
+        let candidates = find_reusable_files(&new, &local);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].file_index, 0);
+        assert_eq!(candidates[0].matched_by, FileMatch::PiecesRoot);
+        assert_eq!(candidates[0].source, old.info_hash);
+
+        // A torrent never reuses its own files
+        let local = [LocalTorrent {
+            metainfo: new.clone(),
+            root: dir.path().to_path_buf(),
+        }];
+        assert!(find_reusable_files(&new, &local).is_empty());
+    }
+
+    #[test]
+    fn test_reverse_similar_and_hash_matching() {
+        let dir = tempfile::tempdir().unwrap();
+        let new = build(release(dir.path(), "new", &[("a", b"abc")]));
+        let old = build(
+            release(dir.path(), "old", &[("a", b"abc")]).similar(InfoHash::Hybrid {
+                v1: new.info_hash.v1().unwrap(),
+                v2: [0; 32],
+            }),
+        );
+
+        let local = [LocalTorrent {
+            metainfo: old,
+            root: dir.path().to_path_buf(),
+        }];
+        assert_eq!(find_reusable_files(&new, &local).len(), 1);
+    }
+}
@@ -0,0 +1,195 @@
+use super::paths::disk_path;
+use crate::models::{file_entry::FileEntry, info::Info};
+use std::{io, path::Path};
+
+/// Applies BEP 47 attributes to downloaded files under `root`: creates symlinks
+/// and sets executable bits. Files that are not on disk yet are skipped; only
+/// Unix platforms have anything to apply
+pub fn apply_attributes(info: &Info, root: &Path) -> io::Result<()> {
+    for file in info.files() {
+        if file.attributes.symlink {
+            create_symlink(info, root, &file)?;
+        } else if file.attributes.executable {
+            set_executable(&disk_path(info, root, &file.path))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(info: &Info, root: &Path, file: &FileEntry) -> io::Result<()> {
+    use super::sanitizer::PathSanitizer;
+    use std::{
+        fs,
+        path::{Component, PathBuf},
+    };
+
+    let escapes = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "symlink path leaves the torrent directory",
+        )
+    };
+    let Some(target) = &file.symlink_path else {
+        return Ok(());
+    };
+    if target.iter().any(|component| {
+        component.is_empty() || component == "." || component == ".." || component.contains('/')
+    }) {
+        return Err(escapes());
+    }
+
+    let torrent_root = match info.is_multi_file() {
+        true => root.join(PathSanitizer::default().sanitize(std::slice::from_ref(&info.name))),
+        false => root.to_path_buf(),
+    };
+    // The link goes where the sanitized path puts it, so its depth must come
+    // from there rather than from the raw path
+    let link = disk_path(info, root, &file.path);
+    let depth = match link.strip_prefix(&torrent_root) {
+        Ok(path) if path.components().count() > 0 => path.components().count() - 1,
+        _ => return Err(escapes()),
+    };
+
+    // `symlink path` is relative to the torrent root; the link needs it relative to itself
+    let mut relative: PathBuf = (0..depth).map(|_| "..").collect();
+    relative.extend(target);
+
+    let parent = link.parent().ok_or_else(escapes)?;
+    let mut resolved = PathBuf::new();
+    for component in parent.join(&relative).components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            component => resolved.push(component),
+        }
+    }
+    if !resolved.starts_with(&torrent_root) || resolved == torrent_root {
+        return Err(escapes());
+    }
+
+    fs::create_dir_all(parent)?;
+    match fs::symlink_metadata(&link) {
+        Ok(metadata) if metadata.is_symlink() && fs::read_link(&link)? == relative => {
+            return Ok(());
+        }
+        // The placeholder a downloader may have created for the zero-length entry
+        Ok(metadata) if !metadata.is_dir() => fs::remove_file(&link)?,
+        _ => {}
+    }
+    std::os::unix::fs::symlink(relative, link)
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> io::Result<()> {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    // Grant execute to whoever may read, like `chmod +x` under the default umask
+    let mode = metadata.permissions().mode();
+    fs::set_permissions(path, fs::Permissions::from_mode(mode | (mode & 0o444) >> 2))
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_info: &Info, _root: &Path, _file: &FileEntry) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::parsers::metainfo::parse_metainfo;
+    use bencode::bencode;
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    fn info(symlink_path: &str) -> Info {
+        parse_metainfo(&bencode!({
+            "info": {
+                "name": "release",
+                "piece length": 16384,
+                "pieces": "",
+                "files": [
+                    { "length": 1, "path": ["bin", "tool"], "attr": "x" },
+                    { "length": 0, "path": ["bin", "latest"], "attr": "l",
+                      "symlink path": ["bin", symlink_path] },
+                    { "length": 0, "path": ["missing"], "attr": "x" },
+                ],
+            },
+        }))
+        .unwrap()
+        .info
+    }
+
+    #[test]
+    fn test_apply_attributes() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("release").join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(bin.join("tool"), b"x").unwrap();
+        fs::set_permissions(bin.join("tool"), fs::Permissions::from_mode(0o640)).unwrap();
+        fs::write(bin.join("latest"), b"").unwrap();
+
+        let info = info("tool");
+        apply_attributes(&info, dir.path()).unwrap();
+        // Applying twice is harmless
+        apply_attributes(&info, dir.path()).unwrap();
+
+        let mode = fs::metadata(bin.join("tool")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        assert_eq!(
+            fs::read_link(bin.join("latest")).unwrap(),
+            Path::new("../bin/tool")
+        );
+        assert_eq!(fs::read(bin.join("latest")).unwrap(), b"x");
+    }
+
+    #[test]
+    fn test_rejects_escaping_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        for target in ["..", "."] {
+            let err = apply_attributes(&info(target), dir.path()).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_symlink_depth_follows_sanitized_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = parse_metainfo(&bencode!({
+            "info": {
+                "name": "t",
+                "piece length": 16384,
+                "pieces": "",
+                "files": [
+                    { "length": 1, "path": ["etc"] },
+                    { "length": 0, "path": ["a", "..", "..", "evil"], "attr": "l",
+                      "symlink path": ["etc"] },
+                ],
+            },
+        }))
+        .unwrap()
+        .info;
+        fs::create_dir(dir.path().join("t")).unwrap();
+        fs::write(dir.path().join("t").join("etc"), b"x").unwrap();
+        apply_attributes(&info, dir.path()).unwrap();
+
+        let link = disk_path(&info, dir.path(), &info.files()[1].path);
+        assert!(link.starts_with(dir.path().join("t")));
+        let resolved = link.parent().unwrap().join(fs::read_link(&link).unwrap());
+        assert_eq!(
+            resolved.canonicalize().unwrap(),
+            dir.path().join("t").join("etc").canonicalize().unwrap()
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod attributes;
+pub mod paths;
+pub mod piece_reader;
+pub mod sanitizer;
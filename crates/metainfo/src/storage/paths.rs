@@ -0,0 +1,17 @@
+use super::sanitizer::PathSanitizer;
+use crate::models::info::Info;
+use std::path::{Path, PathBuf};
+
+/// Where a file of the torrent lives under the download directory `root`:
+/// `root/<name>` for single-file torrents, `root/<name>/<path>` otherwise.
+/// Names and paths are sanitised with the default `PathSanitizer` rules, so the
+/// result never leaves `root`
+pub fn disk_path(info: &Info, root: &Path, path: &[String]) -> PathBuf {
+    let sanitizer = PathSanitizer::default();
+    let mut disk_path = root.to_path_buf();
+    if info.is_multi_file() {
+        disk_path.push(sanitizer.sanitize(std::slice::from_ref(&info.name)));
+    }
+    disk_path.push(sanitizer.sanitize(path));
+    disk_path
+}
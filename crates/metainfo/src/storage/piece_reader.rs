@@ -0,0 +1,201 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct SourceFile {
+    pub(crate) path: PathBuf,
+    pub(crate) length: u64,
+    /// Path relative to the torrent root, empty for single-file torrents
+    pub(crate) components: Vec<String>,
+    /// Padding files read as zeros and have no path on disk
+    pub(crate) padding: bool,
+}
+
+enum OpenFile {
+    Handle(File),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+/// Reads byte ranges of the concatenated source files
+pub(crate) struct PieceReader<'a> {
+    files: &'a [SourceFile],
+    starts: Vec<u64>,
+    memory_map: bool,
+    open: Option<(usize, OpenFile)>,
+}
+
+impl<'a> PieceReader<'a> {
+    pub(crate) fn new(files: &'a [SourceFile]) -> Self {
+        let starts = files
+            .iter()
+            .scan(0, |offset, file| {
+                let start = *offset;
+                *offset += file.length;
+                Some(start)
+            })
+            .collect();
+        PieceReader {
+            files,
+            starts,
+            memory_map: false,
+            open: None,
+        }
+    }
+
+    /// Maps files into memory instead of reading them; needs the `mmap` feature
+    #[cfg(feature = "mmap")]
+    pub(crate) fn memory_mapped(mut self) -> Self {
+        self.memory_map = true;
+        self
+    }
+
+    /// Offset of the file at `index` within the concatenated data
+    pub(crate) fn file_start(&self, index: usize) -> u64 {
+        self.starts[index]
+    }
+
+    /// Fills `buffer` from `offset`; files that are missing or too short fail with
+    /// `NotFound` or `UnexpectedEof`
+    pub(crate) fn read_at(&mut self, mut offset: u64, mut buffer: &mut [u8]) -> io::Result<()> {
+        // Last file starting at or before the offset; zero-length files are skipped below
+        let mut index = self.starts.partition_point(|&start| start <= offset) - 1;
+
+        while !buffer.is_empty() {
+            let file_end = self.starts[index] + self.files[index].length;
+            if offset >= file_end {
+                index += 1;
+                continue;
+            }
+
+            let file_offset = offset - self.starts[index];
+            let chunk = ((file_end - offset) as usize).min(buffer.len());
+            if self.files[index].padding {
+                buffer[..chunk].fill(0);
+            } else {
+                self.read_file(index, file_offset, &mut buffer[..chunk])?;
+            }
+
+            buffer = &mut buffer[chunk..];
+            offset += chunk as u64;
+            index += 1;
+        }
+
+        Ok(())
+    }
+
+    fn read_file(&mut self, index: usize, offset: u64, buffer: &mut [u8]) -> io::Result<()> {
+        match self.file(index)? {
+            OpenFile::Handle(file) => {
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(buffer)
+            }
+            #[cfg(feature = "mmap")]
+            OpenFile::Mapped(map) => {
+                let data = usize::try_from(offset)
+                    .ok()
+                    .and_then(|start| map.get(start..start + buffer.len()))
+                    .ok_or(io::ErrorKind::UnexpectedEof)?;
+                buffer.copy_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+
+    fn file(&mut self, index: usize) -> io::Result<&mut OpenFile> {
+        if self.open.as_ref().is_none_or(|(open, _)| *open != index) {
+            let file = File::open(&self.files[index].path)?;
+            self.open = Some((index, self.wrap(file)?));
+        }
+        Ok(&mut self.open.as_mut().expect("file was just opened").1)
+    }
+
+    #[cfg(feature = "mmap")]
+    fn wrap(&self, file: File) -> io::Result<OpenFile> {
+        if !self.memory_map {
+            return Ok(OpenFile::Handle(file));
+        }
+        // SAFETY: the map is only read, and a file truncated underneath us is the
+        // same hazard every memory-mapped reader accepts
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(OpenFile::Mapped(map))
+    }
+
+    #[cfg(not(feature = "mmap"))]
+    fn wrap(&self, file: File) -> io::Result<OpenFile> {
+        debug_assert!(!self.memory_map);
+        Ok(OpenFile::Handle(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn source(path: PathBuf, length: u64) -> SourceFile {
+        SourceFile {
+            path,
+            length,
+            components: Vec::new(),
+            padding: false,
+        }
+    }
+
+    #[test]
+    fn test_read_across_files_and_padding() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), b"abc").unwrap();
+        fs::write(dir.path().join("b"), b"defg").unwrap();
+        let files = [
+            source(dir.path().join("a"), 3),
+            SourceFile {
+                padding: true,
+                ..source(PathBuf::new(), 2)
+            },
+            source(dir.path().join("empty"), 0),
+            source(dir.path().join("b"), 4),
+        ];
+
+        let mut reader = PieceReader::new(&files);
+        let mut buffer = [0xff; 7];
+        reader.read_at(1, &mut buffer).unwrap();
+        assert_eq!(&buffer, b"bc\0\0def");
+        assert_eq!(reader.file_start(3), 5);
+    }
+
+    #[test]
+    fn test_missing_and_short_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("short"), b"ab").unwrap();
+        let files = [
+            source(dir.path().join("short"), 4),
+            source(dir.path().join("missing"), 4),
+        ];
+
+        let mut reader = PieceReader::new(&files);
+        let mut buffer = [0; 4];
+        let short = reader.read_at(0, &mut buffer).unwrap_err();
+        assert_eq!(short.kind(), io::ErrorKind::UnexpectedEof);
+        let missing = reader.read_at(4, &mut buffer).unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_memory_mapped_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), b"abcdef").unwrap();
+        let files = [source(dir.path().join("a"), 8)];
+
+        let mut reader = PieceReader::new(&files).memory_mapped();
+        let mut buffer = [0; 4];
+        reader.read_at(1, &mut buffer).unwrap();
+        assert_eq!(&buffer, b"bcde");
+        let short = reader.read_at(4, &mut buffer).unwrap_err();
+        assert_eq!(short.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,466 @@
+use crate::{enums::path_error::PathError, models::info::Info};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// Longest file name most Linux file systems accept, in bytes
+pub const MAX_COMPONENT_LENGTH: usize = 255;
+
+/// Names Windows reserves for devices, with or without an extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turns the untrusted names and paths of a torrent into relative paths that stay
+/// under the download directory.
+///
+/// Empty, `.` and `..` components are dropped. Separators, NUL and other control
+/// characters are replaced, and components longer than the limit are truncated,
+/// keeping short extensions.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PathSanitizer {
+    replacement: char,
+    rules: BTreeMap<char, String>,
+    max_component_length: usize,
+    windows_compatible: bool,
+}
+
+impl Default for PathSanitizer {
+    fn default() -> Self {
+        PathSanitizer {
+            replacement: '_',
+            rules: BTreeMap::new(),
+            max_component_length: MAX_COMPONENT_LENGTH,
+            windows_compatible: false,
+        }
+    }
+}
+
+impl PathSanitizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Character that stands in for unsafe characters, `_` by default. An unsafe
+    /// replacement is itself replaced by `_`
+    pub fn replacement(mut self, replacement: char) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    /// Replaces every `from` with `to`, taking precedence over the default rules.
+    /// Unsafe characters in `to` are still replaced
+    pub fn replace(mut self, from: char, to: impl Into<String>) -> Self {
+        self.rules.insert(from, to.into());
+        self
+    }
+
+    /// Longest component in bytes, 255 by default
+    pub fn max_component_length(mut self, length: usize) -> Self {
+        self.max_component_length = length.max(1);
+        self
+    }
+
+    /// Also avoids names Windows rejects: the characters `<>:"\|?*`, trailing dots
+    /// and spaces and reserved device names. Collisions are then detected
+    /// case-insensitively
+    pub fn windows_compatible(mut self, windows_compatible: bool) -> Self {
+        self.windows_compatible = windows_compatible;
+        self
+    }
+
+    /// Sanitises a single name; `None` when nothing is left or the name only
+    /// refers to the current or parent directory
+    pub fn sanitize_component(&self, component: &str) -> Option<String> {
+        let mut name = String::with_capacity(component.len());
+        for c in component.chars() {
+            match self.rules.get(&c) {
+                Some(to) => name.extend(to.chars().map(|c| self.safe_char(c))),
+                None => name.push(self.safe_char(c)),
+            }
+        }
+
+        self.finish_component(&mut name);
+        // Checked on the final name, as trimming can turn `CON.` into `CON`
+        if self.windows_compatible && is_reserved_name(&name) {
+            name.insert(0, self.safe_char(self.replacement));
+            self.finish_component(&mut name);
+        }
+        match name.as_str() {
+            "" | "." | ".." => None,
+            _ => Some(name),
+        }
+    }
+
+    /// Truncates `name`, then trims what Windows would, as truncation can
+    /// leave a trailing dot or a bare `..`
+    fn finish_component(&self, name: &mut String) {
+        truncate_component(name, self.max_component_length);
+        if self.windows_compatible {
+            name.truncate(name.trim_end_matches(['.', ' ']).len());
+        }
+    }
+
+    /// Sanitises a path into a relative path that is never empty
+    pub fn sanitize(&self, path: &[String]) -> PathBuf {
+        let path: PathBuf = path
+            .iter()
+            .filter_map(|component| self.sanitize_component(component))
+            .collect();
+        match path.as_os_str().is_empty() {
+            true => PathBuf::from(self.safe_char(self.replacement).to_string()),
+            false => path,
+        }
+    }
+
+    /// Where each file of `Info::files()` lives relative to the download directory:
+    /// `<name>` for single-file torrents, `<name>/<path>` otherwise. Fails when two
+    /// files end up at the same path, or when a file would have to be a directory
+    pub fn map_files(&self, info: &Info) -> Result<Vec<PathBuf>, PathError> {
+        let name = self.sanitize(std::slice::from_ref(&info.name));
+        let paths: Vec<PathBuf> = match info.is_multi_file() {
+            true => info
+                .files()
+                .iter()
+                .map(|file| name.join(self.sanitize(&file.path)))
+                .collect(),
+            false => vec![name],
+        };
+
+        let mut files = HashMap::new();
+        let mut directories = HashMap::new();
+        for (index, path) in paths.iter().enumerate() {
+            let key = self.collision_key(path);
+            let collision = files
+                .get(&key)
+                .or_else(|| directories.get(&key))
+                .or_else(|| key.ancestors().skip(1).find_map(|dir| files.get(dir)));
+            if let Some(&first) = collision {
+                return Err(PathError::Collision {
+                    first,
+                    second: index,
+                    path: path.clone(),
+                });
+            }
+
+            for directory in key.ancestors().skip(1) {
+                directories.entry(directory.to_path_buf()).or_insert(index);
+            }
+            files.insert(key, index);
+        }
+
+        Ok(paths)
+    }
+
+    fn safe_char(&self, c: char) -> char {
+        let unsafe_char = |c: char| {
+            c == '/'
+                || c.is_control()
+                || (self.windows_compatible
+                    && matches!(c, '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*'))
+        };
+        match (unsafe_char(c), unsafe_char(self.replacement)) {
+            (false, _) => c,
+            (true, false) => self.replacement,
+            (true, true) => '_',
+        }
+    }
+
+    fn collision_key(&self, path: &Path) -> PathBuf {
+        match self.windows_compatible {
+            true => PathBuf::from(path.to_string_lossy().to_lowercase()),
+            false => path.to_path_buf(),
+        }
+    }
+}
+
+fn is_reserved_name(name: &str) -> bool {
+    // Windows also ignores spaces before the extension, as in `LPT1 .txt`
+    let stem = name
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(' ');
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Cuts `name` down to `max` bytes on a character boundary, keeping an extension
+/// that is no longer than a quarter of the limit
+fn truncate_component(name: &mut String, max: usize) {
+    if name.len() <= max {
+        return;
+    }
+
+    let extension = match name.rfind('.') {
+        Some(dot) if dot > 0 && name.len() - dot <= max / 4 => name.split_off(dot),
+        _ => String::new(),
+    };
+    let mut end = max - extension.len();
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    name.truncate(end);
+    name.push_str(&extension);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enums::{file_mode::FileMode, meta_version::MetaVersion},
+        models::file_entry::FileEntry,
+        parsers::metainfo::decode_metainfo,
+        storage::paths::disk_path,
+    };
+    use bencode::{bencode, dispatcher::bencode::encode};
+    use std::path::Component;
+
+    fn path(components: &[&str]) -> Vec<String> {
+        components.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn info(name: &str, files: &[&[&str]]) -> Info {
+        Info {
+            name: name.to_string(),
+            piece_length: 16384,
+            pieces: Vec::new(),
+            private: false,
+            mode: FileMode::Multiple {
+                files: files
+                    .iter()
+                    .map(|components| FileEntry {
+                        length: 1,
+                        path: path(components),
+                        ..Default::default()
+                    })
+                    .collect(),
+            },
+            meta_version: MetaVersion::V1,
+            file_tree: None,
+            similar: Vec::new(),
+            collections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_traversal_is_dropped() {
+        let sanitizer = PathSanitizer::new();
+        for (hostile, expected) in [
+            (&["..", "..", "etc", "passwd"][..], "etc/passwd"),
+            (&["a", "..", "b"], "a/b"),
+            (&[".", "", "a"], "a"),
+            (&["/etc/passwd"], "_etc_passwd"),
+            (&["a/../../b"], "a_.._.._b"),
+            (&["..", "."], "_"),
+            (&[], "_"),
+        ] {
+            let sanitized = sanitizer.sanitize(&path(hostile));
+            assert_eq!(sanitized, PathBuf::from(expected), "{hostile:?}");
+            assert!(sanitized.is_relative());
+        }
+    }
+
+    #[test]
+    fn test_unsafe_characters_are_replaced() {
+        let sanitizer = PathSanitizer::new();
+        assert_eq!(
+            sanitizer.sanitize_component("a\0b\nc\u{7f}d"),
+            Some("a_b_c_d".to_string())
+        );
+        // Only separators and control characters matter on Linux
+        assert_eq!(
+            sanitizer.sanitize_component("a\\b:c*?. "),
+            Some("a\\b:c*?. ".to_string())
+        );
+        assert_eq!(sanitizer.sanitize_component("...").as_deref(), Some("..."));
+        assert_eq!(sanitizer.sanitize_component("\0"), Some("_".to_string()));
+    }
+
+    #[test]
+    fn test_replacement_rules() {
+        let sanitizer = PathSanitizer::new()
+            .replacement('-')
+            .replace(' ', "_")
+            .replace('#', "/x\0");
+        assert_eq!(
+            sanitizer.sanitize_component("a b/c#"),
+            Some("a_b-c-x-".to_string())
+        );
+
+        let sanitizer = PathSanitizer::new().replacement('/');
+        assert_eq!(sanitizer.sanitize_component("a/b"), Some("a_b".to_string()));
+        assert_eq!(sanitizer.sanitize(&[]), PathBuf::from("_"));
+    }
+
+    #[test]
+    fn test_windows_compatible() {
+        let sanitizer = PathSanitizer::new().windows_compatible(true);
+        assert_eq!(
+            sanitizer.sanitize_component("a<b>c:d\"e\\f|g?h*"),
+            Some("a_b_c_d_e_f_g_h_".to_string())
+        );
+        assert_eq!(
+            sanitizer.sanitize_component("name. . "),
+            Some("name".to_string())
+        );
+        assert_eq!(sanitizer.sanitize_component(". ."), None);
+        assert_eq!(
+            sanitizer.sanitize_component("con"),
+            Some("_con".to_string())
+        );
+        assert_eq!(
+            sanitizer.sanitize_component("LPT1.txt"),
+            Some("_LPT1.txt".to_string())
+        );
+        for (reserved, expected) in [
+            ("CON ", "_CON"),
+            ("aux.", "_aux"),
+            ("LPT1 .txt", "_LPT1 .txt"),
+        ] {
+            assert_eq!(
+                sanitizer.sanitize_component(reserved).as_deref(),
+                Some(expected),
+                "{reserved:?}"
+            );
+        }
+        assert_eq!(
+            sanitizer.sanitize_component("CONSOLE"),
+            Some("CONSOLE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_overlong_components_are_truncated() {
+        let sanitizer = PathSanitizer::new();
+        let long = "a".repeat(300);
+        assert_eq!(sanitizer.sanitize_component(&long).unwrap().len(), 255);
+
+        let with_extension = format!("{long}.mkv");
+        let sanitized = sanitizer.sanitize_component(&with_extension).unwrap();
+        assert_eq!(sanitized.len(), 255);
+        assert!(sanitized.ends_with("a.mkv"));
+
+        // Multi-byte characters are never split
+        let sanitizer = PathSanitizer::new().max_component_length(5);
+        assert_eq!(sanitizer.sanitize_component("ééé"), Some("éé".to_string()));
+        assert_eq!(
+            sanitizer.sanitize_component("abcdef.extension"),
+            Some("abcde".to_string())
+        );
+        let sanitizer = PathSanitizer::new().max_component_length(2);
+        assert_eq!(sanitizer.sanitize_component("..."), None);
+        let sanitizer = sanitizer.windows_compatible(true);
+        assert_eq!(sanitizer.sanitize_component("a.b"), Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_map_files() {
+        let sanitizer = PathSanitizer::new();
+        let paths = sanitizer
+            .map_files(&info("../dir", &[&["..", "a"], &["sub", "b"]]))
+            .unwrap();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from(".._dir/a"), PathBuf::from(".._dir/sub/b")]
+        );
+
+        let single = Info {
+            mode: FileMode::Single { length: 1 },
+            ..info("/etc/passwd", &[])
+        };
+        assert_eq!(
+            sanitizer.map_files(&single).unwrap(),
+            vec![PathBuf::from("_etc_passwd")]
+        );
+    }
+
+    #[test]
+    fn test_collisions() {
+        let sanitizer = PathSanitizer::new();
+        assert_eq!(
+            sanitizer.map_files(&info("dir", &[&["a"], &["..", "a"]])),
+            Err(PathError::Collision {
+                first: 0,
+                second: 1,
+                path: PathBuf::from("dir/a"),
+            })
+        );
+        assert_eq!(
+            sanitizer.map_files(&info("dir", &[&["x", "a\0"], &["x", "a\n"]])),
+            Err(PathError::Collision {
+                first: 0,
+                second: 1,
+                path: PathBuf::from("dir/x/a_"),
+            })
+        );
+
+        // A file cannot also be a directory, in either order
+        assert_eq!(
+            sanitizer.map_files(&info("dir", &[&["a"], &["a", "b"]])),
+            Err(PathError::Collision {
+                first: 0,
+                second: 1,
+                path: PathBuf::from("dir/a/b"),
+            })
+        );
+        assert_eq!(
+            sanitizer.map_files(&info("dir", &[&["a", "b"], &["c"], &["a"]])),
+            Err(PathError::Collision {
+                first: 0,
+                second: 2,
+                path: PathBuf::from("dir/a"),
+            })
+        );
+
+        // Case only collides where the file system ignores it
+        let files: &[&[&str]] = &[&["README"], &["readme"]];
+        assert!(sanitizer.map_files(&info("dir", files)).is_ok());
+        let windows = PathSanitizer::new().windows_compatible(true);
+        assert!(windows.map_files(&info("dir", files)).is_err());
+    }
+
+    #[test]
+    fn test_hostile_torrent_stays_under_root() {
+        let torrent = encode(&bencode!({
+            "info": {
+                "name": "..",
+                "piece length": 16384,
+                "files": [
+                    { "length": 1, "path": ["..", "..", "..", "home", "user", ".bashrc"] },
+                    { "length": 1, "path": ["/", "etc", "cron.d", "job"] },
+                    { "length": 1, "path": ["nul\0byte"] },
+                    { "length": 1, "path": ["x".repeat(1000)] },
+                    { "length": 1, "path": ["../../escape"] },
+                ],
+                "pieces": vec![0u8; 20],
+            },
+        }))
+        .unwrap();
+        let info = decode_metainfo(&torrent).unwrap().info;
+
+        let root = Path::new("/downloads");
+        let paths = PathSanitizer::new().map_files(&info).unwrap();
+        for (file, relative) in info.files().iter().zip(&paths) {
+            assert!(
+                relative
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)))
+            );
+            assert_eq!(disk_path(&info, root, &file.path), root.join(relative));
+        }
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("_/home/user/.bashrc"),
+                PathBuf::from("_/_/etc/cron.d/job"),
+                PathBuf::from("_/nul_byte"),
+                PathBuf::from(format!("_/{}", "x".repeat(255))),
+                PathBuf::from("_/.._.._escape"),
+            ]
+        );
+    }
+}
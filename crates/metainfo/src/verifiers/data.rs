@@ -0,0 +1,468 @@
+use crate::{
+    enums::{file_tree::flatten_file_tree, verify_error::VerifyError},
+    hashing::{
+        merkle::piece_node,
+        parallel::{HashingRun, ProgressCallback},
+    },
+    models::{
+        bitfield::Bitfield,
+        file_layout::FileLayout,
+        info::MAX_PIECE_LENGTH,
+        metainfo::Metainfo,
+        progress::Progress,
+        verification::{FileCompletion, Verification},
+    },
+    storage::{
+        paths::disk_path,
+        piece_reader::{PieceReader, SourceFile},
+    },
+};
+use sha1::{Digest, Sha1};
+use std::{
+    io,
+    path::PathBuf,
+    sync::{Arc, atomic::AtomicBool},
+    thread,
+};
+
+/// Checks downloaded data against a torrent's piece hashes
+pub struct DataVerifier<'a> {
+    metainfo: &'a Metainfo,
+    root: PathBuf,
+    threads: usize,
+    #[cfg(feature = "mmap")]
+    memory_map: bool,
+    progress: Option<ProgressCallback>,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Verifies the data of `metainfo` stored under the download directory `root`
+pub fn verify(metainfo: &Metainfo, root: impl Into<PathBuf>) -> Result<Verification, VerifyError> {
+    DataVerifier::new(metainfo, root).verify()
+}
+
+impl<'a> DataVerifier<'a> {
+    /// Single-file torrents are read from `root/<name>`, multi-file ones from
+    /// `root/<name>/<path>`
+    pub fn new(metainfo: &'a Metainfo, root: impl Into<PathBuf>) -> Self {
+        DataVerifier {
+            metainfo,
+            root: root.into(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            #[cfg(feature = "mmap")]
+            memory_map: false,
+            progress: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Memory-maps files instead of reading them
+    #[cfg(feature = "mmap")]
+    pub fn memory_map(mut self, memory_map: bool) -> Self {
+        self.memory_map = memory_map;
+        self
+    }
+
+    /// Called after every piece, from whichever thread hashed it
+    pub fn on_progress(mut self, callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Raising the flag makes `verify` stop with `VerifyError::Cancelled`
+    pub fn cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn verify(&self) -> Result<Verification, VerifyError> {
+        // Every thread buffers a whole piece, and the length comes from the torrent
+        let piece_length = self.metainfo.info.piece_length;
+        if piece_length > MAX_PIECE_LENGTH {
+            return Err(VerifyError::PieceLengthTooLarge(piece_length));
+        }
+        // Hybrid torrents are checked through their v1 pieces, which also cover padding
+        match self.metainfo.info.meta_version.has_v1() {
+            true => self.verify_v1(),
+            false => self.verify_v2(),
+        }
+    }
+
+    fn verify_v1(&self) -> Result<Verification, VerifyError> {
+        let info = &self.metainfo.info;
+        let layout = FileLayout::from_info(info).map_err(VerifyError::Layout)?;
+        let entries = info.all_files();
+        let files: Vec<SourceFile> = entries
+            .iter()
+            .map(|entry| SourceFile {
+                path: self.disk_path(&entry.path),
+                length: entry.length,
+                components: entry.path.clone(),
+                padding: entry.is_padding(),
+            })
+            .collect();
+
+        let piece_length = info.piece_length;
+        let total_length = info.total_length();
+        let piece_count = info.pieces.len();
+        let run = self.run(piece_count, total_length);
+
+        let valid = run.run(
+            piece_count,
+            || (self.reader(&files), vec![0u8; piece_length as usize]),
+            |(reader, buffer), index| {
+                let offset = index as u64 * piece_length;
+                let length = piece_length.min(total_length.saturating_sub(offset)) as usize;
+                let valid = match reader.read_at(offset, &mut buffer[..length]) {
+                    Ok(()) => Sha1::digest(&buffer[..length]).as_slice() == info.pieces[index],
+                    Err(err) if is_missing(&err) => false,
+                    Err(err) => return Err(VerifyError::Io(err)),
+                };
+                Ok((valid, length as u64))
+            },
+            || VerifyError::Cancelled,
+        )?;
+
+        let mut completion = Vec::with_capacity(files.len());
+        for (index, file) in files.iter().enumerate() {
+            if file.padding {
+                continue;
+            }
+            let bytes_complete = layout
+                .file_to_piece_range(index)
+                .filter(|&piece| valid.get(piece) == Some(&true))
+                .flat_map(|piece| layout.piece_to_file_ranges(piece))
+                .filter(|file_range| file_range.file == index)
+                .map(|file_range| file_range.range.end - file_range.range.start)
+                .sum();
+            completion.push(FileCompletion {
+                path: file.components.clone(),
+                length: file.length,
+                bytes_complete,
+            });
+        }
+
+        Ok(Verification {
+            pieces: to_bitfield(&valid),
+            files: completion,
+        })
+    }
+
+    fn verify_v2(&self) -> Result<Verification, VerifyError> {
+        let info = &self.metainfo.info;
+        let tree = info
+            .file_tree
+            .as_ref()
+            .expect("v2 torrents always have a file tree");
+        let entries = flatten_file_tree(tree);
+        let files: Vec<SourceFile> = entries
+            .iter()
+            .map(|(path, file)| SourceFile {
+                path: self.disk_path(path),
+                length: file.length,
+                components: path.clone(),
+                padding: false,
+            })
+            .collect();
+
+        // v2 pieces never span files, so each file starts a new piece
+        let piece_length = info.piece_length;
+        let jobs: Vec<(usize, u64)> = files
+            .iter()
+            .enumerate()
+            .flat_map(|(index, file)| {
+                (0..file.length.div_ceil(piece_length)).map(move |piece| (index, piece))
+            })
+            .collect();
+        let total_length = files.iter().map(|file| file.length).sum();
+        let run = self.run(jobs.len(), total_length);
+
+        let valid = run.run(
+            jobs.len(),
+            || (self.reader(&files), vec![0u8; piece_length as usize]),
+            |(reader, buffer), job| {
+                let (index, piece) = jobs[job];
+                let file = &files[index];
+                let offset = piece * piece_length;
+                let length = piece_length.min(file.length - offset) as usize;
+                let pieces_root = entries[index]
+                    .1
+                    .pieces_root
+                    .expect("non-empty files have a pieces root");
+                // Files of one piece are checked against their root directly
+                let expected = match file.length <= piece_length {
+                    true => Some(&pieces_root),
+                    false => self
+                        .metainfo
+                        .piece_layers
+                        .get(&pieces_root)
+                        .and_then(|layer| layer.get(piece as usize)),
+                };
+
+                let valid = match reader
+                    .read_at(reader.file_start(index) + offset, &mut buffer[..length])
+                {
+                    Ok(()) => {
+                        expected == Some(&piece_node(&buffer[..length], file.length, piece_length))
+                    }
+                    Err(err) if is_missing(&err) => false,
+                    Err(err) => return Err(VerifyError::Io(err)),
+                };
+                Ok((valid, length as u64))
+            },
+            || VerifyError::Cancelled,
+        )?;
+
+        let mut completion: Vec<FileCompletion> = files
+            .iter()
+            .map(|file| FileCompletion {
+                path: file.components.clone(),
+                length: file.length,
+                bytes_complete: 0,
+            })
+            .collect();
+        for (&(index, piece), _) in jobs.iter().zip(&valid).filter(|(_, valid)| **valid) {
+            let offset = piece * piece_length;
+            completion[index].bytes_complete += piece_length.min(files[index].length - offset);
+        }
+
+        Ok(Verification {
+            pieces: to_bitfield(&valid),
+            files: completion,
+        })
+    }
+
+    fn run(&self, piece_count: usize, total_bytes: u64) -> HashingRun<'_> {
+        HashingRun::new(
+            self.threads,
+            &self.cancel,
+            self.progress.as_ref(),
+            piece_count,
+            total_bytes,
+        )
+    }
+
+    fn reader<'f>(&self, files: &'f [SourceFile]) -> PieceReader<'f> {
+        let reader = PieceReader::new(files);
+        #[cfg(feature = "mmap")]
+        if self.memory_map {
+            return reader.memory_mapped();
+        }
+        reader
+    }
+
+    fn disk_path(&self, path: &[String]) -> PathBuf {
+        disk_path(&self.metainfo.info, &self.root, path)
+    }
+}
+
+/// Missing and truncated files just leave their pieces incomplete
+fn is_missing(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::UnexpectedEof
+    )
+}
+
+fn to_bitfield(valid: &[bool]) -> Bitfield {
+    let mut bitfield = Bitfield::new(valid.len());
+    for (index, _) in valid.iter().enumerate().filter(|(_, valid)| **valid) {
+        bitfield.set(index, true);
+    }
+    bitfield
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builders::torrent::TorrentBuilder, enums::meta_version::MetaVersion,
+        parsers::metainfo::decode_metainfo,
+    };
+    use std::{
+        fs,
+        path::Path,
+        sync::{
+            Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    const PIECE: u64 = 16384;
+
+    fn pattern(length: usize, seed: u8) -> Vec<u8> {
+        (0..length)
+            .map(|i| (i as u8).wrapping_mul(29) ^ seed)
+            .collect()
+    }
+
+    /// `release/a.bin` (20 000 bytes) and `release/sub/b.bin` (40 000 bytes)
+    fn release(dir: &Path, version: MetaVersion) -> Metainfo {
+        let root = dir.join("release");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("a.bin"), pattern(20_000, 1)).unwrap();
+        fs::write(root.join("sub").join("b.bin"), pattern(40_000, 2)).unwrap();
+
+        let torrent = TorrentBuilder::new(&root)
+            .piece_length(PIECE)
+            .version(version)
+            .build()
+            .unwrap();
+        decode_metainfo(&torrent).unwrap()
+    }
+
+    #[test]
+    fn test_complete_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let metainfo = release(dir.path(), MetaVersion::V1);
+
+        let verification = verify(&metainfo, dir.path()).unwrap();
+        assert!(verification.is_complete());
+        assert_eq!(verification.pieces.len(), 4);
+        assert_eq!(verification.files.len(), 2);
+        assert!(verification.files.iter().all(FileCompletion::is_complete));
+    }
+
+    #[test]
+    fn test_corrupt_missing_and_short_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let metainfo = release(dir.path(), MetaVersion::V1);
+        let root = dir.path().join("release");
+
+        // Corrupting the second piece leaves a.bin with only its first piece
+        let mut a = fs::read(root.join("a.bin")).unwrap();
+        a[PIECE as usize] ^= 0xff;
+        fs::write(root.join("a.bin"), &a).unwrap();
+        let verification = DataVerifier::new(&metainfo, dir.path())
+            .threads(2)
+            .verify()
+            .unwrap();
+        let pieces: Vec<bool> = verification.pieces.iter().collect();
+        assert_eq!(pieces, vec![true, false, true, true]);
+        assert_eq!(verification.files[0].bytes_complete, PIECE);
+        assert_eq!(verification.files[1].bytes_complete, 40_000 - 12_768);
+
+        fs::write(root.join("sub").join("b.bin"), pattern(30_000, 2)).unwrap();
+        let verification = verify(&metainfo, dir.path()).unwrap();
+        let pieces: Vec<bool> = verification.pieces.iter().collect();
+        assert_eq!(pieces, vec![true, false, true, false]);
+
+        fs::remove_file(root.join("a.bin")).unwrap();
+        let verification = verify(&metainfo, dir.path()).unwrap();
+        assert_eq!(verification.pieces.count_ones(), 1);
+        assert_eq!(verification.files[0].bytes_complete, 0);
+    }
+
+    #[test]
+    fn test_v2_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let metainfo = release(dir.path(), MetaVersion::V2);
+        let root = dir.path().join("release");
+
+        let verification = verify(&metainfo, dir.path()).unwrap();
+        // a.bin takes two pieces and b.bin three, since v2 files never share a piece
+        assert_eq!(verification.pieces.len(), 5);
+        assert!(verification.is_complete());
+
+        let mut b = fs::read(root.join("sub").join("b.bin")).unwrap();
+        b[39_999] ^= 0xff;
+        fs::write(root.join("sub").join("b.bin"), &b).unwrap();
+        let verification = verify(&metainfo, dir.path()).unwrap();
+        let pieces: Vec<bool> = verification.pieces.iter().collect();
+        assert_eq!(pieces, vec![true, true, true, true, false]);
+        assert_eq!(verification.files[1].bytes_complete, 2 * PIECE);
+    }
+
+    #[test]
+    fn test_single_piece_v2_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("small.bin");
+        fs::write(&path, pattern(5_000, 3)).unwrap();
+        let torrent = TorrentBuilder::new(&path)
+            .version(MetaVersion::V2)
+            .build()
+            .unwrap();
+        let metainfo = decode_metainfo(&torrent).unwrap();
+
+        assert!(verify(&metainfo, dir.path()).unwrap().is_complete());
+        fs::write(&path, pattern(5_000, 4)).unwrap();
+        assert!(!verify(&metainfo, dir.path()).unwrap().is_complete());
+    }
+
+    #[test]
+    fn test_hybrid_padding_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let metainfo = release(dir.path(), MetaVersion::Hybrid);
+
+        let verification = verify(&metainfo, dir.path()).unwrap();
+        assert!(verification.is_complete());
+        // The padding file after a.bin is not reported
+        assert_eq!(verification.files.len(), 2);
+        assert_eq!(verification.files[1].path, vec!["sub", "b.bin"]);
+    }
+
+    #[test]
+    fn test_progress_and_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let metainfo = release(dir.path(), MetaVersion::V1);
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let last = Arc::new(Mutex::new(None));
+        let (calls_seen, last_seen) = (Arc::clone(&calls), Arc::clone(&last));
+        DataVerifier::new(&metainfo, dir.path())
+            .on_progress(move |progress| {
+                calls_seen.fetch_add(1, Ordering::SeqCst);
+                let mut last = last_seen.lock().unwrap();
+                if last.is_none_or(|last: Progress| progress.pieces_done > last.pieces_done) {
+                    *last = Some(progress);
+                }
+            })
+            .verify()
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(last.lock().unwrap().unwrap().bytes_done, 60_000);
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        assert!(matches!(
+            DataVerifier::new(&metainfo, dir.path())
+                .cancel_flag(cancel)
+                .verify(),
+            Err(VerifyError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_piece_length_too_large() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut metainfo = release(dir.path(), MetaVersion::V1);
+        metainfo.info.piece_length = 1 << 40;
+        assert!(matches!(
+            verify(&metainfo, dir.path()),
+            Err(VerifyError::PieceLengthTooLarge(length)) if length == 1 << 40
+        ));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_memory_mapped_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let metainfo = release(dir.path(), MetaVersion::V1);
+        fs::write(
+            dir.path().join("release").join("sub").join("b.bin"),
+            pattern(30_000, 2),
+        )
+        .unwrap();
+
+        let verification = DataVerifier::new(&metainfo, dir.path())
+            .memory_map(true)
+            .verify()
+            .unwrap();
+        let pieces: Vec<bool> = verification.pieces.iter().collect();
+        assert_eq!(pieces, vec![true, true, true, false]);
+    }
+}
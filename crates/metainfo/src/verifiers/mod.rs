@@ -0,0 +1,3 @@
+pub mod data;
+#[cfg(feature = "signatures")]
+pub mod signature;
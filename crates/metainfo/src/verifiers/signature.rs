@@ -0,0 +1,174 @@
+use crate::{
+    enums::{public_key::PublicKey, signature_error::SignatureError},
+    hashing::info_hash::raw_info_bytes,
+    parsers::metainfo::decode_metainfo,
+};
+
+/// Checks that the raw `.torrent` in `data` carries a BEP 35 signature from
+/// `signer` made with the trusted `key`
+pub fn verify_signature(data: &[u8], signer: &str, key: &PublicKey) -> Result<(), SignatureError> {
+    let metainfo = decode_metainfo(data)?;
+    let signature = metainfo
+        .signatures
+        .iter()
+        .find(|signature| signature.signer == signer)
+        .ok_or_else(|| SignatureError::MissingSignature(signer.to_string()))?;
+    signature.verify(raw_info_bytes(data)?, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editors::torrent::TorrentEditor,
+        enums::{error::MetainfoError, signing_key::SigningKey},
+        models::signature::Signature,
+    };
+
+    const ED25519_KEY: &[u8] = include_bytes!("../../tests/fixtures/ed25519.key.der");
+    const ED25519_CERT: &[u8] = include_bytes!("../../tests/fixtures/ed25519.cert.der");
+    const RSA_KEY: &[u8] = include_bytes!("../../tests/fixtures/rsa.key.der");
+    const RSA_CERT: &[u8] = include_bytes!("../../tests/fixtures/rsa.cert.der");
+
+    // `name` before `length`: signatures must cover the info bytes as written
+    const TORRENT: &[u8] = b"d8:announce3:url4:infod4:name1:a6:lengthi1e\
+        12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+
+    fn tamper(data: &[u8]) -> Vec<u8> {
+        let at = data.windows(9).position(|w| w == b"4:name1:a").unwrap();
+        let mut tampered = data.to_vec();
+        tampered[at + 8] = b'b';
+        tampered
+    }
+
+    fn signed(signer: &str, key: &[u8], certificate: Option<&[u8]>) -> Vec<u8> {
+        let key = SigningKey::from_pkcs8_der(key).unwrap();
+        let mut editor = TorrentEditor::new(TORRENT).unwrap();
+        editor.sign(signer, &key, certificate).unwrap();
+        editor.to_bytes()
+    }
+
+    #[test]
+    fn test_ed25519_signature() {
+        let data = signed("com.example", ED25519_KEY, Some(ED25519_CERT));
+        let metainfo = decode_metainfo(&data).unwrap();
+        assert_eq!(metainfo.signers().collect::<Vec<_>>(), vec!["com.example"]);
+        assert_eq!(metainfo.signatures[0].signature.len(), 64);
+
+        let key = PublicKey::from_certificate(ED25519_CERT).unwrap();
+        assert!(matches!(key, PublicKey::Ed25519(_)));
+        assert_eq!(verify_signature(&data, "com.example", &key), Ok(()));
+        assert_eq!(
+            verify_signature(&data, "org.example", &key),
+            Err(SignatureError::MissingSignature("org.example".to_string()))
+        );
+
+        let other = SigningKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(&[7; 32]));
+        assert_eq!(
+            verify_signature(&data, "com.example", &other.public_key()),
+            Err(SignatureError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_rsa_signature() {
+        let data = signed("com.example", RSA_KEY, Some(RSA_CERT));
+        let key = PublicKey::from_certificate(RSA_CERT).unwrap();
+        assert!(matches!(key, PublicKey::Rsa(_)));
+        assert_eq!(
+            SigningKey::from_pkcs8_der(RSA_KEY).unwrap().public_key(),
+            key
+        );
+        assert_eq!(verify_signature(&data, "com.example", &key), Ok(()));
+
+        let ed25519 = PublicKey::from_certificate(ED25519_CERT).unwrap();
+        assert_eq!(
+            verify_signature(&data, "com.example", &ed25519),
+            Err(SignatureError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_tampering_is_rejected_on_load() {
+        let data = signed("com.example", ED25519_KEY, Some(ED25519_CERT));
+        let tampered = tamper(&data);
+        assert_eq!(
+            decode_metainfo(&tampered),
+            Err(MetainfoError::InvalidSignature("com.example".to_string()))
+        );
+
+        // Without a certificate nothing can be checked until a key is supplied
+        let data = signed("com.example", ED25519_KEY, None);
+        let tampered = tamper(&data);
+        let metainfo = decode_metainfo(&tampered).unwrap();
+        assert_eq!(metainfo.signatures[0].certificate, None);
+        let key = SigningKey::from_pkcs8_der(ED25519_KEY)
+            .unwrap()
+            .public_key();
+        assert_eq!(
+            verify_signature(&tampered, "com.example", &key),
+            Err(SignatureError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_signature_info_is_signed() {
+        let key = SigningKey::from_pkcs8_der(ED25519_KEY).unwrap();
+        let info_bytes = raw_info_bytes(TORRENT).unwrap();
+        let mut signature = Signature::sign("com.example", info_bytes, &key, None).unwrap();
+        signature.info = Some(b"d7:expiresi10ee".to_vec());
+        signature.signature = key
+            .sign(&Signature::signed_message(
+                info_bytes,
+                signature.info.as_deref(),
+            ))
+            .unwrap();
+        assert_eq!(signature.verify(info_bytes, &key.public_key()), Ok(()));
+
+        signature.info = Some(b"d7:expiresi99ee".to_vec());
+        assert_eq!(
+            signature.verify(info_bytes, &key.public_key()),
+            Err(SignatureError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_editing_signatures() {
+        let key = SigningKey::from_pkcs8_der(ED25519_KEY).unwrap();
+        let mut editor = TorrentEditor::new(TORRENT).unwrap();
+        editor.sign("a.example", &key, None).unwrap();
+        editor.sign("b.example", &key, Some(ED25519_CERT)).unwrap();
+        let metainfo = decode_metainfo(&editor.to_bytes()).unwrap();
+        assert_eq!(
+            metainfo.signers().collect::<Vec<_>>(),
+            vec!["a.example", "b.example"]
+        );
+
+        assert!(editor.remove_signature("a.example"));
+        assert!(!editor.remove_signature("a.example"));
+        assert!(editor.remove_signature("b.example"));
+        assert_eq!(editor.get("signatures"), None);
+
+        // Changing the info dictionary invalidates every signature
+        editor.sign("a.example", &key, Some(ED25519_CERT)).unwrap();
+        editor.set_private(true).unwrap();
+        assert_eq!(editor.get("signatures"), None);
+        assert!(decode_metainfo(&editor.to_bytes()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        assert_eq!(
+            SigningKey::from_pkcs8_der(b"junk").err(),
+            Some(SignatureError::InvalidKey)
+        );
+        assert_eq!(
+            PublicKey::from_certificate(ED25519_KEY),
+            Err(SignatureError::InvalidCertificate)
+        );
+        assert_eq!(
+            PublicKey::from_public_key_der(b"junk"),
+            Err(SignatureError::InvalidKey)
+        );
+    }
+}
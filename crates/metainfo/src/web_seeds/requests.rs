@@ -0,0 +1,172 @@
+use crate::{
+    encoding::percent::{encode_path_segment, encode_percent_bytes},
+    enums::{error::MetainfoError, info_hash::InfoHash},
+    models::{file_layout::FileLayout, info::Info, web_seed_request::WebSeedRequest},
+};
+
+/// URL of one file on a BEP 19 web seed. A multi-file seed URL names the
+/// directory holding `<name>/`; a single-file one names the file itself, or
+/// its directory when it ends with `/`
+pub fn file_url(base: &str, info: &Info, path: &[String]) -> String {
+    if !info.is_multi_file() && !base.ends_with('/') {
+        return base.to_string();
+    }
+
+    let mut url = base.to_string();
+    if !url.ends_with('/') {
+        url.push('/');
+    }
+    url.push_str(&encode_path_segment(&info.name));
+    if info.is_multi_file() {
+        for component in path {
+            url.push('/');
+            url.push_str(&encode_path_segment(component));
+        }
+    }
+    url
+}
+
+/// Range requests that together fetch v1 piece `piece` from the web seed at `base`.
+/// Padding and zero-length files need no request, so their bytes are left to the
+/// caller (padding is all zeros); out-of-range pieces yield no requests
+pub fn piece_requests(
+    base: &str,
+    info: &Info,
+    piece: usize,
+) -> Result<Vec<WebSeedRequest>, MetainfoError> {
+    let layout = FileLayout::from_info(info)?;
+    Ok(layout
+        .piece_to_file_ranges(piece)
+        .into_iter()
+        .filter(|file_range| !layout.files()[file_range.file].is_padding())
+        .map(|file_range| WebSeedRequest {
+            url: file_url(base, info, &layout.files()[file_range.file].path),
+            range: file_range.range,
+            piece_offset: file_range.piece_offset,
+        })
+        .collect())
+}
+
+/// BEP 17 request URL for a whole piece from the HTTP seed at `base`
+pub fn http_seed_url(base: &str, info_hash: &InfoHash, piece: usize) -> String {
+    let separator = if base.contains('?') { '&' } else { '?' };
+    format!(
+        "{base}{separator}info_hash={}&piece={piece}",
+        encode_percent_bytes(&info_hash.truncated())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::metainfo::parse_metainfo;
+    use bencode::bencode;
+
+    fn multi_file_info() -> Info {
+        parse_metainfo(&bencode!({
+            "info": {
+                "name": "My Release",
+                "piece length": 16,
+                "pieces": (vec![0u8; 60]),
+                "files": [
+                    { "length": 10, "path": ["a.txt"] },
+                    { "length": 6, "path": [".pad", "6"], "attr": "p" },
+                    { "length": 0, "path": ["empty"] },
+                    { "length": 20, "path": ["sub dir", "b&c.bin"] },
+                ],
+            },
+        }))
+        .unwrap()
+        .info
+    }
+
+    fn single_file_info() -> Info {
+        let info = bencode!({
+            "name": "file.iso",
+            "piece length": 16,
+            "pieces": (vec![0u8; 40]),
+            "length": 20,
+        });
+        parse_metainfo(&bencode!({ "info": info })).unwrap().info
+    }
+
+    #[test]
+    fn test_file_urls() {
+        let multi = multi_file_info();
+        let path = ["sub dir".to_string(), "b&c.bin".to_string()];
+        assert_eq!(
+            file_url("http://mirror/pub", &multi, &path),
+            "http://mirror/pub/My%20Release/sub%20dir/b%26c.bin"
+        );
+        // Separators inside a component stay inside its segment
+        let path = ["a/b".to_string(), "..".to_string(), "c:d@e".to_string()];
+        assert_eq!(
+            file_url("http://mirror/pub", &multi, &path),
+            "http://mirror/pub/My%20Release/a%2Fb/%2E%2E/c%3Ad%40e"
+        );
+
+        let single = single_file_info();
+        assert_eq!(
+            file_url("http://mirror/pub/", &single, &[]),
+            "http://mirror/pub/file.iso"
+        );
+        assert_eq!(
+            file_url("http://mirror/file.iso", &single, &[]),
+            "http://mirror/file.iso"
+        );
+    }
+
+    #[test]
+    fn test_piece_spanning_files_and_padding() {
+        let info = multi_file_info();
+
+        // Piece 0 is a.txt followed by padding
+        let requests = piece_requests("http://m/", &info, 0).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url, "http://m/My%20Release/a.txt");
+        assert_eq!(requests[0].range, 0..10);
+        assert_eq!(requests[0].range_header().unwrap(), "bytes=0-9");
+
+        // Piece 1 starts b.bin after the empty file; the last piece is short
+        let requests = piece_requests("http://m/", &info, 1).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].range, 0..16);
+        assert_eq!(requests[0].piece_offset, 0);
+        let requests = piece_requests("http://m/", &info, 2).unwrap();
+        assert_eq!(requests[0].range, 16..20);
+        assert!(piece_requests("http://m/", &info, 3).unwrap().is_empty());
+
+        let empty = WebSeedRequest {
+            range: 0..0,
+            ..requests[0].clone()
+        };
+        assert_eq!(empty.range_header(), None);
+    }
+
+    #[test]
+    fn test_single_file_pieces() {
+        let info = single_file_info();
+        let requests = piece_requests("http://m/file.iso", &info, 1).unwrap();
+        assert_eq!(
+            requests,
+            vec![WebSeedRequest {
+                url: "http://m/file.iso".to_string(),
+                range: 16..20,
+                piece_offset: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_http_seed_url() {
+        let hash = InfoHash::V1([0xab; 20]);
+        assert_eq!(
+            http_seed_url("http://seed/serve", &hash, 3),
+            format!("http://seed/serve?info_hash={}&piece=3", "%AB".repeat(20))
+        );
+        assert!(
+            http_seed_url("http://seed/?id=1", &hash, 0)
+                .starts_with("http://seed/?id=1&info_hash=")
+        );
+    }
+}
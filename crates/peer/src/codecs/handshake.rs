@@ -0,0 +1,75 @@
+use crate::{
+    enums::peer_error::PeerError,
+    models::handshake::{HANDSHAKE_LEN, Handshake},
+};
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Frames the handshake that opens a connection. Once it is exchanged, switch
+/// to [`MessageCodec`](crate::codecs::message::MessageCodec) with
+/// `Framed::map_codec`, which keeps any bytes already buffered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HandshakeCodec;
+
+impl Decoder for HandshakeCodec {
+    type Item = Handshake;
+    type Error = PeerError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Handshake>, PeerError> {
+        let handshake = Handshake::decode(src)?;
+        if handshake.is_some() {
+            src.advance(HANDSHAKE_LEN);
+        } else {
+            src.reserve(HANDSHAKE_LEN - src.len());
+        }
+        Ok(handshake)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Handshake>, PeerError> {
+        match self.decode(src)? {
+            None if !src.is_empty() => Err(PeerError::Truncated),
+            handshake => Ok(handshake),
+        }
+    }
+}
+
+impl Encoder<Handshake> for HandshakeCodec {
+    type Error = PeerError;
+
+    fn encode(&mut self, handshake: Handshake, dst: &mut BytesMut) -> Result<(), PeerError> {
+        dst.extend_from_slice(&handshake.to_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::message::Message;
+
+    #[test]
+    fn test_handshake_then_messages() {
+        let handshake = Handshake::new([1; 20], [2; 20]);
+        let mut buf = BytesMut::new();
+        HandshakeCodec.encode(handshake, &mut buf).unwrap();
+        buf.extend_from_slice(&[0, 0, 0, 1, 2]);
+
+        assert_eq!(HandshakeCodec.decode(&mut buf), Ok(Some(handshake)));
+        let mut codec = crate::codecs::message::MessageCodec::new();
+        assert_eq!(
+            Decoder::decode(&mut codec, &mut buf),
+            Ok(Some(Message::Interested))
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut buf = BytesMut::from(&b"\x13BitTorrent protocol"[..]);
+        assert_eq!(HandshakeCodec.decode(&mut buf), Ok(None));
+        assert_eq!(
+            HandshakeCodec.decode_eof(&mut buf),
+            Err(PeerError::Truncated)
+        );
+        assert_eq!(HandshakeCodec.decode_eof(&mut BytesMut::new()), Ok(None));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::enums::{message::Message, peer_error::PeerError};
+
+/// Default for [`MessageCodec::max_length`]: room for a 16 KiB block or the
+/// bitfield of a torrent with two million pieces
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 1 << 18;
+
+/// Frames length-prefixed peer wire messages. [`MessageCodec::decode`] and
+/// [`MessageCodec::encode`] work on plain buffers; with the `tokio` feature the
+/// codec is also a tokio-util `Decoder` and `Encoder`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageCodec {
+    max_length: usize,
+}
+
+impl Default for MessageCodec {
+    fn default() -> Self {
+        MessageCodec {
+            max_length: DEFAULT_MAX_MESSAGE_LEN,
+        }
+    }
+}
+
+impl MessageCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest length prefix accepted or sent; longer messages are errors
+    /// rather than something to buffer
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Decodes the message at the start of `buf` and how many bytes it took;
+    /// `None` when more bytes are needed
+    pub fn decode(&self, buf: &[u8]) -> Result<Option<(Message, usize)>, PeerError> {
+        let Some(length) = self.frame_length(buf)? else {
+            return Ok(None);
+        };
+        if buf.len() < 4 + length {
+            return Ok(None);
+        }
+        let message = Message::decode(&buf[4..4 + length])?;
+        Ok(Some((message, 4 + length)))
+    }
+
+    /// Appends `message` to `out`
+    pub fn encode(&self, message: &Message, out: &mut Vec<u8>) -> Result<(), PeerError> {
+        self.check_length(message.len())?;
+        message.encode_into(out);
+        Ok(())
+    }
+
+    /// The length prefix at the start of `buf`, once it has arrived
+    fn frame_length(&self, buf: &[u8]) -> Result<Option<usize>, PeerError> {
+        let Some(prefix) = buf.first_chunk::<4>() else {
+            return Ok(None);
+        };
+        let length = u32::from_be_bytes(*prefix) as usize;
+        self.check_length(length)?;
+        Ok(Some(length))
+    }
+
+    fn check_length(&self, length: usize) -> Result<(), PeerError> {
+        if length > self.max_length {
+            return Err(PeerError::MessageTooLarge {
+                length,
+                max: self.max_length,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio_util::codec::Decoder for MessageCodec {
+    type Item = Message;
+    type Error = PeerError;
+
+    fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Message>, PeerError> {
+        use bytes::Buf;
+
+        match MessageCodec::decode(self, src)? {
+            Some((message, consumed)) => {
+                src.advance(consumed);
+                Ok(Some(message))
+            }
+            None => {
+                // Make room for the whole frame at once
+                if let Some(length) = self.frame_length(src)? {
+                    src.reserve(4 + length - src.len());
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Message>, PeerError> {
+        match tokio_util::codec::Decoder::decode(self, src)? {
+            None if !src.is_empty() => Err(PeerError::Truncated),
+            message => Ok(message),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio_util::codec::Encoder<Message> for MessageCodec {
+    type Error = PeerError;
+
+    fn encode(&mut self, message: Message, dst: &mut bytes::BytesMut) -> Result<(), PeerError> {
+        self.check_length(message.len())?;
+        let mut out = Vec::with_capacity(4 + message.len());
+        message.encode_into(&mut out);
+        dst.extend_from_slice(&out);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_stream() {
+        let codec = MessageCodec::new();
+        let mut buf = Vec::new();
+        codec.encode(&Message::Have(1), &mut buf).unwrap();
+        codec.encode(&Message::KeepAlive, &mut buf).unwrap();
+        codec.encode(&Message::Unchoke, &mut buf).unwrap();
+
+        let mut messages = Vec::new();
+        let mut offset = 0;
+        while let Some((message, consumed)) = codec.decode(&buf[offset..]).unwrap() {
+            messages.push(message);
+            offset += consumed;
+        }
+        assert_eq!(
+            messages,
+            [Message::Have(1), Message::KeepAlive, Message::Unchoke]
+        );
+        assert_eq!(offset, buf.len());
+
+        for len in 0..9 {
+            assert_eq!(codec.decode(&buf[..len]).unwrap().map(|(m, _)| m), None);
+        }
+    }
+
+    #[test]
+    fn test_max_length() {
+        let codec = MessageCodec::new().max_length(16);
+        assert_eq!(
+            codec.decode(&[0, 0, 0, 17]),
+            Err(PeerError::MessageTooLarge {
+                length: 17,
+                max: 16
+            })
+        );
+        assert_eq!(
+            MessageCodec::new().decode(&[0xff, 0xff, 0xff, 0xff]),
+            Err(PeerError::MessageTooLarge {
+                length: u32::MAX as usize,
+                max: DEFAULT_MAX_MESSAGE_LEN
+            })
+        );
+
+        let mut out = Vec::new();
+        let bitfield = Message::Bitfield(vec![0xff; 15]);
+        codec.encode(&bitfield, &mut out).unwrap();
+        assert_eq!(codec.decode(&out), Ok(Some((bitfield, 20))));
+        assert_eq!(
+            codec.encode(&Message::Bitfield(vec![0xff; 16]), &mut Vec::new()),
+            Err(PeerError::MessageTooLarge {
+                length: 17,
+                max: 16
+            })
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_tokio_codec() {
+        use bytes::BytesMut;
+        use tokio_util::codec::{Decoder, Encoder};
+
+        let mut codec = MessageCodec::new();
+        let mut buf = BytesMut::new();
+        let piece = Message::Piece {
+            index: 3,
+            begin: 0,
+            block: vec![7; 100],
+        };
+        Encoder::encode(&mut codec, piece.clone(), &mut buf).unwrap();
+        Encoder::encode(&mut codec, Message::Choke, &mut buf).unwrap();
+
+        let mut partial = buf.split_to(50);
+        assert_eq!(Decoder::decode(&mut codec, &mut partial), Ok(None));
+        assert!(partial.capacity() >= 113);
+        partial.unsplit(buf);
+        assert_eq!(Decoder::decode(&mut codec, &mut partial), Ok(Some(piece)));
+        assert_eq!(
+            Decoder::decode(&mut codec, &mut partial),
+            Ok(Some(Message::Choke))
+        );
+        assert!(partial.is_empty());
+        assert_eq!(codec.decode_eof(&mut partial), Ok(None));
+
+        let mut truncated = BytesMut::from(&[0, 0, 0, 5, 4][..]);
+        assert_eq!(codec.decode_eof(&mut truncated), Err(PeerError::Truncated));
+    }
+}
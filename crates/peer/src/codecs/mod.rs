@@ -0,0 +1,3 @@
+#[cfg(feature = "tokio")]
+pub mod handshake;
+pub mod message;
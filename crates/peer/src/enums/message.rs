@@ -0,0 +1,274 @@
+use crate::enums::peer_error::PeerError;
+
+/// A BEP 3 peer wire message
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Message {
+    /// A zero-length message that keeps an idle connection open
+    KeepAlive,
+    Choke,
+    Unchoke,
+    Interested,
+    NotInterested,
+    /// The sender has the piece with this index
+    Have(u32),
+    /// The pieces the sender has, highest bit of the first byte first; only
+    /// valid right after the handshake
+    Bitfield(Vec<u8>),
+    Request {
+        index: u32,
+        begin: u32,
+        length: u32,
+    },
+    Piece {
+        index: u32,
+        begin: u32,
+        block: Vec<u8>,
+    },
+    Cancel {
+        index: u32,
+        begin: u32,
+        length: u32,
+    },
+    /// The sender's DHT port
+    Port(u16),
+    /// A message this crate does not know, such as those of BEP 6 or BEP 10,
+    /// passed through undecoded
+    Unknown {
+        id: u8,
+        payload: Vec<u8>,
+    },
+}
+
+impl Message {
+    pub const CHOKE: u8 = 0;
+    pub const UNCHOKE: u8 = 1;
+    pub const INTERESTED: u8 = 2;
+    pub const NOT_INTERESTED: u8 = 3;
+    pub const HAVE: u8 = 4;
+    pub const BITFIELD: u8 = 5;
+    pub const REQUEST: u8 = 6;
+    pub const PIECE: u8 = 7;
+    pub const CANCEL: u8 = 8;
+    pub const PORT: u8 = 9;
+
+    /// The message ID; `None` for keep-alives, which have none
+    pub fn id(&self) -> Option<u8> {
+        Some(match self {
+            Message::KeepAlive => return None,
+            Message::Choke => Self::CHOKE,
+            Message::Unchoke => Self::UNCHOKE,
+            Message::Interested => Self::INTERESTED,
+            Message::NotInterested => Self::NOT_INTERESTED,
+            Message::Have(_) => Self::HAVE,
+            Message::Bitfield(_) => Self::BITFIELD,
+            Message::Request { .. } => Self::REQUEST,
+            Message::Piece { .. } => Self::PIECE,
+            Message::Cancel { .. } => Self::CANCEL,
+            Message::Port(_) => Self::PORT,
+            Message::Unknown { id, .. } => *id,
+        })
+    }
+
+    /// The length the message is prefixed with: ID and payload
+    pub fn len(&self) -> usize {
+        match self {
+            Message::KeepAlive => 0,
+            Message::Choke | Message::Unchoke | Message::Interested | Message::NotInterested => 1,
+            Message::Have(_) => 5,
+            Message::Bitfield(bitfield) => 1 + bitfield.len(),
+            Message::Request { .. } | Message::Cancel { .. } => 13,
+            Message::Piece { block, .. } => 9 + block.len(),
+            Message::Port(_) => 3,
+            Message::Unknown { payload, .. } => 1 + payload.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends the length prefix, ID and payload to `out`
+    pub fn encode_into(&self, out: &mut Vec<u8>) {
+        out.reserve(4 + self.len());
+        out.extend_from_slice(&(self.len() as u32).to_be_bytes());
+        let Some(id) = self.id() else {
+            return;
+        };
+        out.push(id);
+        match self {
+            Message::Have(index) => out.extend_from_slice(&index.to_be_bytes()),
+            Message::Bitfield(bitfield) => out.extend_from_slice(bitfield),
+            Message::Request {
+                index,
+                begin,
+                length,
+            }
+            | Message::Cancel {
+                index,
+                begin,
+                length,
+            } => {
+                out.extend_from_slice(&index.to_be_bytes());
+                out.extend_from_slice(&begin.to_be_bytes());
+                out.extend_from_slice(&length.to_be_bytes());
+            }
+            Message::Piece {
+                index,
+                begin,
+                block,
+            } => {
+                out.extend_from_slice(&index.to_be_bytes());
+                out.extend_from_slice(&begin.to_be_bytes());
+                out.extend_from_slice(block);
+            }
+            Message::Port(port) => out.extend_from_slice(&port.to_be_bytes()),
+            Message::Unknown { payload, .. } => out.extend_from_slice(payload),
+            _ => {}
+        }
+    }
+
+    /// Decodes a message from what follows its length prefix, which must be
+    /// exactly as long as the prefix says
+    pub fn decode(body: &[u8]) -> Result<Self, PeerError> {
+        let Some((&id, payload)) = body.split_first() else {
+            return Ok(Message::KeepAlive);
+        };
+        let expected = match id {
+            Self::CHOKE | Self::UNCHOKE | Self::INTERESTED | Self::NOT_INTERESTED => Some(0),
+            Self::HAVE => Some(4),
+            Self::REQUEST | Self::CANCEL => Some(12),
+            Self::PORT => Some(2),
+            _ => None,
+        };
+        if expected.is_some_and(|expected| payload.len() != expected)
+            || (id == Self::PIECE && payload.len() < 8)
+        {
+            return Err(PeerError::InvalidLength {
+                id,
+                length: body.len(),
+            });
+        }
+
+        let u32_at =
+            |offset: usize| u32::from_be_bytes(payload[offset..offset + 4].try_into().unwrap());
+        Ok(match id {
+            Self::CHOKE => Message::Choke,
+            Self::UNCHOKE => Message::Unchoke,
+            Self::INTERESTED => Message::Interested,
+            Self::NOT_INTERESTED => Message::NotInterested,
+            Self::HAVE => Message::Have(u32_at(0)),
+            Self::BITFIELD => Message::Bitfield(payload.to_vec()),
+            Self::REQUEST => Message::Request {
+                index: u32_at(0),
+                begin: u32_at(4),
+                length: u32_at(8),
+            },
+            Self::PIECE => Message::Piece {
+                index: u32_at(0),
+                begin: u32_at(4),
+                block: payload[8..].to_vec(),
+            },
+            Self::CANCEL => Message::Cancel {
+                index: u32_at(0),
+                begin: u32_at(4),
+                length: u32_at(8),
+            },
+            Self::PORT => Message::Port(u16::from_be_bytes([payload[0], payload[1]])),
+            _ => Message::Unknown {
+                id,
+                payload: payload.to_vec(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(message: &Message) -> Vec<u8> {
+        let mut out = Vec::new();
+        message.encode_into(&mut out);
+        out
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(encode(&Message::KeepAlive), [0, 0, 0, 0]);
+        assert_eq!(encode(&Message::Interested), [0, 0, 0, 1, 2]);
+        assert_eq!(encode(&Message::Have(258)), [0, 0, 0, 5, 4, 0, 0, 1, 2]);
+        assert_eq!(
+            encode(&Message::Request {
+                index: 1,
+                begin: 16384,
+                length: 16384
+            }),
+            [0, 0, 0, 13, 6, 0, 0, 0, 1, 0, 0, 0x40, 0, 0, 0, 0x40, 0]
+        );
+        assert_eq!(encode(&Message::Port(6881)), [0, 0, 0, 3, 9, 0x1a, 0xe1]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let messages = [
+            Message::KeepAlive,
+            Message::Choke,
+            Message::Unchoke,
+            Message::Interested,
+            Message::NotInterested,
+            Message::Have(7),
+            Message::Bitfield(vec![0b1010_0000]),
+            Message::Request {
+                index: 1,
+                begin: 2,
+                length: 3,
+            },
+            Message::Piece {
+                index: 1,
+                begin: 2,
+                block: b"data".to_vec(),
+            },
+            Message::Piece {
+                index: 1,
+                begin: 2,
+                block: Vec::new(),
+            },
+            Message::Cancel {
+                index: 1,
+                begin: 2,
+                length: 3,
+            },
+            Message::Port(6881),
+            Message::Unknown {
+                id: 20,
+                payload: b"d1:md11:ut_metadatai1eee".to_vec(),
+            },
+        ];
+        for message in messages {
+            let encoded = encode(&message);
+            assert_eq!(encoded.len(), 4 + message.len());
+            assert_eq!(Message::decode(&encoded[4..]), Ok(message));
+        }
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        for body in [
+            &[0, 0][..],
+            &[4, 0, 0, 1],
+            &[4, 0, 0, 0, 1, 0],
+            &[6; 12],
+            &[7; 8],
+            &[8; 14],
+            &[9, 0],
+        ] {
+            assert_eq!(
+                Message::decode(body),
+                Err(PeerError::InvalidLength {
+                    id: body[0],
+                    length: body.len()
+                })
+            );
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PeerError {
+    /// The handshake does not start with the BitTorrent protocol string
+    InvalidProtocol,
+    /// A message's length prefix exceeds the configured maximum
+    MessageTooLarge { length: usize, max: usize },
+    /// A message's length does not fit its ID
+    InvalidLength { id: u8, length: usize },
+    /// The connection closed in the middle of a handshake or message
+    Truncated,
+    /// Reading from or writing to the connection failed
+    Io(String),
+}
+
+impl fmt::Display for PeerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerError::InvalidProtocol => write!(f, "peer does not speak the BitTorrent protocol"),
+            PeerError::MessageTooLarge { length, max } => {
+                write!(f, "message of {length} bytes exceeds the maximum of {max}")
+            }
+            PeerError::InvalidLength { id, length } => {
+                write!(f, "invalid length {length} for message {id}")
+            }
+            PeerError::Truncated => write!(f, "connection closed mid-message"),
+            PeerError::Io(reason) => write!(f, "connection failed: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for PeerError {}
+
+impl From<std::io::Error> for PeerError {
+    fn from(err: std::io::Error) -> Self {
+        PeerError::Io(err.to_string())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod codecs;
+pub mod enums;
+pub mod models;
@@ -0,0 +1,118 @@
+use crate::enums::peer_error::PeerError;
+
+/// The protocol string every BEP 3 handshake starts with, after its length
+pub const PROTOCOL: &[u8; 19] = b"BitTorrent protocol";
+
+/// Length of an encoded handshake
+pub const HANDSHAKE_LEN: usize = 1 + PROTOCOL.len() + 8 + 20 + 20;
+
+/// The first message each side of a connection sends
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Handshake {
+    /// Extension bits; bit 0 is the lowest bit of the last byte
+    pub reserved: [u8; 8],
+    /// v1 info-hash, or the truncated v2 info-hash for v2-only torrents
+    pub info_hash: [u8; 20],
+    pub peer_id: [u8; 20],
+}
+
+impl Handshake {
+    /// BEP 5: the peer runs a DHT node and sends `port` messages
+    pub const DHT: u32 = 0;
+    /// BEP 6: the peer supports the fast extension
+    pub const FAST: u32 = 2;
+    /// BEP 10: the peer supports the extension protocol
+    pub const EXTENSION_PROTOCOL: u32 = 20;
+
+    pub fn new(info_hash: [u8; 20], peer_id: [u8; 20]) -> Self {
+        Handshake {
+            reserved: [0; 8],
+            info_hash,
+            peer_id,
+        }
+    }
+
+    /// Sets reserved bit `bit`, counted from the lowest bit of the last byte
+    pub fn with_reserved_bit(mut self, bit: u32) -> Self {
+        let (byte, mask) = reserved_position(bit);
+        self.reserved[byte] |= mask;
+        self
+    }
+
+    pub fn has_reserved_bit(&self, bit: u32) -> bool {
+        let (byte, mask) = reserved_position(bit);
+        self.reserved[byte] & mask != 0
+    }
+
+    pub fn to_bytes(&self) -> [u8; HANDSHAKE_LEN] {
+        let mut bytes = [0; HANDSHAKE_LEN];
+        bytes[0] = PROTOCOL.len() as u8;
+        bytes[1..20].copy_from_slice(PROTOCOL);
+        bytes[20..28].copy_from_slice(&self.reserved);
+        bytes[28..48].copy_from_slice(&self.info_hash);
+        bytes[48..68].copy_from_slice(&self.peer_id);
+        bytes
+    }
+
+    /// Decodes a handshake from the start of `buf`; `None` when more bytes are
+    /// needed. The protocol string is checked as soon as it arrives, so a peer
+    /// speaking another protocol is rejected without waiting for the rest
+    pub fn decode(buf: &[u8]) -> Result<Option<Self>, PeerError> {
+        let header = &buf[..buf.len().min(20)];
+        if let Some((&length, protocol)) = header.split_first()
+            && (usize::from(length) != PROTOCOL.len() || !PROTOCOL.starts_with(protocol))
+        {
+            return Err(PeerError::InvalidProtocol);
+        }
+        if buf.len() < HANDSHAKE_LEN {
+            return Ok(None);
+        }
+        Ok(Some(Handshake {
+            reserved: buf[20..28].try_into().unwrap(),
+            info_hash: buf[28..48].try_into().unwrap(),
+            peer_id: buf[48..68].try_into().unwrap(),
+        }))
+    }
+}
+
+fn reserved_position(bit: u32) -> (usize, u8) {
+    assert!(bit < 64, "reserved bits range from 0 to 63");
+    (7 - bit as usize / 8, 1 << (bit % 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let handshake = Handshake::new([1; 20], [2; 20])
+            .with_reserved_bit(Handshake::DHT)
+            .with_reserved_bit(Handshake::EXTENSION_PROTOCOL);
+        let bytes = handshake.to_bytes();
+        assert_eq!(&bytes[..20], b"\x13BitTorrent protocol");
+        assert_eq!(bytes[20..28], [0, 0, 0, 0, 0, 0x10, 0, 0x01]);
+        assert_eq!(Handshake::decode(&bytes), Ok(Some(handshake)));
+
+        assert!(handshake.has_reserved_bit(Handshake::EXTENSION_PROTOCOL));
+        assert!(!handshake.has_reserved_bit(Handshake::FAST));
+    }
+
+    #[test]
+    fn test_partial() {
+        let bytes = Handshake::new([1; 20], [2; 20]).to_bytes();
+        for len in [0, 1, 10, 20, 67] {
+            assert_eq!(Handshake::decode(&bytes[..len]), Ok(None));
+        }
+    }
+
+    #[test]
+    fn test_invalid_protocol() {
+        assert_eq!(Handshake::decode(b"\x12"), Err(PeerError::InvalidProtocol));
+        assert_eq!(Handshake::decode(b"\x13BitTorrent pro"), Ok(None));
+        assert_eq!(
+            Handshake::decode(b"\x13GET / HTTP/1.1"),
+            Err(PeerError::InvalidProtocol)
+        );
+    }
+}
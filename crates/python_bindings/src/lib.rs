@@ -29,7 +29,7 @@ fn py_to_bencode_tokens(obj: Bound<PyAny>, decode_utf: Option<bool>) -> PyResult
     }
 
     // Integers
-    if let Ok(int_val) = obj.extract::<isize>() {
+    if let Ok(int_val) = obj.extract::<i64>() {
         return Ok(BencodeValue::Int(int_val));
     }
 
@@ -143,9 +143,17 @@ fn bencode_tokens_to_py(
 
 #[pymodule(name = "bencode_rs")]
 mod python_bindings {
-    use ::bencode::dispatcher::{bdecode::decode_bencode, bencode::encode_bencode};
+    use ::bencode::dispatcher::{bdecode::decode_bencode, bencode::encode};
+    use metainfo::{
+        encoding::hex::encode_hex,
+        enums::info_hash::InfoHash,
+        hashing::info_hash::info_hash as compute_info_hash,
+        models::magnet::MagnetLink,
+        parsers::{magnet::parse_magnet as parse_magnet_uri, metainfo::decode_metainfo},
+    };
     use pyo3::exceptions::PyValueError;
     use pyo3::prelude::*;
+    use pyo3::types::PyDict;
 
     use super::{bencode_tokens_to_py, py_to_bencode_tokens};
 
@@ -153,7 +161,7 @@ mod python_bindings {
     #[pyo3(signature = (obj, decode_utf=false))]
     fn bencode(obj: Bound<PyAny>, decode_utf: Option<bool>) -> PyResult<Vec<u8>> {
         let objects = py_to_bencode_tokens(obj, decode_utf)?;
-        let encoded = encode_bencode(objects).unwrap();
+        let encoded = encode(&objects).unwrap();
         Ok(encoded)
     }
 
@@ -168,4 +176,43 @@ mod python_bindings {
         let python_objects = bencode_tokens_to_py(py, decoded_objects, decode_utf)?;
         Ok(python_objects)
     }
+
+    fn info_hash_to_py<'py>(py: Python<'py>, hash: &InfoHash) -> PyResult<Bound<'py, PyDict>> {
+        let hashes = PyDict::new(py);
+        hashes.set_item("v1", hash.v1().map(|v1| encode_hex(&v1)))?;
+        hashes.set_item("v2", hash.v2().map(|v2| encode_hex(&v2)))?;
+        Ok(hashes)
+    }
+
+    #[pyfunction]
+    fn info_hash<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyDict>> {
+        let hash = compute_info_hash(data).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        info_hash_to_py(py, &hash)
+    }
+
+    #[pyfunction]
+    fn parse_magnet<'py>(py: Python<'py>, uri: &str) -> PyResult<Bound<'py, PyDict>> {
+        let magnet = parse_magnet_uri(uri).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let select_only: Vec<(usize, usize)> = magnet
+            .select_only
+            .iter()
+            .map(|range| (*range.start(), *range.end()))
+            .collect();
+
+        let parsed = PyDict::new(py);
+        parsed.set_item("info_hash", info_hash_to_py(py, &magnet.info_hash)?)?;
+        parsed.set_item("name", magnet.display_name)?;
+        parsed.set_item("trackers", magnet.trackers)?;
+        parsed.set_item("web_seeds", magnet.web_seeds)?;
+        parsed.set_item("peers", magnet.peers)?;
+        parsed.set_item("select_only", select_only)?;
+        parsed.set_item("extra", magnet.extra)?;
+        Ok(parsed)
+    }
+
+    #[pyfunction]
+    fn magnet_from_torrent(data: &[u8]) -> PyResult<String> {
+        let metainfo = decode_metainfo(data).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(MagnetLink::from_metainfo(&metainfo).to_uri())
+    }
 }
@@ -0,0 +1,30 @@
+import hashlib
+
+import pytest
+from bencode_rs import info_hash
+
+INFO = b"d6:lengthi1e4:name1:a12:piece lengthi16384e6:pieces20:" + b"\x00" * 20 + b"e"
+
+
+def test_v1_info_hash():
+    torrent = b"d8:announce3:url4:info" + INFO + b"e"
+    assert info_hash(torrent) == {"v1": hashlib.sha1(INFO).hexdigest(), "v2": None}
+
+
+def test_info_hash_uses_original_bytes():
+    # Unsorted keys must be hashed verbatim, not re-encoded
+    info = b"d4:name1:a6:lengthi1ee"
+    assert info_hash(b"d4:info" + info + b"e")["v1"] == hashlib.sha1(info).hexdigest()
+
+
+def test_v2_info_hash():
+    info = b"d12:meta versioni2e4:name1:ae"
+    assert info_hash(b"d4:info" + info + b"e") == {
+        "v1": None,
+        "v2": hashlib.sha256(info).hexdigest(),
+    }
+
+
+def test_missing_info():
+    with pytest.raises(ValueError):
+        info_hash(b"de")
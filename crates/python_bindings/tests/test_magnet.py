@@ -0,0 +1,34 @@
+import pytest
+from bencode_rs import info_hash, magnet_from_torrent, parse_magnet
+
+V1 = "c12fe1c06bba254a9dc9f519b335aa7c1367a88a"
+
+
+def test_parse_magnet():
+    magnet = parse_magnet(
+        f"magnet:?xt=urn:btih:{V1}&dn=Big+Buck+Bunny&tr=udp://a.example:1337"
+        "&tr=udp://b.example:1337&x.pe=10.0.0.1:6881&so=0,2-3&x.custom=1"
+    )
+    assert magnet == {
+        "info_hash": {"v1": V1, "v2": None},
+        "name": "Big Buck Bunny",
+        "trackers": ["udp://a.example:1337", "udp://b.example:1337"],
+        "web_seeds": [],
+        "peers": ["10.0.0.1:6881"],
+        "select_only": [(0, 0), (2, 3)],
+        "extra": [("x.custom", "1")],
+    }
+
+
+def test_magnet_from_torrent():
+    torrent = (
+        b"d8:announce14:http://tracker4:infod6:lengthi1e4:name1:a"
+        b"12:piece lengthi16384e6:pieces20:" + b"\x00" * 20 + b"ee"
+    )
+    v1 = info_hash(torrent)["v1"]
+    assert magnet_from_torrent(torrent) == f"magnet:?xt=urn:btih:{v1}&dn=a&tr=http://tracker"
+
+
+def test_invalid_magnet():
+    with pytest.raises(ValueError):
+        parse_magnet("magnet:?dn=no-hash")
@@ -0,0 +1,390 @@
+use crate::{
+    enums::tracker_error::TrackerError,
+    models::{
+        announce_request::AnnounceRequest, announce_response::AnnounceResponse,
+        scrape_stats::ScrapeStats,
+    },
+    parsers::{announce_response::parse_announce_response, scrape_response::parse_scrape_response},
+};
+use metainfo::encoding::percent::{encode_percent, encode_percent_bytes};
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+use ureq::Agent;
+
+/// Trackers answer in a few kilobytes; anything much larger is not a tracker
+const MAX_RESPONSE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Announces to and scrapes a BEP 3 HTTP(S) tracker
+#[derive(Debug, Clone)]
+pub struct HttpTracker {
+    url: String,
+    agent: Agent,
+    scrape_batch_size: usize,
+}
+
+impl HttpTracker {
+    pub fn new(url: impl Into<String>) -> Self {
+        HttpTracker {
+            url: url.into(),
+            agent: agent(Duration::from_secs(15)),
+            scrape_batch_size: 32,
+        }
+    }
+
+    /// Limit for a whole request, 15 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.agent = agent(timeout);
+        self
+    }
+
+    /// Info-hashes sent per scrape request, 32 by default; 1 for trackers that
+    /// refuse multi-torrent scrapes
+    pub fn scrape_batch_size(mut self, scrape_batch_size: usize) -> Self {
+        self.scrape_batch_size = scrape_batch_size.max(1);
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The announce URL with the request's query parameters appended; binary
+    /// values are percent-encoded byte by byte
+    pub fn announce_url(&self, request: &AnnounceRequest) -> String {
+        let separator = if self.url.contains('?') { '&' } else { '?' };
+        let mut url = format!(
+            "{}{separator}info_hash={}&peer_id={}&port={}&uploaded={}&downloaded={}&left={}&compact={}",
+            self.url,
+            encode_percent_bytes(&request.info_hash),
+            encode_percent_bytes(&request.peer_id),
+            request.port,
+            request.uploaded,
+            request.downloaded,
+            request.left,
+            u8::from(request.compact),
+        );
+        if let Some(event) = request.event {
+            let _ = write!(url, "&event={}", event.as_str());
+        }
+        if let Some(numwant) = request.numwant {
+            let _ = write!(url, "&numwant={numwant}");
+        }
+        if let Some(key) = request.key {
+            let _ = write!(url, "&key={key:08X}");
+        }
+        if let Some(tracker_id) = &request.tracker_id {
+            let _ = write!(url, "&trackerid={}", encode_percent_bytes(tracker_id));
+        }
+        if request.no_peer_id {
+            url.push_str("&no_peer_id=1");
+        }
+        if let Some(ipv4) = request.ipv4 {
+            let _ = write!(url, "&ipv4={ipv4}");
+        }
+        if let Some(ipv6) = request.ipv6 {
+            let _ = write!(url, "&ipv6={}", encode_percent(&ipv6.to_string()));
+        }
+        url
+    }
+
+    pub fn announce(&self, request: &AnnounceRequest) -> Result<AnnounceResponse, TrackerError> {
+        let body = self.get(&self.announce_url(request))?;
+        parse_announce_response(&body)
+    }
+
+    /// The scrape URL by convention: the last path segment must start with
+    /// `announce`, which is replaced by `scrape`
+    pub fn scrape_url(&self) -> Option<String> {
+        let (base, query) = match self.url.split_once('?') {
+            Some((base, query)) => (base, Some(query)),
+            None => (self.url.as_str(), None),
+        };
+        let (path, segment) = base.rsplit_once('/')?;
+        let rest = segment.strip_prefix("announce")?;
+        // Leaves a bare authority such as "http://announce.example" alone
+        if path.ends_with('/') {
+            return None;
+        }
+
+        let mut url = format!("{path}/scrape{rest}");
+        if let Some(query) = query {
+            url.push('?');
+            url.push_str(query);
+        }
+        Some(url)
+    }
+
+    /// Scrapes the given torrents in batches of `scrape_batch_size` per request.
+    /// Torrents the tracker does not know are missing from the result
+    pub fn scrape(
+        &self,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<BTreeMap<[u8; 20], ScrapeStats>, TrackerError> {
+        let scrape_url = self.scrape_url().ok_or(TrackerError::ScrapeUnsupported)?;
+        let mut stats = BTreeMap::new();
+        for batch in info_hashes.chunks(self.scrape_batch_size) {
+            let mut url = scrape_url.clone();
+            let mut separator = if url.contains('?') { '&' } else { '?' };
+            for info_hash in batch {
+                let _ = write!(
+                    url,
+                    "{separator}info_hash={}",
+                    encode_percent_bytes(info_hash)
+                );
+                separator = '&';
+            }
+            stats.extend(parse_scrape_response(&self.get(&url)?)?);
+        }
+        Ok(stats)
+    }
+
+    fn get(&self, url: &str) -> Result<Vec<u8>, TrackerError> {
+        let mut response = self.agent.get(url).call()?;
+        if response.status() != 200 {
+            return Err(TrackerError::Status(response.status().as_u16()));
+        }
+        Ok(response
+            .body_mut()
+            .with_config()
+            .limit(MAX_RESPONSE_SIZE)
+            .read_to_vec()?)
+    }
+}
+
+fn agent(timeout: Duration) -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enums::announce_event::AnnounceEvent, models::peer::Peer, stubs::HttpStub};
+    use bencode::{bencode, dispatcher::bencode::encode};
+
+    fn request() -> AnnounceRequest {
+        let mut info_hash = [0xab; 20];
+        info_hash[..4].copy_from_slice(b"a b~");
+        AnnounceRequest::new(info_hash, *b"-RS0001-123456789012", 6881)
+    }
+
+    #[test]
+    fn test_announce_url() {
+        let mut request = request();
+        request.left = 100;
+        request.event = Some(AnnounceEvent::Started);
+        request.numwant = Some(50);
+        request.key = Some(0xbeef);
+        request.tracker_id = Some(b"id 1".to_vec());
+
+        let tracker = HttpTracker::new("http://tracker.example/announce?passkey=x");
+        assert_eq!(
+            tracker.announce_url(&request),
+            format!(
+                "http://tracker.example/announce?passkey=x&info_hash=a%20b~{}\
+                &peer_id=-RS0001-123456789012&port=6881&uploaded=0&downloaded=0&left=100\
+                &compact=1&event=started&numwant=50&key=0000BEEF&trackerid=id%201",
+                "%AB".repeat(16)
+            )
+        );
+
+        request.event = None;
+        request.compact = false;
+        let url = HttpTracker::new("http://t/a").announce_url(&request);
+        assert!(url.contains("&compact=0"));
+        assert!(!url.contains("event="));
+        assert!(!url.contains("ipv"));
+
+        request.ipv4 = Some("198.51.100.7".parse().unwrap());
+        request.ipv6 = Some("2001:db8::7".parse().unwrap());
+        let url = HttpTracker::new("http://t/a").announce_url(&request);
+        assert!(url.ends_with("&ipv4=198.51.100.7&ipv6=2001:db8::7"));
+
+        request.ipv4 = None;
+        request.ipv6 = None;
+        request.no_peer_id = true;
+        let url = HttpTracker::new("http://t/a").announce_url(&request);
+        assert!(url.ends_with("&trackerid=id%201&no_peer_id=1"));
+    }
+
+    #[test]
+    fn test_announce_against_stub() {
+        let body = encode(&bencode!({
+            "interval": 1800,
+            "min interval": 60,
+            "tracker id": "session",
+            "warning message": "be nice",
+            "peers": (vec![192u8, 168, 0, 2, 0x1a, 0xe1]),
+        }))
+        .unwrap();
+        let stub = HttpStub::serve(vec![(200, body)]);
+        let tracker = HttpTracker::new(&stub.url);
+
+        let mut request = request();
+        request.event = Some(AnnounceEvent::Completed);
+        let response = tracker.announce(&request).unwrap();
+        assert_eq!(response.interval, Duration::from_secs(1800));
+        assert_eq!(response.min_interval, Some(Duration::from_secs(60)));
+        assert_eq!(response.tracker_id, Some(b"session".to_vec()));
+        assert_eq!(response.warning_message, Some("be nice".to_string()));
+        assert_eq!(
+            response.peers,
+            vec![Peer::new("192.168.0.2:6881".parse().unwrap())]
+        );
+
+        let requests = stub.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].starts_with("/announce?info_hash=a%20b~%AB"));
+        assert!(requests[0].contains("&event=completed"));
+    }
+
+    #[test]
+    fn test_dual_stack_peers_against_stub() {
+        let mut peers6 = "2001:db8::2"
+            .parse::<std::net::Ipv6Addr>()
+            .unwrap()
+            .octets()
+            .to_vec();
+        peers6.extend_from_slice(&6881u16.to_be_bytes());
+        let body = encode(&bencode!({
+            "interval": 60,
+            "peers": (vec![192u8, 168, 0, 2, 0x1a, 0xe1]),
+            "peers6": peers6,
+        }))
+        .unwrap();
+        let stub = HttpStub::serve(vec![(200, body)]);
+
+        let mut request = request();
+        request.ipv6 = Some("2001:db8::1".parse().unwrap());
+        let response = HttpTracker::new(&stub.url).announce(&request).unwrap();
+        let addrs: Vec<_> = response.peers.iter().map(|peer| peer.addr).collect();
+        assert_eq!(
+            addrs,
+            vec![
+                "192.168.0.2:6881".parse().unwrap(),
+                "[2001:db8::2]:6881".parse().unwrap(),
+            ]
+        );
+        assert!(stub.requests()[0].ends_with("&ipv6=2001:db8::1"));
+    }
+
+    #[test]
+    fn test_dictionary_peers_against_stub() {
+        let body = encode(&bencode!({
+            "interval": 60,
+            "peers": [{ "ip": "10.1.1.1", "port": 80, "peer id": (vec![b'p'; 20]) }],
+        }))
+        .unwrap();
+        let stub = HttpStub::serve(vec![(200, body)]);
+
+        let mut request = request();
+        request.compact = false;
+        let response = HttpTracker::new(&stub.url).announce(&request).unwrap();
+        assert_eq!(
+            response.peers,
+            vec![Peer {
+                addr: "10.1.1.1:80".parse().unwrap(),
+                peer_id: Some([b'p'; 20]),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_scrape_url() {
+        let scrape_url = |url: &str| HttpTracker::new(url).scrape_url();
+        assert_eq!(
+            scrape_url("http://example.com/announce"),
+            Some("http://example.com/scrape".to_string())
+        );
+        assert_eq!(
+            scrape_url("http://example.com/x/announce.php?passkey=a/b"),
+            Some("http://example.com/x/scrape.php?passkey=a/b".to_string())
+        );
+        assert_eq!(
+            scrape_url("http://example.com/announce?x2%0644"),
+            Some("http://example.com/scrape?x2%0644".to_string())
+        );
+        for url in [
+            "http://example.com/a",
+            "http://example.com/announce/x",
+            "http://example.com/x%064announce",
+            "http://announce.example",
+        ] {
+            assert_eq!(scrape_url(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn test_scrape_against_stub() {
+        let files = |hashes: &[[u8; 20]]| {
+            let files = hashes
+                .iter()
+                .map(|hash| {
+                    let stats =
+                        bencode!({ "complete": (i64::from(hash[0])), "incomplete": 1, "downloaded": 2 });
+                    (hash.to_vec(), stats)
+                })
+                .collect::<std::collections::BTreeMap<_, _>>();
+            encode(&bencode!({ "files": files })).unwrap()
+        };
+        let stub = HttpStub::serve(vec![
+            (200, files(&[[1; 20], [2; 20]])),
+            (200, files(&[[3; 20]])),
+        ]);
+        let tracker = HttpTracker::new(format!("{}?passkey=k", stub.url)).scrape_batch_size(2);
+
+        let stats = tracker.scrape(&[[1; 20], [2; 20], [3; 20]]).unwrap();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(
+            stats[&[3; 20]],
+            ScrapeStats {
+                complete: 3,
+                incomplete: 1,
+                downloaded: 2,
+            }
+        );
+        assert_eq!(
+            stub.requests(),
+            vec![
+                format!(
+                    "/scrape?passkey=k&info_hash={}&info_hash={}",
+                    "%01".repeat(20),
+                    "%02".repeat(20)
+                ),
+                format!("/scrape?passkey=k&info_hash={}", "%03".repeat(20)),
+            ]
+        );
+
+        assert_eq!(
+            HttpTracker::new("http://example.com/a").scrape(&[[1; 20]]),
+            Err(TrackerError::ScrapeUnsupported)
+        );
+    }
+
+    #[test]
+    fn test_failures_against_stub() {
+        let stub = HttpStub::serve(vec![
+            (200, b"d14:failure reason6:bannede".to_vec()),
+            (404, b"not found".to_vec()),
+            (200, b"<html>".to_vec()),
+        ]);
+        let tracker = HttpTracker::new(&stub.url);
+        assert_eq!(
+            tracker.announce(&request()),
+            Err(TrackerError::Failure("banned".to_string()))
+        );
+        assert_eq!(tracker.announce(&request()), Err(TrackerError::Status(404)));
+        assert!(matches!(
+            tracker.announce(&request()),
+            Err(TrackerError::Decode(_))
+        ));
+
+        // Nothing listens on the stub any more
+        assert!(matches!(
+            tracker.announce(&request()),
+            Err(TrackerError::Transport(_))
+        ));
+    }
+}
@@ -0,0 +1,611 @@
+use crate::{
+    enums::tracker_error::TrackerError,
+    models::{
+        announce_request::AnnounceRequest, announce_response::AnnounceResponse,
+        scrape_stats::ScrapeStats,
+    },
+    parsers::peers::{parse_compact_v4, parse_compact_v6},
+};
+use std::{
+    collections::BTreeMap,
+    io::ErrorKind,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    time::{Duration, Instant},
+};
+
+/// Magic constant identifying a connect request
+pub const PROTOCOL_ID: u64 = 0x41727101980;
+pub const ACTION_CONNECT: u32 = 0;
+pub const ACTION_ANNOUNCE: u32 = 1;
+pub const ACTION_SCRAPE: u32 = 2;
+pub const ACTION_ERROR: u32 = 3;
+/// A connection ID may be used for one minute after it was received
+const CONNECTION_LIFETIME: Duration = Duration::from_secs(60);
+/// Most info-hashes a single scrape packet can carry
+pub const MAX_SCRAPE_HASHES: usize = 74;
+/// BEP 41 announce option types
+pub const OPTION_END: u8 = 0;
+pub const OPTION_NOP: u8 = 1;
+pub const OPTION_URL_DATA: u8 = 2;
+/// BEP 15 gives up after 8 retransmissions, about an hour at the default timeout
+pub const MAX_RETRIES: u32 = 8;
+
+/// Talks to a BEP 15 UDP tracker. Every request is retransmitted after
+/// `timeout * 2^n` seconds, n counting up to `max_retries`
+#[derive(Debug)]
+pub struct UdpTracker {
+    addr: SocketAddr,
+    timeout: Duration,
+    max_retries: u32,
+    socket: Option<UdpSocket>,
+    connection: Option<(u64, Instant)>,
+    /// Path and query of the tracker URL, sent with announces as BEP 41 URLData
+    url_data: String,
+}
+
+impl UdpTracker {
+    pub fn new(addr: SocketAddr) -> Self {
+        UdpTracker {
+            addr,
+            timeout: Duration::from_secs(15),
+            max_retries: MAX_RETRIES,
+            socket: None,
+            connection: None,
+            url_data: String::new(),
+        }
+    }
+
+    /// Resolves a `udp://host:port/announce` URL. The path and query, which
+    /// may hold a passkey, are sent with every announce
+    pub fn from_url(url: &str) -> Result<Self, TrackerError> {
+        let invalid = || TrackerError::InvalidUrl(url.to_string());
+        let rest = url.strip_prefix("udp://").ok_or_else(invalid)?;
+        let authority = rest.split(['/', '?']).next().unwrap_or_default();
+        let addr = authority
+            .to_socket_addrs()
+            .map_err(|_| invalid())?
+            .next()
+            .ok_or_else(invalid)?;
+        Ok(UdpTracker::new(addr).url_data(&rest[authority.len()..]))
+    }
+
+    /// Path and query sent with every announce as BEP 41 URLData, such as
+    /// `/<passkey>/announce`; none by default
+    pub fn url_data(mut self, url_data: impl Into<String>) -> Self {
+        self.url_data = url_data.into();
+        self
+    }
+
+    /// Wait before the first retransmission, 15 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retransmissions before giving up, 8 by default and at most `MAX_RETRIES`
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries.min(MAX_RETRIES);
+        self
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn announce(
+        &mut self,
+        request: &AnnounceRequest,
+    ) -> Result<AnnounceResponse, TrackerError> {
+        let url_data = self.url_data.clone();
+        let packet = self.send(ACTION_ANNOUNCE, |packet| {
+            packet.extend_from_slice(&request.info_hash);
+            packet.extend_from_slice(&request.peer_id);
+            packet.extend_from_slice(&request.downloaded.to_be_bytes());
+            packet.extend_from_slice(&request.left.to_be_bytes());
+            packet.extend_from_slice(&request.uploaded.to_be_bytes());
+            let event = request.event.map_or(0, |event| event.udp_id());
+            packet.extend_from_slice(&event.to_be_bytes());
+            packet.extend_from_slice(&0u32.to_be_bytes()); // IP address: the sender's
+            packet.extend_from_slice(&request.key.unwrap_or(0).to_be_bytes());
+            let numwant = request
+                .numwant
+                .map_or(-1, |n| n.min(i32::MAX as u32) as i32);
+            packet.extend_from_slice(&numwant.to_be_bytes());
+            packet.extend_from_slice(&request.port.to_be_bytes());
+            // Each option carries at most 255 bytes; the tracker joins them
+            for chunk in url_data.as_bytes().chunks(u8::MAX as usize) {
+                packet.push(OPTION_URL_DATA);
+                packet.push(chunk.len() as u8);
+                packet.extend_from_slice(chunk);
+            }
+        })?;
+
+        if packet.len() < 20 {
+            return Err(TrackerError::MalformedPacket("announce response too short"));
+        }
+        // Peers are in the address family of the connection to the tracker
+        let peers = match self.addr {
+            SocketAddr::V4(_) => parse_compact_v4(&packet[20..]),
+            SocketAddr::V6(_) => parse_compact_v6(&packet[20..]),
+        }
+        .map_err(|_| TrackerError::MalformedPacket("truncated peer list"))?;
+
+        Ok(AnnounceResponse {
+            interval: Duration::from_secs(read_u32(&packet, 8).into()),
+            min_interval: None,
+            tracker_id: None,
+            complete: Some(read_u32(&packet, 16)),
+            incomplete: Some(read_u32(&packet, 12)),
+            peers,
+            warning_message: None,
+            external_ip: None,
+        })
+    }
+
+    /// Scrapes the given torrents, `MAX_SCRAPE_HASHES` per packet
+    pub fn scrape(
+        &mut self,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<BTreeMap<[u8; 20], ScrapeStats>, TrackerError> {
+        let mut stats = BTreeMap::new();
+        for batch in info_hashes.chunks(MAX_SCRAPE_HASHES) {
+            stats.extend(self.scrape_batch(batch)?);
+        }
+        Ok(stats)
+    }
+
+    fn scrape_batch(
+        &mut self,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<BTreeMap<[u8; 20], ScrapeStats>, TrackerError> {
+        let packet = self.send(ACTION_SCRAPE, |packet| {
+            info_hashes
+                .iter()
+                .for_each(|hash| packet.extend_from_slice(hash));
+        })?;
+
+        if packet.len() < 8 + 12 * info_hashes.len() {
+            return Err(TrackerError::MalformedPacket("scrape response too short"));
+        }
+        Ok(info_hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| {
+                let offset = 8 + 12 * i;
+                let stats = ScrapeStats {
+                    complete: read_u32(&packet, offset),
+                    downloaded: read_u32(&packet, offset + 4),
+                    incomplete: read_u32(&packet, offset + 8),
+                };
+                (*hash, stats)
+            })
+            .collect())
+    }
+
+    /// Sends a request built on a cached or fresh connection ID, retransmitting
+    /// until a response for `action` arrives
+    fn send(&mut self, action: u32, body: impl Fn(&mut Vec<u8>)) -> Result<Vec<u8>, TrackerError> {
+        for attempt in 0..=self.max_retries {
+            let timeout = self.timeout.saturating_mul(1 << attempt);
+
+            let connection_id = match self.connection {
+                Some((id, received)) if received.elapsed() < CONNECTION_LIFETIME => id,
+                _ => {
+                    self.connection = None;
+                    let transaction_id = rand::random();
+                    let packet = header(PROTOCOL_ID, ACTION_CONNECT, transaction_id);
+                    self.socket()?.send(&packet)?;
+                    match self.receive(ACTION_CONNECT, transaction_id, timeout)? {
+                        Some(response) if response.len() >= 16 => {
+                            let id = u64::from_be_bytes(response[8..16].try_into().unwrap());
+                            self.connection = Some((id, Instant::now()));
+                            id
+                        }
+                        Some(_) => {
+                            return Err(TrackerError::MalformedPacket(
+                                "connect response too short",
+                            ));
+                        }
+                        None => continue,
+                    }
+                }
+            };
+
+            let transaction_id = rand::random();
+            let mut packet = header(connection_id, action, transaction_id);
+            body(&mut packet);
+            self.socket()?.send(&packet)?;
+            if let Some(response) = self.receive(action, transaction_id, timeout)? {
+                return Ok(response);
+            }
+        }
+        Err(TrackerError::Timeout)
+    }
+
+    /// Waits for the response to `transaction_id`, ignoring stray packets;
+    /// `None` once `timeout` elapses
+    fn receive(
+        &mut self,
+        action: u32,
+        transaction_id: u32,
+        timeout: Duration,
+    ) -> Result<Option<Vec<u8>>, TrackerError> {
+        let deadline = Instant::now() + timeout;
+        let mut buffer = vec![0; 65_536];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            let socket = self.socket()?;
+            socket.set_read_timeout(Some(remaining))?;
+            let len = match socket.recv(&mut buffer) {
+                Ok(len) => len,
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(None);
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            let packet = &buffer[..len];
+            if len < 8 || read_u32(packet, 4) != transaction_id {
+                continue;
+            }
+            return match read_u32(packet, 0) {
+                ACTION_ERROR => {
+                    // The error may be a rejected connection ID; connect afresh next time
+                    self.connection = None;
+                    Err(TrackerError::Failure(
+                        String::from_utf8_lossy(&packet[8..]).into_owned(),
+                    ))
+                }
+                received if received == action => Ok(Some(packet.to_vec())),
+                _ => Err(TrackerError::MalformedPacket("unexpected action")),
+            };
+        }
+    }
+
+    fn socket(&mut self) -> Result<&UdpSocket, TrackerError> {
+        if self.socket.is_none() {
+            let local: SocketAddr = match self.addr {
+                SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+                SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+            };
+            let socket = UdpSocket::bind(local)?;
+            socket.connect(self.addr)?;
+            self.socket = Some(socket);
+        }
+        Ok(self.socket.as_ref().unwrap())
+    }
+}
+
+fn header(connection_id: u64, action: u32, transaction_id: u32) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(98);
+    packet.extend_from_slice(&connection_id.to_be_bytes());
+    packet.extend_from_slice(&action.to_be_bytes());
+    packet.extend_from_slice(&transaction_id.to_be_bytes());
+    packet
+}
+
+fn read_u32(packet: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(packet[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enums::announce_event::AnnounceEvent, models::peer::Peer, stubs::UdpStub};
+
+    const CONNECTION_ID: u64 = 0xC0FFEE;
+
+    /// A well-behaved tracker answering with `peers` in compact form
+    fn tracker(peers: Vec<u8>) -> impl FnMut(&[u8]) -> Vec<Vec<u8>> + Send + 'static {
+        move |packet| {
+            let connection_id = u64::from_be_bytes(packet[..8].try_into().unwrap());
+            let action = read_u32(packet, 8);
+            let transaction_id = read_u32(packet, 12);
+
+            if action == ACTION_CONNECT {
+                assert_eq!(connection_id, PROTOCOL_ID);
+                let mut response = header(0, ACTION_CONNECT, transaction_id)[8..].to_vec();
+                response.extend_from_slice(&CONNECTION_ID.to_be_bytes());
+                return vec![response];
+            }
+            if connection_id != CONNECTION_ID {
+                let mut response = header(0, ACTION_ERROR, transaction_id)[8..].to_vec();
+                response.extend_from_slice(b"bad connection id");
+                return vec![response];
+            }
+
+            let mut response = header(0, action, transaction_id)[8..].to_vec();
+            match action {
+                ACTION_ANNOUNCE => {
+                    for value in [1800u32, 3, 5] {
+                        response.extend_from_slice(&value.to_be_bytes());
+                    }
+                    response.extend_from_slice(&peers);
+                }
+                ACTION_SCRAPE => {
+                    for hash in packet[16..].chunks(20) {
+                        for value in [hash[0].into(), 2u32, 3] {
+                            response.extend_from_slice(&value.to_be_bytes());
+                        }
+                    }
+                }
+                _ => unreachable!(),
+            }
+            vec![response]
+        }
+    }
+
+    fn request() -> AnnounceRequest {
+        let mut request = AnnounceRequest::new([1; 20], [2; 20], 6881);
+        request.downloaded = 10;
+        request.left = 20;
+        request.uploaded = 30;
+        request.event = Some(AnnounceEvent::Started);
+        request.key = Some(0xAABBCCDD);
+        request
+    }
+
+    fn actions(stub: &UdpStub) -> Vec<u32> {
+        stub.packets()
+            .iter()
+            .map(|packet| read_u32(packet, 8))
+            .collect()
+    }
+
+    #[test]
+    fn test_announce_v4() {
+        let stub = UdpStub::serve("127.0.0.1:0", tracker(vec![10, 0, 0, 1, 0x1a, 0xe1]));
+        let mut tracker = UdpTracker::new(stub.addr);
+
+        let response = tracker.announce(&request()).unwrap();
+        assert_eq!(response.interval, Duration::from_secs(1800));
+        assert_eq!(response.incomplete, Some(3));
+        assert_eq!(response.complete, Some(5));
+        assert_eq!(
+            response.peers,
+            vec![Peer::new("10.0.0.1:6881".parse().unwrap())]
+        );
+
+        let packets = stub.packets();
+        assert_eq!(packets[0].len(), 16);
+        let announce = &packets[1];
+        assert_eq!(announce.len(), 98);
+        assert_eq!(&announce[..8], CONNECTION_ID.to_be_bytes());
+        assert_eq!(&announce[16..36], [1; 20]);
+        assert_eq!(&announce[36..56], [2; 20]);
+        assert_eq!(&announce[56..64], 10u64.to_be_bytes());
+        assert_eq!(&announce[64..72], 20u64.to_be_bytes());
+        assert_eq!(&announce[72..80], 30u64.to_be_bytes());
+        assert_eq!(read_u32(announce, 80), 2); // started
+        assert_eq!(read_u32(announce, 88), 0xAABBCCDD);
+        assert_eq!(read_u32(announce, 92), u32::MAX); // numwant -1
+        assert_eq!(&announce[96..], 6881u16.to_be_bytes());
+    }
+
+    #[test]
+    fn test_url_data() {
+        let stub = UdpStub::serve("127.0.0.1:0", tracker(vec![]));
+        let url = format!("udp://{}/k1/announce?x=1", stub.addr);
+        UdpTracker::from_url(&url)
+            .unwrap()
+            .announce(&request())
+            .unwrap();
+        assert_eq!(&stub.packets()[1][98..], b"\x02\x10/k1/announce?x=1");
+
+        // Long paths are split over several options
+        let stub = UdpStub::serve("127.0.0.1:0", tracker(vec![]));
+        let path = format!("/{}", "a".repeat(300));
+        UdpTracker::new(stub.addr)
+            .url_data(path.as_str())
+            .announce(&request())
+            .unwrap();
+        let options = &stub.packets()[1][98..];
+        assert_eq!(options[..2], [OPTION_URL_DATA, 255]);
+        assert_eq!(options[257..259], [OPTION_URL_DATA, 46]);
+        assert_eq!(options.len(), 2 + 255 + 2 + 46);
+    }
+
+    #[test]
+    fn test_announce_v6() {
+        let mut peers = "2001:db8::1".parse::<Ipv6Addr>().unwrap().octets().to_vec();
+        peers.extend_from_slice(&51413u16.to_be_bytes());
+        let stub = UdpStub::serve("[::1]:0", tracker(peers));
+
+        let response = UdpTracker::new(stub.addr).announce(&request()).unwrap();
+        assert_eq!(
+            response.peers,
+            vec![Peer::new("[2001:db8::1]:51413".parse().unwrap())]
+        );
+    }
+
+    #[test]
+    fn test_connection_id_is_cached() {
+        let stub = UdpStub::serve("127.0.0.1:0", tracker(vec![]));
+        let mut tracker = UdpTracker::new(stub.addr);
+
+        tracker.announce(&request()).unwrap();
+        tracker.scrape(&[[7; 20]]).unwrap();
+        tracker.announce(&request()).unwrap();
+        assert_eq!(
+            actions(&stub),
+            vec![
+                ACTION_CONNECT,
+                ACTION_ANNOUNCE,
+                ACTION_SCRAPE,
+                ACTION_ANNOUNCE
+            ]
+        );
+
+        // An expired connection ID is replaced before the next request
+        tracker.connection = Some((CONNECTION_ID, Instant::now() - CONNECTION_LIFETIME));
+        tracker.announce(&request()).unwrap();
+        assert_eq!(actions(&stub)[4..], [ACTION_CONNECT, ACTION_ANNOUNCE]);
+    }
+
+    #[test]
+    fn test_scrape() {
+        let stub = UdpStub::serve("127.0.0.1:0", tracker(vec![]));
+        let mut tracker = UdpTracker::new(stub.addr);
+
+        let stats = tracker.scrape(&[[4; 20], [9; 20]]).unwrap();
+        assert_eq!(
+            stats,
+            BTreeMap::from([
+                (
+                    [4; 20],
+                    ScrapeStats {
+                        complete: 4,
+                        downloaded: 2,
+                        incomplete: 3,
+                    }
+                ),
+                (
+                    [9; 20],
+                    ScrapeStats {
+                        complete: 9,
+                        downloaded: 2,
+                        incomplete: 3,
+                    }
+                ),
+            ])
+        );
+
+        assert_eq!(tracker.scrape(&[]), Ok(BTreeMap::new()));
+        assert_eq!(stub.packets().len(), 2);
+
+        // Larger scrapes are split across packets
+        let info_hashes: Vec<[u8; 20]> = (0..100).map(|i| [i; 20]).collect();
+        let stats = tracker.scrape(&info_hashes).unwrap();
+        assert_eq!(stats.len(), 100);
+        assert_eq!(stats[&[99; 20]].complete, 99);
+        let sizes: Vec<usize> = stub.packets()[2..].iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![16 + 20 * MAX_SCRAPE_HASHES, 16 + 20 * 26]);
+    }
+
+    #[test]
+    fn test_error_packet() {
+        let stub = UdpStub::serve("127.0.0.1:0", tracker(vec![]));
+        let mut tracker = UdpTracker::new(stub.addr);
+        tracker.connection = Some((1, Instant::now()));
+        assert_eq!(
+            tracker.announce(&request()),
+            Err(TrackerError::Failure("bad connection id".to_string()))
+        );
+
+        // The rejected connection ID is dropped
+        tracker.announce(&request()).unwrap();
+        assert_eq!(
+            actions(&stub),
+            vec![ACTION_ANNOUNCE, ACTION_CONNECT, ACTION_ANNOUNCE]
+        );
+    }
+
+    #[test]
+    fn test_max_retries_is_capped() {
+        let tracker = UdpTracker::new("127.0.0.1:6969".parse().unwrap())
+            .timeout(Duration::MAX)
+            .max_retries(40);
+        assert_eq!(tracker.max_retries, MAX_RETRIES);
+        assert_eq!(
+            tracker.timeout.saturating_mul(1 << MAX_RETRIES),
+            Duration::MAX
+        );
+    }
+
+    #[test]
+    fn test_retransmits_lost_packets() {
+        let mut answer = tracker(vec![]);
+        let mut received = 0;
+        // Drops the first connect and the first announce
+        let stub = UdpStub::serve("127.0.0.1:0", move |packet| {
+            received += 1;
+            match received {
+                1 | 3 => vec![],
+                _ => answer(packet),
+            }
+        });
+        let mut tracker = UdpTracker::new(stub.addr)
+            .timeout(Duration::from_millis(20))
+            .max_retries(3);
+
+        tracker.announce(&request()).unwrap();
+        assert_eq!(
+            actions(&stub),
+            vec![
+                ACTION_CONNECT,
+                ACTION_CONNECT,
+                ACTION_ANNOUNCE,
+                ACTION_ANNOUNCE
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let stub = UdpStub::serve("127.0.0.1:0", |_| vec![]);
+        let mut tracker = UdpTracker::new(stub.addr)
+            .timeout(Duration::from_millis(10))
+            .max_retries(2);
+
+        let started = Instant::now();
+        assert_eq!(tracker.announce(&request()), Err(TrackerError::Timeout));
+        // 10 + 20 + 40 milliseconds
+        assert!(started.elapsed() >= Duration::from_millis(70));
+        assert_eq!(stub.packets().len(), 3);
+    }
+
+    #[test]
+    fn test_ignores_stray_packets() {
+        let mut answer = tracker(vec![]);
+        let stub = UdpStub::serve("127.0.0.1:0", move |packet| {
+            let mut responses = answer(packet);
+            // A late answer to some other transaction arrives first
+            let mut stray = responses[0].clone();
+            stray[4] ^= 0xff;
+            responses.insert(0, stray);
+            responses.insert(0, vec![0; 3]);
+            responses
+        });
+
+        let response = UdpTracker::new(stub.addr).announce(&request()).unwrap();
+        assert_eq!(response.interval, Duration::from_secs(1800));
+    }
+
+    #[test]
+    fn test_malformed_responses() {
+        // Every request, announces included, is answered with a connect response
+        let stub = UdpStub::serve("127.0.0.1:0", |packet| {
+            let mut response = header(0, ACTION_CONNECT, read_u32(packet, 12))[8..].to_vec();
+            response.extend_from_slice(&CONNECTION_ID.to_be_bytes());
+            vec![response]
+        });
+        assert_eq!(
+            UdpTracker::new(stub.addr).announce(&request()),
+            Err(TrackerError::MalformedPacket("unexpected action"))
+        );
+
+        let stub = UdpStub::serve("127.0.0.1:0", tracker(vec![1, 2, 3]));
+        assert_eq!(
+            UdpTracker::new(stub.addr).announce(&request()),
+            Err(TrackerError::MalformedPacket("truncated peer list"))
+        );
+    }
+
+    #[test]
+    fn test_from_url() {
+        let tracker = UdpTracker::from_url("udp://127.0.0.1:6969/announce").unwrap();
+        assert_eq!(tracker.addr(), "127.0.0.1:6969".parse().unwrap());
+        let tracker = UdpTracker::from_url("udp://[::1]:80").unwrap();
+        assert_eq!(tracker.addr(), "[::1]:80".parse().unwrap());
+
+        for url in ["http://127.0.0.1:80/announce", "udp://127.0.0.1/announce"] {
+            assert_eq!(
+                UdpTracker::from_url(url).unwrap_err(),
+                TrackerError::InvalidUrl(url.to_string())
+            );
+        }
+    }
+}
@@ -0,0 +1,30 @@
+/// Why a client announces; regular re-announces send no event
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum AnnounceEvent {
+    /// The first announce of a download
+    Started,
+    /// Sent once when the download finishes, not when starting already complete
+    Completed,
+    /// The client is shutting down the torrent
+    Stopped,
+}
+
+impl AnnounceEvent {
+    /// Value of the HTTP `event` parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnnounceEvent::Started => "started",
+            AnnounceEvent::Completed => "completed",
+            AnnounceEvent::Stopped => "stopped",
+        }
+    }
+
+    /// Value of the `event` field of a UDP announce; 0 means no event
+    pub fn udp_id(&self) -> u32 {
+        match self {
+            AnnounceEvent::Completed => 1,
+            AnnounceEvent::Started => 2,
+            AnnounceEvent::Stopped => 3,
+        }
+    }
+}
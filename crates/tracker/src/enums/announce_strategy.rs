@@ -0,0 +1,10 @@
+/// How an `AnnounceScheduler` uses the tiers of a torrent's trackers
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum AnnounceStrategy {
+    /// Every tracker of every tier is announced to on its own schedule
+    #[default]
+    AllTiers,
+    /// BEP 12: trackers are tried in tier order until one answers, which then
+    /// moves to the front of its tier
+    FirstSuccess,
+}
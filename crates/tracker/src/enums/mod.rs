@@ -0,0 +1,5 @@
+pub mod announce_event;
+pub mod announce_strategy;
+pub mod tracker_client;
+pub mod tracker_error;
+pub mod tracker_status;
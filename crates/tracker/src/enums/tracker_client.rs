@@ -0,0 +1,60 @@
+use super::tracker_error::TrackerError;
+use crate::{
+    clients::{http::HttpTracker, udp::UdpTracker},
+    models::{
+        announce_request::AnnounceRequest, announce_response::AnnounceResponse,
+        scrape_stats::ScrapeStats,
+    },
+};
+use std::{collections::BTreeMap, time::Duration};
+
+/// A client for either kind of tracker, picked from the URL scheme
+#[derive(Debug)]
+pub enum TrackerClient {
+    Http(HttpTracker),
+    Udp(UdpTracker),
+}
+
+impl TrackerClient {
+    /// `http://` and `https://` URLs get an HTTP client, `udp://` URLs a UDP one
+    pub fn from_url(url: &str) -> Result<Self, TrackerError> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            Ok(TrackerClient::Http(HttpTracker::new(url)))
+        } else if url.starts_with("udp://") {
+            Ok(TrackerClient::Udp(UdpTracker::from_url(url)?))
+        } else {
+            Err(TrackerError::InvalidUrl(url.to_string()))
+        }
+    }
+
+    /// Limits each request to `timeout`; UDP requests are also resent at most
+    /// `max_retries` times, while HTTP requests are never resent
+    pub fn limits(self, timeout: Duration, max_retries: u32) -> Self {
+        match self {
+            TrackerClient::Http(tracker) => TrackerClient::Http(tracker.timeout(timeout)),
+            TrackerClient::Udp(tracker) => {
+                TrackerClient::Udp(tracker.timeout(timeout).max_retries(max_retries))
+            }
+        }
+    }
+
+    pub fn announce(
+        &mut self,
+        request: &AnnounceRequest,
+    ) -> Result<AnnounceResponse, TrackerError> {
+        match self {
+            TrackerClient::Http(tracker) => tracker.announce(request),
+            TrackerClient::Udp(tracker) => tracker.announce(request),
+        }
+    }
+
+    pub fn scrape(
+        &mut self,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<BTreeMap<[u8; 20], ScrapeStats>, TrackerError> {
+        match self {
+            TrackerClient::Http(tracker) => tracker.scrape(info_hashes),
+            TrackerClient::Udp(tracker) => tracker.scrape(info_hashes),
+        }
+    }
+}
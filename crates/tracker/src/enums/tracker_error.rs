@@ -0,0 +1,76 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TrackerError {
+    /// The request could not be sent or the response not received
+    Transport(String),
+    /// The tracker answered with a status other than 200
+    Status(u16),
+    /// The response is not valid bencode
+    Decode(&'static str),
+    /// The response is not a dictionary
+    NotADictionary,
+    MissingKey(&'static str),
+    WrongType {
+        key: &'static str,
+        expected: &'static str,
+    },
+    InvalidValue {
+        key: &'static str,
+        reason: &'static str,
+    },
+    /// The tracker refused the request; holds its `failure reason`
+    Failure(String),
+    /// A UDP tracker sent a packet that does not follow BEP 15
+    MalformedPacket(&'static str),
+    /// No answer arrived after every retransmission
+    Timeout,
+    /// An invalid tracker URL
+    InvalidUrl(String),
+    /// The announce URL does not follow the `/announce` convention, so the
+    /// tracker has no known scrape URL
+    ScrapeUnsupported,
+}
+
+impl fmt::Display for TrackerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackerError::Transport(reason) => write!(f, "request failed: {reason}"),
+            TrackerError::Status(status) => write!(f, "tracker returned HTTP {status}"),
+            TrackerError::Decode(reason) => write!(f, "invalid bencode: {reason}"),
+            TrackerError::NotADictionary => write!(f, "response must be a dictionary"),
+            TrackerError::MissingKey(key) => write!(f, "missing required key '{key}'"),
+            TrackerError::WrongType { key, expected } => {
+                write!(f, "key '{key}' must be {expected}")
+            }
+            TrackerError::InvalidValue { key, reason } => {
+                write!(f, "invalid value for '{key}': {reason}")
+            }
+            TrackerError::Failure(reason) => write!(f, "tracker failure: {reason}"),
+            TrackerError::MalformedPacket(reason) => write!(f, "malformed packet: {reason}"),
+            TrackerError::Timeout => write!(f, "tracker did not respond"),
+            TrackerError::InvalidUrl(url) => write!(f, "invalid tracker URL '{url}'"),
+            TrackerError::ScrapeUnsupported => write!(f, "tracker does not support scrape"),
+        }
+    }
+}
+
+impl std::error::Error for TrackerError {}
+
+impl From<&'static str> for TrackerError {
+    fn from(reason: &'static str) -> Self {
+        TrackerError::Decode(reason)
+    }
+}
+
+impl From<ureq::Error> for TrackerError {
+    fn from(err: ureq::Error) -> Self {
+        TrackerError::Transport(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for TrackerError {
+    fn from(err: std::io::Error) -> Self {
+        TrackerError::Transport(err.to_string())
+    }
+}
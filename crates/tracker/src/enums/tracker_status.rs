@@ -0,0 +1,28 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TrackerStatus {
+    /// Not announced to yet
+    NotContacted,
+    /// The last announce succeeded
+    Working,
+    /// The last announce failed; it is retried after a backoff
+    Failing,
+    /// The URL has no supported scheme, so the tracker is never contacted
+    Unsupported,
+    /// `stopped` was sent on shutdown
+    Stopped,
+}
+
+impl fmt::Display for TrackerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            TrackerStatus::NotContacted => "not contacted",
+            TrackerStatus::Working => "working",
+            TrackerStatus::Failing => "failing",
+            TrackerStatus::Unsupported => "unsupported",
+            TrackerStatus::Stopped => "stopped",
+        };
+        f.write_str(status)
+    }
+}
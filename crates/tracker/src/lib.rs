@@ -0,0 +1,7 @@
+pub mod clients;
+pub mod enums;
+pub mod models;
+pub mod parsers;
+pub mod schedulers;
+#[cfg(test)]
+mod stubs;
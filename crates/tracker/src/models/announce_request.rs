@@ -0,0 +1,101 @@
+use crate::enums::announce_event::AnnounceEvent;
+use rand::{Rng, distr::Alphanumeric};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+/// Parameters of one announce, shared by the HTTP and UDP clients
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AnnounceRequest {
+    /// v1 info-hash, or the truncated v2 info-hash for v2-only torrents
+    pub info_hash: [u8; 20],
+    pub peer_id: [u8; 20],
+    pub port: u16,
+    pub uploaded: u64,
+    pub downloaded: u64,
+    /// Bytes still missing
+    pub left: u64,
+    pub event: Option<AnnounceEvent>,
+    /// Asks for 6-byte compact peers instead of a list of dictionaries
+    pub compact: bool,
+    /// How many peers to ask for; the tracker decides when unset
+    pub numwant: Option<u32>,
+    /// Identifies the client across IP address changes; never shared with peers
+    pub key: Option<u32>,
+    /// The `tracker id` of the tracker's previous response
+    pub tracker_id: Option<Vec<u8>>,
+    /// BEP 7: an IPv4 address to list besides the one the request comes from
+    pub ipv4: Option<Ipv4Addr>,
+    /// BEP 7: an IPv6 address to list besides the one the request comes from
+    pub ipv6: Option<Ipv6Addr>,
+    /// Asks for non-compact peer lists without peer IDs; ignored by trackers
+    /// that send compact lists
+    pub no_peer_id: bool,
+}
+
+impl AnnounceRequest {
+    pub fn new(info_hash: [u8; 20], peer_id: [u8; 20], port: u16) -> Self {
+        AnnounceRequest {
+            info_hash,
+            peer_id,
+            port,
+            uploaded: 0,
+            downloaded: 0,
+            left: 0,
+            event: None,
+            compact: true,
+            numwant: None,
+            key: None,
+            tracker_id: None,
+            ipv4: None,
+            ipv6: None,
+            no_peer_id: false,
+        }
+    }
+
+    /// Replaces the peer ID with `prefix` followed by random alphanumeric
+    /// characters, and the key with a random one. Doing this per torrent keeps
+    /// trackers and peers from linking a client's torrents together
+    pub fn randomise_identity(mut self, prefix: &[u8]) -> Self {
+        self.peer_id = random_peer_id(prefix);
+        self.key = Some(rand::random());
+        self
+    }
+}
+
+/// A peer ID made of `prefix`, such as an Azureus-style `-XX0100-`, followed by
+/// random alphanumeric characters; a prefix longer than 20 bytes is truncated
+pub fn random_peer_id(prefix: &[u8]) -> [u8; 20] {
+    let mut peer_id = [0; 20];
+    let prefix = &prefix[..prefix.len().min(20)];
+    peer_id[..prefix.len()].copy_from_slice(prefix);
+    let mut rng = rand::rng();
+    for byte in &mut peer_id[prefix.len()..] {
+        *byte = rng.sample(Alphanumeric);
+    }
+    peer_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_peer_id() {
+        let peer_id = random_peer_id(b"-RS0100-");
+        assert!(peer_id.starts_with(b"-RS0100-"));
+        assert!(peer_id[8..].iter().all(u8::is_ascii_alphanumeric));
+        assert_ne!(random_peer_id(b"-RS0100-"), peer_id);
+
+        assert_eq!(random_peer_id(&[b'x'; 25]), [b'x'; 20]);
+        assert!(random_peer_id(b"").iter().all(u8::is_ascii_alphanumeric));
+    }
+
+    #[test]
+    fn test_randomise_identity() {
+        let base = AnnounceRequest::new([1; 20], [0; 20], 6881);
+        let first = base.clone().randomise_identity(b"-RS0100-");
+        let second = base.randomise_identity(b"-RS0100-");
+        assert_ne!(first.peer_id, second.peer_id);
+        assert!(first.key.is_some());
+        assert_ne!(first.key, second.key);
+    }
+}
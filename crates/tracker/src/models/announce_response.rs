@@ -0,0 +1,21 @@
+use super::peer::Peer;
+use std::{net::IpAddr, time::Duration};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AnnounceResponse {
+    /// How long to wait before the next regular announce
+    pub interval: Duration,
+    /// Announcing sooner than this may be refused
+    pub min_interval: Option<Duration>,
+    /// To be sent back in the next announce to this tracker
+    pub tracker_id: Option<Vec<u8>>,
+    /// Seeders
+    pub complete: Option<u32>,
+    /// Leechers
+    pub incomplete: Option<u32>,
+    pub peers: Vec<Peer>,
+    /// Shown to the user; the announce itself succeeded
+    pub warning_message: Option<String>,
+    /// BEP 24: the client's address as seen by the tracker
+    pub external_ip: Option<IpAddr>,
+}
@@ -0,0 +1,5 @@
+pub mod announce_request;
+pub mod announce_response;
+pub mod peer;
+pub mod scrape_stats;
+pub mod tracker_stats;
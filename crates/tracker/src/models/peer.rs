@@ -0,0 +1,18 @@
+use std::net::SocketAddr;
+
+/// A peer returned by a tracker
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Peer {
+    pub addr: SocketAddr,
+    /// Only present in non-compact dictionary peer lists
+    pub peer_id: Option<[u8; 20]>,
+}
+
+impl Peer {
+    pub fn new(addr: SocketAddr) -> Self {
+        Peer {
+            addr,
+            peer_id: None,
+        }
+    }
+}
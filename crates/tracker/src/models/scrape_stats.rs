@@ -0,0 +1,10 @@
+/// Swarm counts for one torrent, as returned by a scrape
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct ScrapeStats {
+    /// Seeders
+    pub complete: u32,
+    /// Leechers
+    pub incomplete: u32,
+    /// Completed downloads the tracker has seen
+    pub downloaded: u32,
+}
@@ -0,0 +1,51 @@
+use crate::enums::{tracker_error::TrackerError, tracker_status::TrackerStatus};
+use std::{
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+/// What a scheduler knows about one tracker, for display
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TrackerStats {
+    pub url: String,
+    /// Index of the tracker's tier
+    pub tier: usize,
+    pub status: TrackerStatus,
+    /// Why the last announce failed; cleared by a successful one
+    pub last_error: Option<TrackerError>,
+    pub consecutive_failures: u32,
+    /// Time of the last successful announce
+    pub last_announce: Option<Instant>,
+    /// When the tracker will be announced to next; `None` while it is due
+    pub next_announce: Option<Instant>,
+    pub interval: Option<Duration>,
+    pub min_interval: Option<Duration>,
+    pub seeders: Option<u32>,
+    pub leechers: Option<u32>,
+    /// Peers in the last response
+    pub peers_received: usize,
+    pub warning_message: Option<String>,
+    /// The `external ip` of the last response
+    pub external_ip: Option<IpAddr>,
+}
+
+impl TrackerStats {
+    pub fn new(url: impl Into<String>, tier: usize) -> Self {
+        TrackerStats {
+            url: url.into(),
+            tier,
+            status: TrackerStatus::NotContacted,
+            last_error: None,
+            consecutive_failures: 0,
+            last_announce: None,
+            next_announce: None,
+            interval: None,
+            min_interval: None,
+            seeders: None,
+            leechers: None,
+            peers_received: 0,
+            warning_message: None,
+            external_ip: None,
+        }
+    }
+}
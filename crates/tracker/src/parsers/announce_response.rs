@@ -0,0 +1,200 @@
+use super::{
+    fields::{as_dict, get_bytes, get_text, get_u32, require},
+    peers::{parse_compact_v6, parse_peers},
+};
+use crate::{enums::tracker_error::TrackerError, models::announce_response::AnnounceResponse};
+use bencode::dispatcher::bdecode::decode_bencode;
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::Duration,
+};
+
+/// Parses the bencoded body of an HTTP announce response; a `failure reason`
+/// becomes `TrackerError::Failure`. BEP 7 `peers6` are merged into `peers`
+pub fn parse_announce_response(body: &[u8]) -> Result<AnnounceResponse, TrackerError> {
+    let (value, _) = decode_bencode(body)?;
+    let dict = as_dict(&value)?;
+
+    if let Some(reason) = get_text(dict, "failure reason")? {
+        return Err(TrackerError::Failure(reason));
+    }
+
+    let mut peers = match dict.get(b"peers".as_slice()) {
+        Some(peers) => parse_peers(peers)?,
+        None => Vec::new(),
+    };
+    if let Some(peers6) = get_bytes(dict, "peers6")? {
+        peers.extend(parse_compact_v6(peers6)?);
+    }
+    // A dual-stack tracker may list the same peer in both forms, possibly as an
+    // IPv4-mapped IPv6 address
+    for peer in &mut peers {
+        peer.addr.set_ip(peer.addr.ip().to_canonical());
+    }
+    let mut seen = HashSet::new();
+    peers.retain(|peer| seen.insert(peer.addr));
+    let seconds = |secs: u32| Duration::from_secs(secs.into());
+
+    Ok(AnnounceResponse {
+        interval: seconds(require(get_u32(dict, "interval")?, "interval")?),
+        min_interval: get_u32(dict, "min interval")?.map(seconds),
+        tracker_id: get_bytes(dict, "tracker id")?.map(<[u8]>::to_vec),
+        complete: get_u32(dict, "complete")?,
+        incomplete: get_u32(dict, "incomplete")?,
+        peers,
+        warning_message: get_text(dict, "warning message")?,
+        external_ip: get_bytes(dict, "external ip")?
+            .map(parse_external_ip)
+            .transpose()?,
+    })
+}
+
+/// `external ip` holds the raw address: 4 bytes for IPv4, 16 for IPv6
+fn parse_external_ip(bytes: &[u8]) -> Result<IpAddr, TrackerError> {
+    if let Ok(ip) = <[u8; 4]>::try_from(bytes) {
+        Ok(IpAddr::V4(Ipv4Addr::from(ip)))
+    } else if let Ok(ip) = <[u8; 16]>::try_from(bytes) {
+        Ok(IpAddr::V6(Ipv6Addr::from(ip)).to_canonical())
+    } else {
+        Err(TrackerError::InvalidValue {
+            key: "external ip",
+            reason: "must be 4 or 16 bytes",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::peer::Peer;
+    use bencode::{bencode, dispatcher::bencode::encode};
+
+    #[test]
+    fn test_full_response() {
+        let body = encode(&bencode!({
+            "interval": 1800,
+            "min interval": 900,
+            "tracker id": "abc",
+            "complete": 5,
+            "incomplete": 7,
+            "warning message": "slow down",
+            "peers": (vec![127u8, 0, 0, 1, 0x1a, 0xe1]),
+        }))
+        .unwrap();
+        assert_eq!(
+            parse_announce_response(&body).unwrap(),
+            AnnounceResponse {
+                interval: Duration::from_secs(1800),
+                min_interval: Some(Duration::from_secs(900)),
+                tracker_id: Some(b"abc".to_vec()),
+                complete: Some(5),
+                incomplete: Some(7),
+                peers: vec![Peer::new("127.0.0.1:6881".parse().unwrap())],
+                warning_message: Some("slow down".to_string()),
+                external_ip: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mixed_peers() {
+        let mut peers6 = Vec::new();
+        for ip in ["2001:db8::1", "::ffff:10.0.0.1"] {
+            peers6.extend_from_slice(&ip.parse::<std::net::Ipv6Addr>().unwrap().octets());
+            peers6.extend_from_slice(&6881u16.to_be_bytes());
+        }
+        let body = encode(&bencode!({
+            "interval": 60,
+            "peers": [
+                { "ip": "10.0.0.1", "port": 6881 },
+                { "ip": "2001:db8::1", "port": 6881 },
+            ],
+            "peers6": (peers6.clone()),
+        }))
+        .unwrap();
+        let addrs: Vec<_> = parse_announce_response(&body)
+            .unwrap()
+            .peers
+            .into_iter()
+            .map(|peer| peer.addr)
+            .collect();
+        assert_eq!(
+            addrs,
+            vec![
+                "10.0.0.1:6881".parse().unwrap(),
+                "[2001:db8::1]:6881".parse().unwrap(),
+            ]
+        );
+
+        // peers6 alone, as an IPv6-only tracker sends it
+        let body = encode(&bencode!({ "interval": 60, "peers": "", "peers6": peers6 })).unwrap();
+        let peers = parse_announce_response(&body).unwrap().peers;
+        assert_eq!(peers[1].addr, "10.0.0.1:6881".parse().unwrap());
+
+        assert!(matches!(
+            parse_announce_response(b"d8:intervali60e6:peers61:xe"),
+            Err(TrackerError::InvalidValue { key: "peers6", .. })
+        ));
+        assert!(matches!(
+            parse_announce_response(b"d8:intervali60e6:peers6lee"),
+            Err(TrackerError::WrongType { key: "peers6", .. })
+        ));
+    }
+
+    #[test]
+    fn test_external_ip() {
+        let external_ip = |ip: Vec<u8>| {
+            let body = encode(&bencode!({ "interval": 60, "external ip": ip })).unwrap();
+            parse_announce_response(&body).map(|response| response.external_ip)
+        };
+        assert_eq!(
+            external_ip(vec![203, 0, 113, 9]),
+            Ok(Some("203.0.113.9".parse().unwrap()))
+        );
+        let ipv6: Ipv6Addr = "2001:db8::9".parse().unwrap();
+        assert_eq!(external_ip(ipv6.octets().to_vec()), Ok(Some(ipv6.into())));
+        assert!(matches!(
+            external_ip(b"203.0.113.9".to_vec()),
+            Err(TrackerError::InvalidValue {
+                key: "external ip",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_minimal_response() {
+        let response = parse_announce_response(b"d8:intervali60ee").unwrap();
+        assert_eq!(response.interval, Duration::from_secs(60));
+        assert_eq!(response.min_interval, None);
+        assert!(response.peers.is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse_announce_response(b"d14:failure reason12:unregisterede"),
+            Err(TrackerError::Failure("unregistered".to_string()))
+        );
+        assert_eq!(
+            parse_announce_response(b"d5:peers0:e"),
+            Err(TrackerError::MissingKey("interval"))
+        );
+        assert!(matches!(
+            parse_announce_response(b"d8:intervali-1ee"),
+            Err(TrackerError::InvalidValue {
+                key: "interval",
+                ..
+            })
+        ));
+        assert_eq!(
+            parse_announce_response(b"<html>"),
+            Err(TrackerError::Decode("Unknown type prefix"))
+        );
+        assert_eq!(
+            parse_announce_response(b"le"),
+            Err(TrackerError::NotADictionary)
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::enums::tracker_error::TrackerError;
+use bencode::enums::bencode::BencodeValue;
+use std::collections::BTreeMap;
+
+pub type Dict = BTreeMap<Vec<u8>, BencodeValue>;
+
+pub fn as_dict(value: &BencodeValue) -> Result<&Dict, TrackerError> {
+    match value {
+        BencodeValue::Dict(dict) => Ok(dict),
+        _ => Err(TrackerError::NotADictionary),
+    }
+}
+
+pub fn get_u32(dict: &Dict, key: &'static str) -> Result<Option<u32>, TrackerError> {
+    match dict.get(key.as_bytes()) {
+        None => Ok(None),
+        Some(BencodeValue::Int(n)) => {
+            u32::try_from(*n)
+                .map(Some)
+                .map_err(|_| TrackerError::InvalidValue {
+                    key,
+                    reason: "must be between 0 and 2^32 - 1",
+                })
+        }
+        Some(_) => Err(TrackerError::WrongType {
+            key,
+            expected: "an integer",
+        }),
+    }
+}
+
+pub fn get_bytes<'a>(dict: &'a Dict, key: &'static str) -> Result<Option<&'a [u8]>, TrackerError> {
+    match dict.get(key.as_bytes()) {
+        None => Ok(None),
+        Some(BencodeValue::Str(bytes)) => Ok(Some(bytes)),
+        Some(_) => Err(TrackerError::WrongType {
+            key,
+            expected: "a string",
+        }),
+    }
+}
+
+/// Trackers are not always careful with encodings, so invalid UTF-8 is replaced
+pub fn get_text(dict: &Dict, key: &'static str) -> Result<Option<String>, TrackerError> {
+    Ok(get_bytes(dict, key)?.map(|bytes| String::from_utf8_lossy(bytes).into_owned()))
+}
+
+pub fn require<T>(value: Option<T>, key: &'static str) -> Result<T, TrackerError> {
+    value.ok_or(TrackerError::MissingKey(key))
+}
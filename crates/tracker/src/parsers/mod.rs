@@ -0,0 +1,4 @@
+pub mod announce_response;
+pub mod fields;
+pub mod peers;
+pub mod scrape_response;
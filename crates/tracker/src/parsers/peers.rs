@@ -0,0 +1,181 @@
+use super::fields::{Dict, as_dict, get_bytes, require};
+use crate::{enums::tracker_error::TrackerError, models::peer::Peer};
+use bencode::enums::bencode::BencodeValue;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// Parses `peers` in either form: a string of compact 6-byte IPv4 peers or a
+/// list of dictionaries. Dictionary peers whose `ip` is a host name are skipped
+pub fn parse_peers(value: &BencodeValue) -> Result<Vec<Peer>, TrackerError> {
+    match value {
+        BencodeValue::Str(bytes) => parse_compact_v4(bytes),
+        BencodeValue::List(peers) => {
+            let mut parsed = Vec::with_capacity(peers.len());
+            for peer in peers {
+                if let Some(peer) = parse_peer_dict(as_dict(peer)?)? {
+                    parsed.push(peer);
+                }
+            }
+            Ok(parsed)
+        }
+        _ => Err(TrackerError::WrongType {
+            key: "peers",
+            expected: "a string or a list of dictionaries",
+        }),
+    }
+}
+
+/// Parses compact IPv4 peers: 4 address bytes then a 2-byte port, big-endian
+pub fn parse_compact_v4(bytes: &[u8]) -> Result<Vec<Peer>, TrackerError> {
+    if !bytes.len().is_multiple_of(6) {
+        return Err(TrackerError::InvalidValue {
+            key: "peers",
+            reason: "compact peers must be a multiple of 6 bytes",
+        });
+    }
+    Ok(bytes
+        .chunks_exact(6)
+        .map(|chunk| {
+            let ip = Ipv4Addr::new(chunk[0], chunk[1], chunk[2], chunk[3]);
+            let port = u16::from_be_bytes([chunk[4], chunk[5]]);
+            Peer::new(SocketAddr::new(IpAddr::V4(ip), port))
+        })
+        .collect())
+}
+
+/// Parses compact IPv6 peers: 16 address bytes then a 2-byte port, big-endian
+pub fn parse_compact_v6(bytes: &[u8]) -> Result<Vec<Peer>, TrackerError> {
+    if !bytes.len().is_multiple_of(18) {
+        return Err(TrackerError::InvalidValue {
+            key: "peers6",
+            reason: "compact IPv6 peers must be a multiple of 18 bytes",
+        });
+    }
+    Ok(bytes
+        .chunks_exact(18)
+        .map(|chunk| {
+            let ip: [u8; 16] = chunk[..16].try_into().unwrap();
+            let port = u16::from_be_bytes([chunk[16], chunk[17]]);
+            Peer::new(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), port))
+        })
+        .collect())
+}
+
+fn parse_peer_dict(dict: &Dict) -> Result<Option<Peer>, TrackerError> {
+    let ip = require(get_bytes(dict, "ip")?, "ip")?;
+    let port = match dict.get(b"port".as_slice()) {
+        Some(BencodeValue::Int(port)) => {
+            u16::try_from(*port).map_err(|_| TrackerError::InvalidValue {
+                key: "port",
+                reason: "must be between 0 and 65535",
+            })?
+        }
+        Some(_) => {
+            return Err(TrackerError::WrongType {
+                key: "port",
+                expected: "an integer",
+            });
+        }
+        None => return Err(TrackerError::MissingKey("port")),
+    };
+    let peer_id = match get_bytes(dict, "peer id")? {
+        Some(id) => Some(
+            <[u8; 20]>::try_from(id).map_err(|_| TrackerError::InvalidValue {
+                key: "peer id",
+                reason: "must be 20 bytes",
+            })?,
+        ),
+        None => None,
+    };
+
+    let Some(ip) = std::str::from_utf8(ip).ok().and_then(|ip| ip.parse().ok()) else {
+        return Ok(None);
+    };
+    Ok(Some(Peer {
+        addr: SocketAddr::new(ip, port),
+        peer_id,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::bencode;
+
+    #[test]
+    fn test_compact_peers() {
+        let peers = parse_peers(&bencode!(vec![
+            10u8, 0, 0, 1, 0x1a, 0xe1, 1, 2, 3, 4, 0, 80
+        ]))
+        .unwrap();
+        assert_eq!(
+            peers,
+            vec![
+                Peer::new("10.0.0.1:6881".parse().unwrap()),
+                Peer::new("1.2.3.4:80".parse().unwrap()),
+            ]
+        );
+        assert_eq!(parse_peers(&bencode!("")), Ok(vec![]));
+        assert!(matches!(
+            parse_peers(&bencode!(vec![0u8; 7])),
+            Err(TrackerError::InvalidValue { key: "peers", .. })
+        ));
+    }
+
+    #[test]
+    fn test_compact_v6_peers() {
+        let mut bytes = Ipv6Addr::LOCALHOST.octets().to_vec();
+        bytes.extend_from_slice(&6881u16.to_be_bytes());
+        assert_eq!(
+            parse_compact_v6(&bytes),
+            Ok(vec![Peer::new("[::1]:6881".parse().unwrap())])
+        );
+        assert!(matches!(
+            parse_compact_v6(&bytes[..17]),
+            Err(TrackerError::InvalidValue { key: "peers6", .. })
+        ));
+    }
+
+    #[test]
+    fn test_dictionary_peers() {
+        let peers = parse_peers(&bencode!([
+            { "ip": "10.0.0.1", "port": 6881, "peer id": (vec![b'a'; 20]) },
+            { "ip": "2001:db8::1", "port": 51413 },
+            { "ip": "peer.example", "port": 1 },
+        ]))
+        .unwrap();
+        assert_eq!(
+            peers,
+            vec![
+                Peer {
+                    addr: "10.0.0.1:6881".parse().unwrap(),
+                    peer_id: Some([b'a'; 20]),
+                },
+                Peer::new("[2001:db8::1]:51413".parse().unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_dictionary_peers() {
+        assert_eq!(
+            parse_peers(&bencode!([{ "port": 1 }])),
+            Err(TrackerError::MissingKey("ip"))
+        );
+        assert!(matches!(
+            parse_peers(&bencode!([{ "ip": "1.2.3.4", "port": 70000 }])),
+            Err(TrackerError::InvalidValue { key: "port", .. })
+        ));
+        assert!(matches!(
+            parse_peers(&bencode!([{ "ip": "1.2.3.4", "port": 1, "peer id": "short" }])),
+            Err(TrackerError::InvalidValue { key: "peer id", .. })
+        ));
+        assert_eq!(
+            parse_peers(&bencode!([1])),
+            Err(TrackerError::NotADictionary)
+        );
+        assert!(matches!(
+            parse_peers(&bencode!(1)),
+            Err(TrackerError::WrongType { key: "peers", .. })
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+use super::fields::{as_dict, get_text, get_u32};
+use crate::{enums::tracker_error::TrackerError, models::scrape_stats::ScrapeStats};
+use bencode::{dispatcher::bdecode::decode_bencode, enums::bencode::BencodeValue};
+use std::collections::BTreeMap;
+
+/// Parses the bencoded body of an HTTP scrape response. `files` is keyed by raw
+/// 20-byte info-hashes; counts a tracker leaves out are reported as 0
+pub fn parse_scrape_response(body: &[u8]) -> Result<BTreeMap<[u8; 20], ScrapeStats>, TrackerError> {
+    let (value, _) = decode_bencode(body)?;
+    let dict = as_dict(&value)?;
+
+    if let Some(reason) = get_text(dict, "failure reason")? {
+        return Err(TrackerError::Failure(reason));
+    }
+
+    let files = match dict.get(b"files".as_slice()) {
+        Some(BencodeValue::Dict(files)) => files,
+        Some(_) => {
+            return Err(TrackerError::WrongType {
+                key: "files",
+                expected: "a dictionary",
+            });
+        }
+        None => return Err(TrackerError::MissingKey("files")),
+    };
+
+    let mut stats = BTreeMap::new();
+    for (info_hash, file) in files {
+        let info_hash =
+            <[u8; 20]>::try_from(info_hash.as_slice()).map_err(|_| TrackerError::InvalidValue {
+                key: "files",
+                reason: "keys must be 20-byte info-hashes",
+            })?;
+        let file = as_dict(file)?;
+        stats.insert(
+            info_hash,
+            ScrapeStats {
+                complete: get_u32(file, "complete")?.unwrap_or(0),
+                incomplete: get_u32(file, "incomplete")?.unwrap_or(0),
+                downloaded: get_u32(file, "downloaded")?.unwrap_or(0),
+            },
+        );
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bencode::{bencode, dispatcher::bencode::encode};
+
+    #[test]
+    fn test_files() {
+        // Keys are raw bytes, not hex, and need not be valid UTF-8
+        let body = encode(&bencode!({
+            "files": {
+                (vec![0xffu8; 20]): { "complete": 5, "incomplete": 2, "downloaded": 40 },
+                (vec![b'a'; 20]): { "complete": 1 },
+            },
+        }))
+        .unwrap();
+        assert_eq!(
+            parse_scrape_response(&body),
+            Ok(BTreeMap::from([
+                (
+                    [0xff; 20],
+                    ScrapeStats {
+                        complete: 5,
+                        incomplete: 2,
+                        downloaded: 40,
+                    }
+                ),
+                (
+                    [b'a'; 20],
+                    ScrapeStats {
+                        complete: 1,
+                        ..ScrapeStats::default()
+                    }
+                ),
+            ]))
+        );
+        assert_eq!(parse_scrape_response(b"d5:filesdee"), Ok(BTreeMap::new()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse_scrape_response(b"d14:failure reason8:disablede"),
+            Err(TrackerError::Failure("disabled".to_string()))
+        );
+        assert_eq!(
+            parse_scrape_response(b"de"),
+            Err(TrackerError::MissingKey("files"))
+        );
+        assert!(matches!(
+            parse_scrape_response(b"d5:fileslee"),
+            Err(TrackerError::WrongType { key: "files", .. })
+        ));
+        assert!(matches!(
+            parse_scrape_response(b"d5:filesd3:abcdeee"),
+            Err(TrackerError::InvalidValue { key: "files", .. })
+        ));
+        assert!(matches!(
+            parse_scrape_response(
+                &encode(&bencode!({
+                    "files": { (vec![0u8; 20]): { "complete": "many" } },
+                }))
+                .unwrap()
+            ),
+            Err(TrackerError::WrongType {
+                key: "complete",
+                ..
+            })
+        ));
+    }
+}
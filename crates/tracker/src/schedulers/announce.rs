@@ -0,0 +1,695 @@
+use crate::{
+    enums::{
+        announce_event::AnnounceEvent, announce_strategy::AnnounceStrategy,
+        tracker_client::TrackerClient, tracker_error::TrackerError, tracker_status::TrackerStatus,
+    },
+    models::{announce_request::AnnounceRequest, peer::Peer, tracker_stats::TrackerStats},
+};
+use metainfo::models::{metainfo::Metainfo, tracker_list::TrackerList};
+use std::{
+    collections::HashSet,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+/// Shortest wait between announces to a tracker, whatever interval it sends
+pub const MIN_ANNOUNCE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// One tracker and the announce state kept for it
+#[derive(Debug)]
+struct Slot {
+    client: Option<TrackerClient>,
+    stats: TrackerStats,
+    tracker_id: Option<Vec<u8>>,
+    /// `started` has been delivered
+    started: bool,
+    /// `completed` is still to be delivered
+    completed_pending: bool,
+}
+
+impl Slot {
+    fn is_due(&self, now: Instant) -> bool {
+        self.client.is_some()
+            && self.stats.status != TrackerStatus::Stopped
+            && self.stats.next_announce.is_none_or(|next| next <= now)
+    }
+}
+
+/// Decides when to announce to each of a torrent's trackers. It is driven by
+/// `poll`, which announces to every tracker that is due and blocks meanwhile;
+/// the announce events are chosen by the scheduler, not the caller
+#[derive(Debug)]
+pub struct AnnounceScheduler {
+    tiers: Vec<Vec<Slot>>,
+    strategy: AnnounceStrategy,
+    retry_base: Duration,
+    retry_max: Duration,
+    timeout: Duration,
+    max_retries: u32,
+    stop_timeout: Duration,
+    /// With `FirstSuccess`, when the next round through the tiers starts
+    next_cycle: Option<Instant>,
+    external_ip: Option<IpAddr>,
+}
+
+impl AnnounceScheduler {
+    pub fn new(strategy: AnnounceStrategy) -> Self {
+        AnnounceScheduler {
+            tiers: Vec::new(),
+            strategy,
+            retry_base: Duration::from_secs(60),
+            retry_max: Duration::from_secs(60 * 60),
+            timeout: Duration::from_secs(15),
+            max_retries: 2,
+            stop_timeout: Duration::from_secs(5),
+            next_cycle: None,
+            external_ip: None,
+        }
+    }
+
+    /// Trackers of `announce-list`, or `announce` alone when there is no list;
+    /// each tier is shuffled, as BEP 12 asks
+    pub fn from_metainfo(metainfo: &Metainfo, strategy: AnnounceStrategy) -> Self {
+        let mut trackers = TrackerList::from_metainfo(metainfo);
+        trackers.shuffle();
+        AnnounceScheduler::from_trackers(&trackers, strategy)
+    }
+
+    /// Trackers in the order of `trackers`, such as one saved from `trackers`
+    /// in an earlier session
+    pub fn from_trackers(trackers: &TrackerList, strategy: AnnounceStrategy) -> Self {
+        trackers
+            .tiers()
+            .iter()
+            .fold(AnnounceScheduler::new(strategy), |scheduler, tier| {
+                scheduler.tier(tier)
+            })
+    }
+
+    /// Adds a tier of trackers; URLs without a supported scheme are kept with
+    /// `TrackerStatus::Unsupported` so they still show up in `stats`
+    pub fn tier<S: AsRef<str>>(mut self, urls: impl IntoIterator<Item = S>) -> Self {
+        let index = self.tiers.len();
+        let (timeout, max_retries) = (self.timeout, self.max_retries);
+        let tier = urls
+            .into_iter()
+            .map(|url| {
+                let url = url.as_ref();
+                let mut stats = TrackerStats::new(url, index);
+                let client = TrackerClient::from_url(url)
+                    .map(|client| client.limits(timeout, max_retries))
+                    .inspect_err(|err| {
+                        stats.status = TrackerStatus::Unsupported;
+                        stats.last_error = Some(err.clone());
+                    })
+                    .ok();
+                Slot {
+                    client,
+                    stats,
+                    tracker_id: None,
+                    started: false,
+                    completed_pending: false,
+                }
+            })
+            .collect();
+        self.tiers.push(tier);
+        self
+    }
+
+    /// Wait after the first failure of a tracker, doubled with every further
+    /// failure up to `max`; 1 minute and 1 hour by default
+    pub fn retry_backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.retry_base = base;
+        self.retry_max = max;
+        self
+    }
+
+    /// Limits for each announce: `timeout` per request, resent at most
+    /// `max_retries` times over UDP; 15 seconds and 2 retries by default, so a
+    /// dead UDP tracker holds `poll` up for under 2 minutes
+    pub fn client_limits(mut self, timeout: Duration, max_retries: u32) -> Self {
+        self.timeout = timeout;
+        self.max_retries = max_retries;
+        for slot in self.tiers.iter_mut().flatten() {
+            slot.client = slot
+                .client
+                .take()
+                .map(|client| client.limits(timeout, max_retries));
+        }
+        self
+    }
+
+    /// Limit for each `stopped` announce sent by `shutdown`, which is never
+    /// resent; 5 seconds by default
+    pub fn stop_timeout(mut self, stop_timeout: Duration) -> Self {
+        self.stop_timeout = stop_timeout;
+        self
+    }
+
+    pub fn strategy(&self) -> AnnounceStrategy {
+        self.strategy
+    }
+
+    /// Every tracker in tier order; with `FirstSuccess` a tier's working
+    /// tracker comes first
+    pub fn stats(&self) -> impl Iterator<Item = &TrackerStats> {
+        self.tiers.iter().flatten().map(|slot| &slot.stats)
+    }
+
+    /// The trackers in their current order, which `FirstSuccess` changes as
+    /// trackers answer; unsupported URLs are included
+    pub fn trackers(&self) -> TrackerList {
+        TrackerList::new(
+            self.tiers
+                .iter()
+                .map(|tier| tier.iter().map(|slot| slot.stats.url.clone()).collect())
+                .collect(),
+        )
+    }
+
+    /// The client's address as last reported by a tracker's `external ip`,
+    /// for uses such as deriving a DHT node ID
+    pub fn external_ip(&self) -> Option<IpAddr> {
+        self.external_ip
+    }
+
+    /// When `poll` next has something to do; `None` once nothing is left to
+    /// announce to
+    pub fn next_announce(&self) -> Option<Instant> {
+        let mut next = self
+            .tiers
+            .iter()
+            .flatten()
+            .filter(|slot| slot.client.is_some() && slot.stats.status != TrackerStatus::Stopped)
+            .map(|slot| slot.stats.next_announce)
+            .peekable();
+        next.peek()?;
+        let next = match self.strategy {
+            AnnounceStrategy::AllTiers => next.min().flatten(),
+            AnnounceStrategy::FirstSuccess => self.next_cycle,
+        };
+        Some(next.unwrap_or_else(Instant::now))
+    }
+
+    /// Announces to the trackers due at `now` and returns the peers they sent.
+    /// `request.event` and `request.tracker_id` are set per tracker
+    pub fn poll(&mut self, now: Instant, request: &AnnounceRequest) -> Vec<Peer> {
+        let backoff = (self.retry_base, self.retry_max);
+        let mut peers = Vec::new();
+        match self.strategy {
+            AnnounceStrategy::AllTiers => {
+                for slot in self.tiers.iter_mut().flatten() {
+                    if slot.is_due(now)
+                        && let Ok(received) = announce(slot, now, request, backoff)
+                    {
+                        peers.extend(received);
+                    }
+                }
+            }
+            AnnounceStrategy::FirstSuccess => {
+                if self.next_cycle.is_some_and(|next| next > now) {
+                    return peers;
+                }
+                self.next_cycle = None;
+                'tiers: for tier in &mut self.tiers {
+                    for index in 0..tier.len() {
+                        // Trackers backing off from a failure are skipped
+                        if !tier[index].is_due(now) {
+                            continue;
+                        }
+                        if let Ok(received) = announce(&mut tier[index], now, request, backoff) {
+                            peers = received;
+                            self.next_cycle = tier[index].stats.next_announce;
+                            tier[..=index].rotate_right(1);
+                            break 'tiers;
+                        }
+                    }
+                }
+                // Every tracker failed: start over once the first one may retry
+                if self.next_cycle.is_none() {
+                    self.next_cycle = self
+                        .tiers
+                        .iter()
+                        .flatten()
+                        .filter_map(|slot| slot.client.as_ref().and(slot.stats.next_announce))
+                        .min();
+                }
+            }
+        }
+
+        // Responses from this poll hold the freshest address
+        let reported = self
+            .tiers
+            .iter()
+            .flatten()
+            .filter(|slot| slot.stats.last_announce == Some(now))
+            .find_map(|slot| slot.stats.external_ip);
+        if reported.is_some() {
+            self.external_ip = reported;
+        }
+
+        let mut seen = HashSet::new();
+        peers.retain(|peer| seen.insert(peer.addr));
+        peers
+    }
+
+    /// Queues `completed` for every tracker that was sent `started`, announcing
+    /// as soon as each tracker's minimum interval allows
+    pub fn complete(&mut self, now: Instant) {
+        for slot in self.tiers.iter_mut().flatten() {
+            if !slot.started || slot.stats.status == TrackerStatus::Stopped {
+                continue;
+            }
+            slot.completed_pending = true;
+            if slot.stats.status == TrackerStatus::Working {
+                let earliest = slot
+                    .stats
+                    .last_announce
+                    .zip(slot.stats.min_interval)
+                    .map(|(last, min_interval)| last + min_interval);
+                slot.stats.next_announce = earliest.filter(|earliest| *earliest > now);
+            }
+        }
+        if self.strategy == AnnounceStrategy::FirstSuccess {
+            let working = self
+                .stats()
+                .find(|stats| stats.status == TrackerStatus::Working);
+            if let Some(working) = working {
+                self.next_cycle = working.next_announce;
+            }
+        }
+    }
+
+    /// Sends `stopped` to every tracker that was sent `started`, once each and
+    /// within `stop_timeout`; the scheduler announces nothing afterwards
+    pub fn shutdown(&mut self, request: &AnnounceRequest) {
+        for slot in self.tiers.iter_mut().flatten() {
+            if slot.started
+                && let Some(client) = slot.client.take()
+            {
+                let mut client = client.limits(self.stop_timeout, 0);
+                let mut request = request.clone();
+                request.event = Some(AnnounceEvent::Stopped);
+                request.tracker_id = slot.tracker_id.clone();
+                slot.stats.last_error = client.announce(&request).err();
+                slot.client = Some(client);
+            }
+            slot.stats.status = TrackerStatus::Stopped;
+            slot.stats.next_announce = None;
+        }
+    }
+}
+
+/// Announces to one tracker and records the outcome in its stats; failures
+/// back off from `backoff.0`, doubling up to `backoff.1`
+fn announce(
+    slot: &mut Slot,
+    now: Instant,
+    request: &AnnounceRequest,
+    backoff: (Duration, Duration),
+) -> Result<Vec<Peer>, TrackerError> {
+    let Some(client) = &mut slot.client else {
+        return Err(TrackerError::InvalidUrl(slot.stats.url.clone()));
+    };
+
+    let mut request = request.clone();
+    request.event = if !slot.started {
+        Some(AnnounceEvent::Started)
+    } else if slot.completed_pending {
+        Some(AnnounceEvent::Completed)
+    } else {
+        None
+    };
+    request.tracker_id = slot.tracker_id.clone();
+
+    let stats = &mut slot.stats;
+    match client.announce(&request) {
+        Ok(response) => {
+            slot.started = true;
+            if request.event == Some(AnnounceEvent::Completed) {
+                slot.completed_pending = false;
+            }
+            if response.tracker_id.is_some() {
+                slot.tracker_id = response.tracker_id;
+            }
+            let interval = response
+                .interval
+                .max(response.min_interval.unwrap_or_default())
+                .max(MIN_ANNOUNCE_INTERVAL);
+            stats.status = TrackerStatus::Working;
+            stats.last_error = None;
+            stats.consecutive_failures = 0;
+            stats.last_announce = Some(now);
+            stats.next_announce = Some(now + interval);
+            stats.interval = Some(response.interval);
+            stats.min_interval = response.min_interval;
+            stats.seeders = response.complete;
+            stats.leechers = response.incomplete;
+            stats.peers_received = response.peers.len();
+            stats.warning_message = response.warning_message;
+            stats.external_ip = response.external_ip;
+            Ok(response.peers)
+        }
+        Err(err) => {
+            stats.consecutive_failures += 1;
+            let exponent = (stats.consecutive_failures - 1).min(16);
+            let backoff = backoff.0.saturating_mul(2u32.pow(exponent)).min(backoff.1);
+            stats.status = TrackerStatus::Failing;
+            stats.last_error = Some(err.clone());
+            stats.next_announce = Some(now + backoff);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stubs::{HttpStub, UdpStub};
+    use bencode::{bencode, dispatcher::bencode::encode};
+    use std::net::TcpListener;
+
+    fn ok(interval: i64, peer: u8) -> (u16, Vec<u8>) {
+        let body = bencode!({
+            "interval": interval,
+            "min interval": 60,
+            "complete": 4,
+            "incomplete": 2,
+            "tracker id": "session",
+            "peers": (vec![10u8, 0, 0, peer, 0x1a, 0xe1]),
+        });
+        (200, encode(&body).unwrap())
+    }
+
+    /// An HTTP URL nothing listens on
+    fn dead_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/announce", listener.local_addr().unwrap())
+    }
+
+    fn request() -> AnnounceRequest {
+        AnnounceRequest::new([1; 20], [2; 20], 6881)
+    }
+
+    fn all_stats(scheduler: &AnnounceScheduler) -> Vec<TrackerStats> {
+        scheduler.stats().cloned().collect()
+    }
+
+    #[test]
+    fn test_all_tiers_and_intervals() {
+        let first = HttpStub::serve(vec![ok(1800, 1), ok(1800, 1)]);
+        let second = HttpStub::serve(vec![ok(900, 2), ok(900, 2)]);
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers)
+            .tier([&first.url])
+            .tier([&second.url]);
+        let start = Instant::now();
+        assert!(scheduler.next_announce().unwrap() <= Instant::now());
+
+        let peers = scheduler.poll(start, &request());
+        assert_eq!(peers.len(), 2);
+        assert!(first.requests()[0].contains("&event=started"));
+        assert!(second.requests()[0].contains("&event=started"));
+
+        let stats = all_stats(&scheduler);
+        assert_eq!(stats[0].status, TrackerStatus::Working);
+        assert_eq!(stats[0].seeders, Some(4));
+        assert_eq!(stats[0].leechers, Some(2));
+        assert_eq!(stats[0].peers_received, 1);
+        assert_eq!(stats[1].tier, 1);
+        assert_eq!(
+            stats[1].next_announce,
+            Some(start + Duration::from_secs(900))
+        );
+        assert_eq!(
+            scheduler.next_announce(),
+            Some(start + Duration::from_secs(900))
+        );
+
+        // Nothing is due before the interval
+        assert!(
+            scheduler
+                .poll(start + Duration::from_secs(899), &request())
+                .is_empty()
+        );
+        assert_eq!(
+            scheduler
+                .poll(start + Duration::from_secs(900), &request())
+                .len(),
+            1
+        );
+        assert_eq!(first.requests().len(), 1);
+        let regular = &second.requests()[1];
+        assert!(!regular.contains("event="));
+        assert!(regular.contains("&trackerid=session"));
+    }
+
+    #[test]
+    fn test_failure_backoff() {
+        let stub = HttpStub::serve(vec![
+            (500, vec![]),
+            (200, b"d14:failure reason6:bannede".to_vec()),
+            (500, vec![]),
+            ok(1800, 1),
+        ]);
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers)
+            .tier([&stub.url])
+            .retry_backoff(Duration::from_secs(10), Duration::from_secs(25));
+        let mut now = Instant::now();
+
+        for (delay, error) in [
+            (10, TrackerError::Status(500)),
+            (20, TrackerError::Failure("banned".to_string())),
+            (25, TrackerError::Status(500)),
+        ] {
+            assert!(scheduler.poll(now, &request()).is_empty());
+            let stats = all_stats(&scheduler).remove(0);
+            assert_eq!(stats.status, TrackerStatus::Failing);
+            assert_eq!(stats.last_error, Some(error));
+            assert_eq!(stats.next_announce, Some(now + Duration::from_secs(delay)));
+
+            // Retrying early does nothing
+            scheduler.poll(now + Duration::from_secs(delay - 1), &request());
+            now += Duration::from_secs(delay);
+        }
+        assert_eq!(all_stats(&scheduler)[0].consecutive_failures, 3);
+
+        assert_eq!(scheduler.poll(now, &request()).len(), 1);
+        let stats = all_stats(&scheduler).remove(0);
+        assert_eq!(stats.status, TrackerStatus::Working);
+        assert_eq!(stats.last_error, None);
+        assert_eq!(stats.consecutive_failures, 0);
+        // started is sent until a tracker accepts it
+        assert!(
+            stub.requests()
+                .iter()
+                .all(|request| request.contains("event=started"))
+        );
+    }
+
+    #[test]
+    fn test_first_success() {
+        let dead = dead_url();
+        let good = HttpStub::serve(vec![ok(1800, 1), ok(1800, 1)]);
+        let spare = HttpStub::serve(vec![]);
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::FirstSuccess)
+            .tier([&dead, &good.url])
+            .tier([&spare.url]);
+        let start = Instant::now();
+
+        assert_eq!(scheduler.poll(start, &request()).len(), 1);
+        let urls: Vec<_> = scheduler.stats().map(|stats| stats.url.clone()).collect();
+        assert_eq!(
+            urls,
+            vec![good.url.clone(), dead.clone(), spare.url.clone()]
+        );
+        let stats = all_stats(&scheduler);
+        assert_eq!(stats[0].status, TrackerStatus::Working);
+        assert!(matches!(
+            stats[1].last_error,
+            Some(TrackerError::Transport(_))
+        ));
+        assert_eq!(stats[2].status, TrackerStatus::NotContacted);
+        assert_eq!(
+            scheduler.next_announce(),
+            Some(start + Duration::from_secs(1800))
+        );
+
+        // The working tracker is tried first from now on
+        assert!(
+            scheduler
+                .poll(start + Duration::from_secs(60), &request())
+                .is_empty()
+        );
+        scheduler.poll(start + Duration::from_secs(1800), &request());
+        assert_eq!(good.requests().len(), 2);
+        assert!(spare.requests().is_empty());
+        assert_eq!(all_stats(&scheduler)[1].consecutive_failures, 1);
+        assert_eq!(
+            scheduler.trackers().tiers(),
+            [vec![good.url.clone(), dead], vec![spare.url.clone()]]
+        );
+    }
+
+    #[test]
+    fn test_from_trackers_keeps_order() {
+        let trackers = TrackerList::new(vec![
+            vec![
+                "http://b/announce".to_string(),
+                "udp://a:1/announce".to_string(),
+            ],
+            vec!["wss://c/announce".to_string()],
+        ]);
+        let scheduler = AnnounceScheduler::from_trackers(&trackers, AnnounceStrategy::FirstSuccess);
+        assert_eq!(scheduler.trackers(), trackers);
+        assert_eq!(all_stats(&scheduler)[2].tier, 1);
+    }
+
+    #[test]
+    fn test_client_limits() {
+        let dead = UdpStub::serve("127.0.0.1:0", |_| Vec::new());
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers)
+            .tier([format!("udp://{}/announce", dead.addr)])
+            .client_limits(Duration::from_millis(20), 1);
+
+        let start = Instant::now();
+        scheduler.poll(start, &request());
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(dead.packets().len(), 2);
+        assert_eq!(
+            all_stats(&scheduler)[0].last_error,
+            Some(TrackerError::Timeout)
+        );
+    }
+
+    #[test]
+    fn test_first_success_falls_through_tiers() {
+        let spare = HttpStub::serve(vec![ok(1800, 3)]);
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::FirstSuccess)
+            .tier([dead_url()])
+            .tier([
+                "wss://tracker.example/announce".to_string(),
+                spare.url.clone(),
+            ]);
+
+        let peers = scheduler.poll(Instant::now(), &request());
+        assert_eq!(peers[0].addr, "10.0.0.3:6881".parse().unwrap());
+        let stats = all_stats(&scheduler);
+        assert_eq!(stats[0].status, TrackerStatus::Failing);
+        assert_eq!(stats[1].url, spare.url);
+        assert_eq!(stats[2].status, TrackerStatus::Unsupported);
+    }
+
+    #[test]
+    fn test_completed_respects_min_interval() {
+        let stub = HttpStub::serve(vec![ok(1800, 1), ok(1800, 1), ok(1800, 1)]);
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers).tier([&stub.url]);
+        let start = Instant::now();
+        scheduler.poll(start, &request());
+
+        scheduler.complete(start + Duration::from_secs(10));
+        assert_eq!(
+            scheduler.next_announce(),
+            Some(start + Duration::from_secs(60))
+        );
+        scheduler.poll(start + Duration::from_secs(30), &request());
+        assert_eq!(stub.requests().len(), 1);
+
+        scheduler.poll(start + Duration::from_secs(60), &request());
+        scheduler.poll(start + Duration::from_secs(1860), &request());
+        let requests = stub.requests();
+        assert!(requests[1].contains("&event=completed"));
+        assert!(!requests[2].contains("event="));
+    }
+
+    #[test]
+    fn test_interval_floor() {
+        let stub = HttpStub::serve(vec![(200, b"d8:intervali0e5:peers0:e".to_vec())]);
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers).tier([&stub.url]);
+        let start = Instant::now();
+        scheduler.poll(start, &request());
+        assert_eq!(
+            scheduler.next_announce(),
+            Some(start + MIN_ANNOUNCE_INTERVAL)
+        );
+
+        // A huge backoff base saturates instead of overflowing
+        let dead = dead_url();
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers)
+            .tier([&dead])
+            .retry_backoff(Duration::MAX, Duration::from_secs(3600));
+        let retry = start + Duration::from_secs(3600);
+        scheduler.poll(start, &request());
+        scheduler.poll(retry, &request());
+        let stats = all_stats(&scheduler).remove(0);
+        assert_eq!(stats.consecutive_failures, 2);
+        assert_eq!(stats.next_announce, Some(retry + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_external_ip() {
+        let stub = HttpStub::serve(vec![(
+            200,
+            b"d8:intervali60e11:external ip4:\xcb\x00\x71\x09e".to_vec(),
+        )]);
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers).tier([&stub.url]);
+        assert_eq!(scheduler.external_ip(), None);
+
+        scheduler.poll(Instant::now(), &request());
+        let external_ip = Some("203.0.113.9".parse().unwrap());
+        assert_eq!(scheduler.external_ip(), external_ip);
+        assert_eq!(all_stats(&scheduler)[0].external_ip, external_ip);
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let started = HttpStub::serve(vec![ok(1800, 1), ok(1800, 1)]);
+        let failing = dead_url();
+        let mut scheduler =
+            AnnounceScheduler::new(AnnounceStrategy::AllTiers).tier([&started.url, &failing]);
+        scheduler.poll(Instant::now(), &request());
+
+        scheduler.shutdown(&request());
+        assert!(started.requests()[1].contains("&event=stopped"));
+        assert!(
+            all_stats(&scheduler)
+                .iter()
+                .all(|stats| stats.status == TrackerStatus::Stopped)
+        );
+        assert_eq!(scheduler.next_announce(), None);
+        assert!(scheduler.poll(Instant::now(), &request()).is_empty());
+    }
+
+    #[test]
+    fn test_stopped_is_sent_once() {
+        // Answers the connect and the first announce, then goes silent
+        let mut announces = 0;
+        let stub = UdpStub::serve("127.0.0.1:0", move |packet| {
+            let mut response = packet[8..16].to_vec();
+            match u32::from_be_bytes(packet[8..12].try_into().unwrap()) {
+                0 => response.extend_from_slice(&42u64.to_be_bytes()),
+                _ if announces == 0 => {
+                    announces += 1;
+                    response.extend_from_slice(&[0, 0, 0x07, 0x08, 0, 0, 0, 0, 0, 0, 0, 0]);
+                }
+                _ => return Vec::new(),
+            }
+            vec![response]
+        });
+        let mut scheduler = AnnounceScheduler::new(AnnounceStrategy::AllTiers)
+            .tier([format!("udp://{}/announce", stub.addr)])
+            .stop_timeout(Duration::from_millis(50));
+        scheduler.poll(Instant::now(), &request());
+        assert_eq!(all_stats(&scheduler)[0].status, TrackerStatus::Working);
+
+        let start = Instant::now();
+        scheduler.shutdown(&request());
+        assert!(start.elapsed() < Duration::from_secs(1));
+        let packets = stub.packets();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[2][80..84], 3u32.to_be_bytes());
+        assert_eq!(
+            all_stats(&scheduler)[0].last_error,
+            Some(TrackerError::Timeout)
+        );
+    }
+}
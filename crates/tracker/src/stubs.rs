@@ -0,0 +1,92 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// An in-process HTTP server answering requests with canned responses, in order
+pub struct HttpStub {
+    pub url: String,
+    /// Request targets, such as `/announce?info_hash=...`, in arrival order
+    pub requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl HttpStub {
+    pub fn serve(responses: Vec<(u16, Vec<u8>)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/announce", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let target = request_line.split(' ').nth(1).unwrap_or_default();
+                recorded.lock().unwrap().push(target.to_string());
+
+                // Skip the headers; announces have no body
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                let mut stream = reader.into_inner();
+                let head = format!(
+                    "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(head.as_bytes()).unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+
+        HttpStub { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// An in-process UDP server; `handler` maps each received packet to the
+/// packets sent back, which may be none to simulate loss
+pub struct UdpStub {
+    pub addr: std::net::SocketAddr,
+    /// Received packets in arrival order
+    pub packets: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl UdpStub {
+    pub fn serve<F>(bind: &str, mut handler: F) -> Self
+    where
+        F: FnMut(&[u8]) -> Vec<Vec<u8>> + Send + 'static,
+    {
+        let socket = std::net::UdpSocket::bind(bind).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let packets = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&packets);
+        thread::spawn(move || {
+            let mut buffer = [0; 2048];
+            while let Ok((len, from)) = socket.recv_from(&mut buffer) {
+                let packet = &buffer[..len];
+                recorded.lock().unwrap().push(packet.to_vec());
+                for response in handler(packet) {
+                    socket.send_to(&response, from).unwrap();
+                }
+            }
+        });
+
+        UdpStub { addr, packets }
+    }
+
+    pub fn packets(&self) -> Vec<Vec<u8>> {
+        self.packets.lock().unwrap().clone()
+    }
+}
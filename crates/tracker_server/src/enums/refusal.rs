@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Why the tracker turned a request down; sent to the client as its
+/// `failure reason`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Refusal {
+    /// The info-hash is not whitelisted
+    UnknownTorrent,
+    /// The passkey is missing or not registered
+    InvalidPasskey,
+    /// The tracker already holds as many swarms as it allows
+    TooManyTorrents,
+    /// The address already has as many peers in the swarm as it may
+    TooManyPeers,
+    MissingParameter(&'static str),
+    InvalidParameter(&'static str),
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::UnknownTorrent => write!(f, "unregistered torrent"),
+            Refusal::InvalidPasskey => write!(f, "invalid passkey"),
+            Refusal::TooManyTorrents => write!(f, "too many torrents tracked"),
+            Refusal::TooManyPeers => write!(f, "too many peers from this address"),
+            Refusal::MissingParameter(name) => write!(f, "missing parameter '{name}'"),
+            Refusal::InvalidParameter(name) => write!(f, "invalid parameter '{name}'"),
+        }
+    }
+}
+
+impl std::error::Error for Refusal {}
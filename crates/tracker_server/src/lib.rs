@@ -0,0 +1,4 @@
+pub mod enums;
+pub mod models;
+pub mod servers;
+pub mod swarms;
@@ -0,0 +1,134 @@
+use metainfo::encoding::hex::decode_hex;
+use std::{env, fs, path::PathBuf, process, sync::Arc, thread, time::Duration};
+use tracker_server::{
+    servers::{http::HttpServer, udp::UdpServer},
+    swarms::registry::SwarmRegistry,
+};
+
+const USAGE: &str = "\
+Usage: tracker-server [OPTIONS]
+
+Options:
+  --http <ADDR>        Serve HTTP on ADDR (default 0.0.0.0:6969 when no server is given)
+  --udp <ADDR>         Serve UDP on ADDR (default 0.0.0.0:6969 when no server is given)
+  --whitelist <FILE>   Only serve the hex info-hashes listed in FILE, one per line
+  --passkeys <FILE>    Require one of the passkeys listed in FILE, one per line
+  --stats <FILE>       Load swarm stats from FILE on start and save them every minute
+  --interval <SECS>    Announce interval sent to clients, at least 60 (default 1800)";
+
+/// Housekeeping period: peer expiry and stats saving
+const TICK: Duration = Duration::from_secs(60);
+
+/// Minimum announce interval sent to clients, and the lowest `--interval`
+const MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+fn main() {
+    if let Err(message) = run() {
+        eprintln!("error: {message}\n\n{USAGE}");
+        process::exit(2);
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut http = None;
+    let mut udp = None;
+    let mut stats = None;
+    let mut registry = SwarmRegistry::new().min_interval(MIN_INTERVAL);
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--http" => http = Some(value()?),
+            "--udp" => udp = Some(value()?),
+            "--stats" => stats = Some(PathBuf::from(value()?)),
+            "--whitelist" => {
+                let info_hashes = read_lines(&value()?)?
+                    .iter()
+                    .map(|line| {
+                        decode_hex(line)
+                            .ok()
+                            .and_then(|bytes| <[u8; 20]>::try_from(bytes).ok())
+                            .ok_or(format!("'{line}' is not a hex info-hash"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                registry = registry.whitelist(info_hashes);
+            }
+            "--passkeys" => registry = registry.passkeys(read_lines(&value()?)?),
+            "--interval" => {
+                let secs = value()?;
+                let interval = secs
+                    .parse()
+                    .map(Duration::from_secs)
+                    .map_err(|_| format!("'{secs}' is not a number of seconds"))?;
+                if interval < MIN_INTERVAL {
+                    return Err(format!(
+                        "--interval must be at least {} seconds",
+                        MIN_INTERVAL.as_secs()
+                    ));
+                }
+                registry = registry.interval(interval);
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => return Err(format!("unknown option '{arg}'")),
+        }
+    }
+    if http.is_none() && udp.is_none() {
+        http = Some("0.0.0.0:6969".to_string());
+        udp = Some("0.0.0.0:6969".to_string());
+    }
+
+    if let Some(path) = &stats
+        && path.exists()
+    {
+        registry
+            .load_stats(path)
+            .map_err(|err| format!("cannot load {}: {err}", path.display()))?;
+    }
+    let registry = Arc::new(registry);
+
+    if let Some(addr) = http {
+        let server = HttpServer::bind(&addr, Arc::clone(&registry))
+            .map_err(|err| format!("cannot bind HTTP to {addr}: {err}"))?;
+        println!("HTTP tracker on {}", server.local_addr().unwrap());
+        thread::spawn(move || {
+            if let Err(err) = server.run() {
+                eprintln!("HTTP server stopped: {err}");
+            }
+        });
+    }
+    if let Some(addr) = udp {
+        let server = UdpServer::bind(&addr, Arc::clone(&registry))
+            .map_err(|err| format!("cannot bind UDP to {addr}: {err}"))?;
+        println!("UDP tracker on {}", server.local_addr().unwrap());
+        thread::spawn(move || {
+            if let Err(err) = server.run() {
+                eprintln!("UDP server stopped: {err}");
+            }
+        });
+    }
+
+    loop {
+        thread::sleep(TICK);
+        registry.expire_peers();
+        if let Some(path) = &stats
+            && let Err(err) = registry.save_stats(path)
+        {
+            eprintln!("cannot save {}: {err}", path.display());
+        }
+    }
+}
+
+/// Non-empty, trimmed lines of a file
+fn read_lines(path: &str) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
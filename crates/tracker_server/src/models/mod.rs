@@ -0,0 +1 @@
+pub mod peer_entry;
@@ -0,0 +1,16 @@
+use std::{net::SocketAddr, time::Instant};
+
+/// A peer as the tracker remembers it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PeerEntry {
+    pub addr: SocketAddr,
+    /// Bytes the peer still misses; 0 for seeders
+    pub left: u64,
+    pub last_seen: Instant,
+}
+
+impl PeerEntry {
+    pub fn is_seeder(&self) -> bool {
+        self.left == 0
+    }
+}
@@ -0,0 +1,571 @@
+use crate::{enums::refusal::Refusal, servers::sockets::recover, swarms::registry::SwarmRegistry};
+use bencode::{bencode, dispatcher::bencode::encode, enums::bencode::BencodeValue};
+use metainfo::encoding::percent::decode_percent_bytes;
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use tracker::{
+    enums::announce_event::AnnounceEvent,
+    models::{announce_request::AnnounceRequest, announce_response::AnnounceResponse},
+};
+
+/// Most bytes accepted for the request line and headers together
+const MAX_HEAD_LENGTH: u64 = 16 * 1024;
+
+/// Serves `/announce` and `/scrape` over HTTP, or `/<passkey>/announce` and
+/// `/<passkey>/scrape` when the registry requires passkeys
+#[derive(Debug)]
+pub struct HttpServer {
+    listener: TcpListener,
+    registry: Arc<SwarmRegistry>,
+    request_timeout: Duration,
+    max_connections: usize,
+}
+
+impl HttpServer {
+    pub fn bind(addr: impl ToSocketAddrs, registry: Arc<SwarmRegistry>) -> io::Result<Self> {
+        Ok(HttpServer {
+            listener: TcpListener::bind(addr)?,
+            registry,
+            request_timeout: Duration::from_secs(10),
+            max_connections: 256,
+        })
+    }
+
+    /// Time a client has to send its whole request, 10 seconds by default
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    /// Connections served at once, 256 by default; further connections wait
+    /// in the listen backlog until one finishes
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections.max(1);
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections forever, one thread per connection; failed accepts
+    /// are skipped
+    pub fn run(self) -> io::Result<()> {
+        let slots = Arc::new(Slots::new(self.max_connections));
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    recover(&err);
+                    continue;
+                }
+            };
+            let slot = Slots::acquire(&slots);
+            let registry = Arc::clone(&self.registry);
+            let deadline = Instant::now() + self.request_timeout;
+            thread::spawn(move || {
+                // A client that goes away mid-request is not the server's problem
+                let _ = serve_connection(&registry, stream, deadline);
+                drop(slot);
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Counts the connections being served
+#[derive(Debug)]
+struct Slots {
+    active: Mutex<usize>,
+    freed: Condvar,
+    max: usize,
+}
+
+/// One connection's place among the `Slots`, given back on drop
+struct Slot(Arc<Slots>);
+
+impl Slots {
+    fn new(max: usize) -> Self {
+        Slots {
+            active: Mutex::new(0),
+            freed: Condvar::new(),
+            max,
+        }
+    }
+
+    /// Waits until fewer than `max` connections are being served
+    fn acquire(slots: &Arc<Slots>) -> Slot {
+        let mut active = slots.active.lock().unwrap();
+        while *active >= slots.max {
+            active = slots.freed.wait(active).unwrap();
+        }
+        *active += 1;
+        Slot(Arc::clone(slots))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        *self.0.active.lock().unwrap() -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// A stream whose reads fail once `deadline` has passed, however slowly the
+/// bytes trickle in
+struct DeadlineStream {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+fn serve_connection(
+    registry: &SwarmRegistry,
+    stream: TcpStream,
+    deadline: Instant,
+) -> io::Result<()> {
+    let ip = stream.peer_addr()?.ip().to_canonical();
+    let mut reader = BufReader::new(DeadlineStream { stream, deadline });
+
+    let mut head = (&mut reader).take(MAX_HEAD_LENGTH);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match head.read_line(&mut line)? {
+            // The head ended early or outgrew its limit
+            0 => return Err(io::ErrorKind::InvalidData.into()),
+            1 | 2 if line.trim_end().is_empty() => break,
+            _ => {}
+        }
+    }
+
+    let response = match request_line.split(' ').collect::<Vec<_>>()[..] {
+        ["GET", target, _] => respond(registry, target, ip),
+        _ => None,
+    };
+    let (status, body) = match response {
+        Some(body) => ("200 OK", body),
+        None => ("404 Not Found", b"not found".to_vec()),
+    };
+
+    let mut stream = reader.into_inner().stream;
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&body)
+}
+
+/// The bencoded body answering a request target such as `/announce?...` from
+/// `ip`; `None` for paths the tracker does not serve
+pub fn respond(registry: &SwarmRegistry, target: &str, ip: IpAddr) -> Option<Vec<u8>> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+    let (passkey, endpoint) = match segments[..] {
+        [endpoint] => (None, endpoint),
+        [passkey, endpoint] => (Some(passkey), endpoint),
+        _ => return None,
+    };
+
+    let body = match endpoint {
+        "announce" => announce(registry, passkey, query, ip),
+        "scrape" => scrape(registry, passkey, query),
+        _ => return None,
+    };
+    let body = body.unwrap_or_else(|refusal| bencode!({ "failure reason": (refusal.to_string()) }));
+    Some(encode(&body).expect("tracker responses always encode"))
+}
+
+fn announce(
+    registry: &SwarmRegistry,
+    passkey: Option<&str>,
+    query: &str,
+    ip: IpAddr,
+) -> Result<BencodeValue, Refusal> {
+    let params = parse_query(query)?;
+    let param = |name: &'static str| {
+        params
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_slice())
+    };
+    let number = |name: &'static str| -> Result<Option<u64>, Refusal> {
+        param(name)
+            .map(|value| {
+                std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or(Refusal::InvalidParameter(name))
+            })
+            .transpose()
+    };
+    let id = |name: &'static str| -> Result<[u8; 20], Refusal> {
+        let value = param(name).ok_or(Refusal::MissingParameter(name))?;
+        value
+            .try_into()
+            .map_err(|_| Refusal::InvalidParameter(name))
+    };
+
+    let port = number("port")?.ok_or(Refusal::MissingParameter("port"))?;
+    let mut request = AnnounceRequest::new(
+        id("info_hash")?,
+        id("peer_id")?,
+        u16::try_from(port).map_err(|_| Refusal::InvalidParameter("port"))?,
+    );
+    request.uploaded = number("uploaded")?.unwrap_or(0);
+    request.downloaded = number("downloaded")?.unwrap_or(0);
+    request.left = number("left")?.unwrap_or(0);
+    request.compact = param("compact") != Some(b"0");
+    request.no_peer_id = param("no_peer_id") == Some(b"1");
+    request.numwant = number("numwant")?.map(|n| n.min(u32::MAX.into()) as u32);
+    request.event = match param("event") {
+        None | Some(b"") | Some(b"empty") => None,
+        Some(b"started") => Some(AnnounceEvent::Started),
+        Some(b"completed") => Some(AnnounceEvent::Completed),
+        Some(b"stopped") => Some(AnnounceEvent::Stopped),
+        Some(_) => return Err(Refusal::InvalidParameter("event")),
+    };
+
+    let response = registry.announce(passkey, &request, ip)?;
+    Ok(encode_announce(&response, &request))
+}
+
+fn encode_announce(response: &AnnounceResponse, request: &AnnounceRequest) -> BencodeValue {
+    let mut announce = bencode!({
+        "interval": (response.interval.as_secs() as i64),
+        "complete": (i64::from(response.complete.unwrap_or(0))),
+        "incomplete": (i64::from(response.incomplete.unwrap_or(0))),
+    });
+    let BencodeValue::Dict(dict) = &mut announce else {
+        unreachable!("bencode! builds a dictionary")
+    };
+    if let Some(min_interval) = response.min_interval {
+        dict.insert(
+            b"min interval".to_vec(),
+            BencodeValue::from(min_interval.as_secs() as i64),
+        );
+    }
+
+    if let Some(ip) = response.external_ip {
+        let ip = match ip {
+            IpAddr::V4(ip) => ip.octets().to_vec(),
+            IpAddr::V6(ip) => ip.octets().to_vec(),
+        };
+        dict.insert(b"external ip".to_vec(), BencodeValue::Str(ip));
+    }
+
+    if request.compact {
+        let (mut peers, mut peers6) = (Vec::new(), Vec::new());
+        for peer in &response.peers {
+            match peer.addr {
+                SocketAddr::V4(addr) => {
+                    peers.extend_from_slice(&addr.ip().octets());
+                    peers.extend_from_slice(&addr.port().to_be_bytes());
+                }
+                SocketAddr::V6(addr) => {
+                    peers6.extend_from_slice(&addr.ip().octets());
+                    peers6.extend_from_slice(&addr.port().to_be_bytes());
+                }
+            }
+        }
+        dict.insert(b"peers".to_vec(), BencodeValue::Str(peers));
+        if !peers6.is_empty() {
+            dict.insert(b"peers6".to_vec(), BencodeValue::Str(peers6));
+        }
+    } else {
+        let peers = response
+            .peers
+            .iter()
+            .map(|peer| {
+                let mut entry = bencode!({
+                    "ip": (peer.addr.ip().to_string()),
+                    "port": (i64::from(peer.addr.port())),
+                });
+                if let (BencodeValue::Dict(entry), Some(peer_id)) = (&mut entry, peer.peer_id)
+                    && !request.no_peer_id
+                {
+                    entry.insert(b"peer id".to_vec(), BencodeValue::Str(peer_id.to_vec()));
+                }
+                entry
+            })
+            .collect();
+        dict.insert(b"peers".to_vec(), BencodeValue::List(peers));
+    }
+    announce
+}
+
+fn scrape(
+    registry: &SwarmRegistry,
+    passkey: Option<&str>,
+    query: &str,
+) -> Result<BencodeValue, Refusal> {
+    let info_hashes = parse_query(query)?
+        .into_iter()
+        .filter(|(key, _)| *key == "info_hash")
+        .map(|(_, value)| {
+            <[u8; 20]>::try_from(value).map_err(|_| Refusal::InvalidParameter("info_hash"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // Scraping every torrent at once is not supported
+    if info_hashes.is_empty() {
+        return Err(Refusal::MissingParameter("info_hash"));
+    }
+
+    let files: BTreeMap<Vec<u8>, BencodeValue> = registry
+        .scrape(passkey, &info_hashes)?
+        .into_iter()
+        .map(|(info_hash, stats)| {
+            let stats = bencode!({
+                "complete": (i64::from(stats.complete)),
+                "incomplete": (i64::from(stats.incomplete)),
+                "downloaded": (i64::from(stats.downloaded)),
+            });
+            (info_hash.to_vec(), stats)
+        })
+        .collect();
+    Ok(bencode!({ "files": files }))
+}
+
+/// Splits a query string into its percent-decoded parameters, keeping repeats
+fn parse_query(query: &str) -> Result<Vec<(&str, Vec<u8>)>, Refusal> {
+    query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            let value =
+                decode_percent_bytes(value).map_err(|_| Refusal::InvalidParameter("query"))?;
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracker::{clients::http::HttpTracker, enums::tracker_error::TrackerError};
+
+    fn serve(registry: SwarmRegistry) -> String {
+        let server = HttpServer::bind("127.0.0.1:0", Arc::new(registry)).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        format!("http://{addr}")
+    }
+
+    fn request(peer: u8, left: u64) -> AnnounceRequest {
+        let mut request = AnnounceRequest::new([0xab; 20], [peer; 20], 6881 + u16::from(peer));
+        request.left = left;
+        request
+    }
+
+    #[test]
+    fn test_announce_and_scrape_with_client() {
+        let url = serve(SwarmRegistry::new().interval(Duration::from_secs(120)));
+        let tracker = HttpTracker::new(format!("{url}/announce"));
+
+        let response = tracker.announce(&request(1, 10)).unwrap();
+        assert_eq!(response.interval, Duration::from_secs(120));
+        assert!(response.peers.is_empty());
+
+        let response = tracker.announce(&request(2, 0)).unwrap();
+        assert_eq!(response.complete, Some(1));
+        assert_eq!(response.incomplete, Some(1));
+        let addrs: Vec<_> = response.peers.iter().map(|peer| peer.addr).collect();
+        assert_eq!(addrs, vec!["127.0.0.1:6882".parse().unwrap()]);
+
+        // Non-compact responses carry peer IDs
+        let mut request = request(3, 10);
+        request.compact = false;
+        let response = tracker.announce(&request).unwrap();
+        assert_eq!(response.peers.len(), 2);
+        assert!(response.peers.iter().all(|peer| peer.peer_id.is_some()));
+        assert_eq!(response.external_ip, Some("127.0.0.1".parse().unwrap()));
+
+        request.no_peer_id = true;
+        let response = tracker.announce(&request).unwrap();
+        assert!(response.peers.iter().all(|peer| peer.peer_id.is_none()));
+
+        let stats = tracker.scrape(&[[0xab; 20], [0xcd; 20]]).unwrap();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[&[0xab; 20]].complete, 1);
+        assert_eq!(stats[&[0xab; 20]].incomplete, 2);
+    }
+
+    #[test]
+    fn test_passkeys_and_whitelist() {
+        let url = serve(
+            SwarmRegistry::new()
+                .passkeys(["k1".to_string()])
+                .whitelist([[0xab; 20]]),
+        );
+
+        let tracker = HttpTracker::new(format!("{url}/k1/announce"));
+        assert!(tracker.announce(&request(1, 0)).is_ok());
+        assert_eq!(tracker.scrape(&[[0xab; 20]]).unwrap().len(), 1);
+
+        let mut unknown = request(1, 0);
+        unknown.info_hash = [0; 20];
+        assert_eq!(
+            tracker.announce(&unknown),
+            Err(TrackerError::Failure("unregistered torrent".to_string()))
+        );
+
+        for url in [format!("{url}/announce"), format!("{url}/k2/announce")] {
+            assert_eq!(
+                HttpTracker::new(url).announce(&request(1, 0)),
+                Err(TrackerError::Failure("invalid passkey".to_string()))
+            );
+        }
+        assert_eq!(
+            HttpTracker::new(format!("{url}/a/b/announce")).announce(&request(1, 0)),
+            Err(TrackerError::Status(404))
+        );
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let registry = SwarmRegistry::new();
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let respond = |target: &str| {
+            respond(&registry, target, ip).map(|body| String::from_utf8(body).unwrap())
+        };
+
+        assert_eq!(respond("/index.html"), None);
+        assert_eq!(
+            respond("/announce?peer_id=aaaaaaaaaaaaaaaaaaaa&port=1"),
+            Some("d14:failure reason29:missing parameter 'info_hash'e".to_string())
+        );
+        let valid = "info_hash=aaaaaaaaaaaaaaaaaaaa&peer_id=bbbbbbbbbbbbbbbbbbbb";
+        for (query, parameter) in [
+            (
+                "info_hash=short&peer_id=bbbbbbbbbbbbbbbbbbbb&port=1".to_string(),
+                "info_hash",
+            ),
+            (format!("{valid}&port=65536"), "port"),
+            (format!("{valid}&port=0"), "port"),
+            (format!("{valid}&port=1&left=-1"), "left"),
+            (format!("{valid}&port=1&event=paused"), "event"),
+            (format!("{valid}&port=1&key=%zz"), "query"),
+        ] {
+            let body = respond(&format!("/announce?{query}")).unwrap();
+            assert!(
+                body.contains(&format!("invalid parameter '{parameter}'")),
+                "{query}: {body}"
+            );
+        }
+        assert_eq!(
+            respond("/scrape"),
+            Some("d14:failure reason29:missing parameter 'info_hash'e".to_string())
+        );
+    }
+
+    #[test]
+    fn test_slow_and_oversized_requests_are_dropped() {
+        let server = HttpServer::bind("127.0.0.1:0", Arc::new(SwarmRegistry::new()))
+            .unwrap()
+            .request_timeout(Duration::from_millis(300));
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        // Trickling headers does not extend the deadline
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        thread::spawn(move || {
+            let _ = writer.write_all(b"GET /scrape HTTP/1.1\r\n");
+            while writer.write_all(b"X: y\r\n").is_ok() {
+                thread::sleep(Duration::from_millis(50));
+            }
+        });
+        let start = Instant::now();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response);
+        assert!(response.is_empty());
+        assert!(start.elapsed() < Duration::from_secs(3));
+
+        // Headers past the size limit get no response
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let mut head = b"GET /scrape HTTP/1.1\r\n".to_vec();
+        while head.len() as u64 <= MAX_HEAD_LENGTH {
+            head.extend_from_slice(b"X-Filler: 0123456789abcdef\r\n");
+        }
+        head.extend_from_slice(b"\r\n");
+        let _ = stream.write_all(&head);
+        response.clear();
+        let _ = stream.read_to_end(&mut response);
+        assert!(response.is_empty());
+    }
+
+    #[test]
+    fn test_max_connections() {
+        let server = HttpServer::bind("127.0.0.1:0", Arc::new(SwarmRegistry::new()))
+            .unwrap()
+            .max_connections(1);
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        // An idle connection holds the only slot until it is closed
+        let idle = TcpStream::connect(addr).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET /scrape HTTP/1.1\r\n\r\n").unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(300)))
+            .unwrap();
+        assert!(stream.read(&mut [0]).is_err());
+
+        drop(idle);
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[test]
+    fn test_compact_peers6() {
+        let registry = SwarmRegistry::new();
+        let announce = |peer: u8, ip: IpAddr| {
+            let query = format!(
+                "info_hash={}&peer_id={}&port=6881&left=1",
+                "%01".repeat(20),
+                format!("%{peer:02X}").repeat(20)
+            );
+            respond(&registry, &format!("/announce?{query}"), ip).unwrap()
+        };
+        announce(1, "2001:db8::1".parse().unwrap());
+        announce(2, "10.0.0.2".parse().unwrap());
+
+        let body = announce(3, "10.0.0.3".parse().unwrap());
+        let response = tracker::parsers::announce_response::parse_announce_response(&body).unwrap();
+        let mut addrs: Vec<_> = response
+            .peers
+            .iter()
+            .map(|peer| peer.addr.to_string())
+            .collect();
+        addrs.sort();
+        assert_eq!(addrs, ["10.0.0.2:6881", "[2001:db8::1]:6881"]);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod http;
+mod sockets;
+pub mod udp;
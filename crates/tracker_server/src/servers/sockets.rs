@@ -0,0 +1,25 @@
+use std::{
+    io::{self, ErrorKind},
+    thread,
+    time::Duration,
+};
+
+/// Wait after a socket error that is not one client's doing, such as running
+/// out of file descriptors, so the server does not spin on it
+const ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Gets a server past a failed accept or receive: errors caused by a single
+/// client are skipped at once, anything else after a short wait
+pub(crate) fn recover(err: &io::Error) {
+    let transient = matches!(
+        err.kind(),
+        ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+    );
+    if !transient {
+        thread::sleep(ERROR_BACKOFF);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{enums::refusal::Refusal, servers::sockets::recover, swarms::registry::SwarmRegistry};
+use std::{
+    hash::{BuildHasher, RandomState},
+    io,
+    net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracker::{
+    clients::udp::{
+        ACTION_ANNOUNCE, ACTION_CONNECT, ACTION_ERROR, ACTION_SCRAPE, MAX_SCRAPE_HASHES,
+        OPTION_END, OPTION_NOP, OPTION_URL_DATA, PROTOCOL_ID,
+    },
+    enums::announce_event::AnnounceEvent,
+    models::announce_request::AnnounceRequest,
+};
+
+/// Serves BEP 15 announces and scrapes. Announces carry their passkey in the
+/// BEP 41 URL path, `/<passkey>/announce`; scrapes have no room for one, so a
+/// registry that requires passkeys refuses them
+#[derive(Debug)]
+pub struct UdpServer {
+    socket: UdpSocket,
+    registry: Arc<SwarmRegistry>,
+    /// Keys connection IDs, so they cannot be forged without a connect
+    secret: RandomState,
+}
+
+impl UdpServer {
+    pub fn bind(addr: impl ToSocketAddrs, registry: Arc<SwarmRegistry>) -> io::Result<Self> {
+        Ok(UdpServer {
+            socket: UdpSocket::bind(addr)?,
+            registry,
+            secret: RandomState::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Answers packets forever; failed receives, such as ICMP errors left by
+    /// an earlier reply, are skipped
+    pub fn run(self) -> io::Result<()> {
+        let mut buffer = [0; 2048];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(err) => {
+                    recover(&err);
+                    continue;
+                }
+            };
+            if let Some(response) = self.handle(&buffer[..len], from) {
+                // Replies are best effort; the client retransmits
+                let _ = self.socket.send_to(&response, from);
+            }
+        }
+    }
+
+    /// The response to one packet from `from`; `None` for packets that are not
+    /// worth an answer
+    pub fn handle(&self, packet: &[u8], from: SocketAddr) -> Option<Vec<u8>> {
+        if packet.len() < 16 {
+            return None;
+        }
+        let connection_id = u64::from_be_bytes(packet[..8].try_into().unwrap());
+        let action = read_u32(packet, 8);
+        let transaction_id = read_u32(packet, 12);
+        let ip = from.ip().to_canonical();
+
+        if action == ACTION_CONNECT {
+            if connection_id != PROTOCOL_ID {
+                return None;
+            }
+            let mut response = header(ACTION_CONNECT, transaction_id);
+            response.extend_from_slice(&self.connection_id(ip, 0).to_be_bytes());
+            return Some(response);
+        }
+
+        if !self.is_valid_connection(connection_id, ip) {
+            return Some(error(transaction_id, "invalid connection id"));
+        }
+        let result = match action {
+            ACTION_ANNOUNCE => self.announce(packet, ip),
+            ACTION_SCRAPE => self.scrape(packet),
+            _ => Err(Refusal::InvalidParameter("action")),
+        };
+        Some(match result {
+            Ok(body) => {
+                let mut response = header(action, transaction_id);
+                response.extend_from_slice(&body);
+                response
+            }
+            Err(refusal) => error(transaction_id, &refusal.to_string()),
+        })
+    }
+
+    fn announce(&self, packet: &[u8], ip: IpAddr) -> Result<Vec<u8>, Refusal> {
+        if packet.len() < 98 {
+            return Err(Refusal::MissingParameter("announce"));
+        }
+        let mut request = AnnounceRequest::new(
+            packet[16..36].try_into().unwrap(),
+            packet[36..56].try_into().unwrap(),
+            u16::from_be_bytes([packet[96], packet[97]]),
+        );
+        request.downloaded = read_u64(packet, 56);
+        request.left = read_u64(packet, 64);
+        request.uploaded = read_u64(packet, 72);
+        request.event = match read_u32(packet, 80) {
+            0 => None,
+            1 => Some(AnnounceEvent::Completed),
+            2 => Some(AnnounceEvent::Started),
+            3 => Some(AnnounceEvent::Stopped),
+            _ => return Err(Refusal::InvalidParameter("event")),
+        };
+        let numwant = read_u32(packet, 92) as i32;
+        request.numwant = u32::try_from(numwant).ok();
+
+        let path = url_data(&packet[98..]);
+        let passkey = passkey(&path)?;
+
+        let response = self.registry.announce(passkey, &request, ip)?;
+        let mut body = Vec::with_capacity(12 + 18 * response.peers.len());
+        body.extend_from_slice(&(response.interval.as_secs() as u32).to_be_bytes());
+        body.extend_from_slice(&response.incomplete.unwrap_or(0).to_be_bytes());
+        body.extend_from_slice(&response.complete.unwrap_or(0).to_be_bytes());
+        // Peers must be in the address family of the request
+        for peer in response.peers {
+            match (peer.addr.ip(), ip) {
+                (IpAddr::V4(peer_ip), IpAddr::V4(_)) => body.extend_from_slice(&peer_ip.octets()),
+                (IpAddr::V6(peer_ip), IpAddr::V6(_)) => body.extend_from_slice(&peer_ip.octets()),
+                _ => continue,
+            }
+            body.extend_from_slice(&peer.addr.port().to_be_bytes());
+        }
+        Ok(body)
+    }
+
+    fn scrape(&self, packet: &[u8]) -> Result<Vec<u8>, Refusal> {
+        let info_hashes: Vec<[u8; 20]> = packet[16..]
+            .chunks_exact(20)
+            .take(MAX_SCRAPE_HASHES)
+            .map(|info_hash| info_hash.try_into().unwrap())
+            .collect();
+        let stats = self.registry.scrape(None, &info_hashes)?;
+
+        // Answers are positional, so unknown torrents are reported as empty
+        let mut body = Vec::with_capacity(12 * info_hashes.len());
+        for info_hash in &info_hashes {
+            let stats = stats.get(info_hash).copied().unwrap_or_default();
+            body.extend_from_slice(&stats.complete.to_be_bytes());
+            body.extend_from_slice(&stats.downloaded.to_be_bytes());
+            body.extend_from_slice(&stats.incomplete.to_be_bytes());
+        }
+        Ok(body)
+    }
+
+    /// Connection IDs change every minute; `age` minutes back
+    fn connection_id(&self, ip: IpAddr, age: u64) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let minute = (now.as_secs() / 60).wrapping_sub(age);
+        self.secret.hash_one((ip, minute))
+    }
+
+    /// A connection ID stays valid for one to two minutes
+    fn is_valid_connection(&self, connection_id: u64, ip: IpAddr) -> bool {
+        (0..2).any(|age| self.connection_id(ip, age) == connection_id)
+    }
+}
+
+fn header(action: u32, transaction_id: u32) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(2048);
+    packet.extend_from_slice(&action.to_be_bytes());
+    packet.extend_from_slice(&transaction_id.to_be_bytes());
+    packet
+}
+
+fn error(transaction_id: u32, message: &str) -> Vec<u8> {
+    let mut packet = header(ACTION_ERROR, transaction_id);
+    packet.extend_from_slice(message.as_bytes());
+    packet
+}
+
+/// Joins the BEP 41 URLData options that follow an announce into the path
+/// and query of the tracker URL; malformed options end the list
+fn url_data(mut options: &[u8]) -> String {
+    let mut url = Vec::new();
+    loop {
+        match options {
+            [] | [OPTION_END, ..] => break,
+            [OPTION_NOP, rest @ ..] => options = rest,
+            [kind, len, rest @ ..] if rest.len() >= usize::from(*len) => {
+                let (data, rest) = rest.split_at(usize::from(*len));
+                if *kind == OPTION_URL_DATA {
+                    url.extend_from_slice(data);
+                }
+                options = rest;
+            }
+            _ => break,
+        }
+    }
+    String::from_utf8_lossy(&url).into_owned()
+}
+
+/// The passkey of a `/<passkey>/announce` path; `None` for `/announce` or no
+/// path at all
+fn passkey(url: &str) -> Result<Option<&str>, Refusal> {
+    let path = url.split_once('?').map_or(url, |(path, _)| path);
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+    match segments[..] {
+        [] | ["announce"] => Ok(None),
+        [passkey, "announce"] => Ok(Some(passkey)),
+        _ => Err(Refusal::InvalidParameter("path")),
+    }
+}
+
+fn read_u32(packet: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(packet[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(packet: &[u8], offset: usize) -> u64 {
+    u64::from_be_bytes(packet[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+    use tracker::{clients::udp::UdpTracker, enums::tracker_error::TrackerError};
+
+    fn serve(bind: &str, registry: SwarmRegistry) -> UdpTracker {
+        let server = UdpServer::bind(bind, Arc::new(registry)).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        UdpTracker::new(addr)
+            .timeout(Duration::from_millis(500))
+            .max_retries(2)
+    }
+
+    fn request(peer: u8, left: u64) -> AnnounceRequest {
+        let mut request = AnnounceRequest::new([7; 20], [peer; 20], 6881 + u16::from(peer));
+        request.left = left;
+        request
+    }
+
+    #[test]
+    fn test_announce_and_scrape_with_client() {
+        let mut tracker = serve("127.0.0.1:0", SwarmRegistry::new());
+
+        let response = tracker.announce(&request(1, 10)).unwrap();
+        assert_eq!(response.interval, Duration::from_secs(30 * 60));
+        assert!(response.peers.is_empty());
+
+        let mut seeder = request(2, 0);
+        seeder.event = Some(AnnounceEvent::Completed);
+        let response = tracker.announce(&seeder).unwrap();
+        assert_eq!((response.complete, response.incomplete), (Some(1), Some(1)));
+        let addrs: Vec<_> = response.peers.iter().map(|peer| peer.addr).collect();
+        assert_eq!(addrs, vec!["127.0.0.1:6882".parse().unwrap()]);
+
+        let stats = tracker.scrape(&[[7; 20], [8; 20]]).unwrap();
+        assert_eq!(stats[&[7; 20]].downloaded, 1);
+        assert_eq!(stats[&[8; 20]], Default::default());
+    }
+
+    #[test]
+    fn test_ipv6() {
+        let mut tracker = serve("[::1]:0", SwarmRegistry::new());
+        tracker.announce(&request(1, 10)).unwrap();
+        let response = tracker.announce(&request(2, 10)).unwrap();
+        let addrs: Vec<_> = response.peers.iter().map(|peer| peer.addr).collect();
+        assert_eq!(addrs, vec!["[::1]:6882".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_refusals() {
+        let mut tracker = serve("127.0.0.1:0", SwarmRegistry::new().whitelist([[1; 20]]));
+        assert_eq!(
+            tracker.announce(&request(1, 0)),
+            Err(TrackerError::Failure("unregistered torrent".to_string()))
+        );
+
+        let mut tracker = serve(
+            "127.0.0.1:0",
+            SwarmRegistry::new().passkeys(["k".to_string()]),
+        );
+        assert_eq!(
+            tracker.announce(&request(1, 0)),
+            Err(TrackerError::Failure("invalid passkey".to_string()))
+        );
+
+        // The client sends the passkey in the URL path
+        let mut tracker = tracker.url_data("/k/announce");
+        assert!(tracker.announce(&request(1, 0)).is_ok());
+    }
+
+    #[test]
+    fn test_connection_ids() {
+        let server = UdpServer::bind("127.0.0.1:0", Arc::new(SwarmRegistry::new())).unwrap();
+        let from: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+
+        let mut connect = PROTOCOL_ID.to_be_bytes().to_vec();
+        connect.extend_from_slice(&[0, 0, 0, 0, 1, 2, 3, 4]);
+        let response = server.handle(&connect, from).unwrap();
+        assert_eq!(&response[..8], [0, 0, 0, 0, 1, 2, 3, 4]);
+        let connection_id = &response[8..16];
+
+        let mut scrape = connection_id.to_vec();
+        scrape.extend_from_slice(&ACTION_SCRAPE.to_be_bytes());
+        scrape.extend_from_slice(&[5; 4]);
+        scrape.extend_from_slice(&[9; 20]);
+        assert_eq!(
+            read_u32(&server.handle(&scrape, from).unwrap(), 0),
+            ACTION_SCRAPE
+        );
+
+        // The ID is bound to the address that connected
+        let other: SocketAddr = "127.0.0.2:1000".parse().unwrap();
+        let response = server.handle(&scrape, other).unwrap();
+        assert_eq!(read_u32(&response, 0), ACTION_ERROR);
+        assert_eq!(&response[8..], b"invalid connection id");
+
+        assert_eq!(server.handle(&connect[..15], from), None);
+        connect[0] = 1;
+        assert_eq!(server.handle(&connect, from), None);
+    }
+
+    #[test]
+    fn test_passkeys_in_url_data() {
+        let server = UdpServer::bind(
+            "127.0.0.1:0",
+            Arc::new(SwarmRegistry::new().passkeys(["k1".to_string()])),
+        )
+        .unwrap();
+        let from: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+        let mut connect = PROTOCOL_ID.to_be_bytes().to_vec();
+        connect.extend_from_slice(&[0; 8]);
+        let connection_id = server.handle(&connect, from).unwrap()[8..16].to_vec();
+
+        let announce = |options: &[u8]| {
+            let mut packet = connection_id.clone();
+            packet.extend_from_slice(&ACTION_ANNOUNCE.to_be_bytes());
+            packet.extend_from_slice(&[0; 4]);
+            packet.extend_from_slice(&[7; 40]);
+            packet.extend_from_slice(&[0; 40]);
+            packet.extend_from_slice(&6881u16.to_be_bytes());
+            packet.extend_from_slice(options);
+            let response = server.handle(&packet, from).unwrap();
+            match read_u32(&response, 0) {
+                ACTION_ERROR => Err(String::from_utf8(response[8..].to_vec()).unwrap()),
+                _ => Ok(()),
+            }
+        };
+
+        // The path may be split over several options, with NOPs in between
+        assert_eq!(announce(b"\x02\x04/k1/\x01\x02\x08announce\x00"), Ok(()));
+        assert_eq!(
+            announce(b"\x02\x0c/k2/announce"),
+            Err("invalid passkey".to_string())
+        );
+        assert_eq!(announce(b""), Err("invalid passkey".to_string()));
+        assert_eq!(
+            announce(b"\x02\x0c/k1/announcx"),
+            Err("invalid parameter 'path'".to_string())
+        );
+        // A truncated option is ignored along with everything after it
+        assert_eq!(
+            announce(b"\x02\x20/k1/announce"),
+            Err("invalid passkey".to_string())
+        );
+    }
+}
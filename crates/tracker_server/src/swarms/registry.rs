@@ -0,0 +1,512 @@
+use super::swarm::Swarm;
+use crate::{enums::refusal::Refusal, models::peer_entry::PeerEntry};
+use bencode::{bencode, dispatcher::bencode::encode};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    net::{IpAddr, SocketAddr},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracker::{
+    enums::announce_event::AnnounceEvent,
+    models::{
+        announce_request::AnnounceRequest, announce_response::AnnounceResponse,
+        scrape_stats::ScrapeStats,
+    },
+    parsers::scrape_response::parse_scrape_response,
+};
+
+/// In-memory state of every swarm, shared by the HTTP and UDP servers
+#[derive(Debug)]
+pub struct SwarmRegistry {
+    swarms: Mutex<HashMap<[u8; 20], Swarm>>,
+    interval: Duration,
+    min_interval: Duration,
+    /// Half an interval past the announce interval when unset
+    peer_timeout: Option<Duration>,
+    default_numwant: u32,
+    max_numwant: u32,
+    max_swarms: usize,
+    max_peers_per_ip: usize,
+    whitelist: Option<HashSet<[u8; 20]>>,
+    passkeys: Option<HashSet<String>>,
+}
+
+impl Default for SwarmRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SwarmRegistry {
+    pub fn new() -> Self {
+        SwarmRegistry {
+            swarms: Mutex::new(HashMap::new()),
+            interval: Duration::from_secs(30 * 60),
+            min_interval: Duration::from_secs(60),
+            peer_timeout: None,
+            default_numwant: 50,
+            max_numwant: 200,
+            max_swarms: 100_000,
+            max_peers_per_ip: 16,
+            whitelist: None,
+            passkeys: None,
+        }
+    }
+
+    /// Announce interval sent to clients, 30 minutes by default
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Minimum announce interval sent to clients, 1 minute by default
+    pub fn min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Peers silent for this long are dropped by `expire_peers`; by default 1.5
+    /// announce intervals, 45 minutes with the default interval
+    pub fn peer_timeout(mut self, peer_timeout: Duration) -> Self {
+        self.peer_timeout = Some(peer_timeout);
+        self
+    }
+
+    /// Peers returned when the client sends no `numwant`, and the most it may
+    /// ask for; 50 and 200 by default
+    pub fn numwant(mut self, default_numwant: u32, max_numwant: u32) -> Self {
+        self.default_numwant = default_numwant;
+        self.max_numwant = max_numwant;
+        self
+    }
+
+    /// Most swarms tracked at once, and most peers one address may have in a
+    /// swarm; 100,000 and 16 by default. Announces past either are refused
+    pub fn limits(mut self, max_swarms: usize, max_peers_per_ip: usize) -> Self {
+        self.max_swarms = max_swarms;
+        self.max_peers_per_ip = max_peers_per_ip;
+        self
+    }
+
+    /// Only serves the given torrents; every torrent is served by default
+    pub fn whitelist(mut self, info_hashes: impl IntoIterator<Item = [u8; 20]>) -> Self {
+        self.whitelist = Some(info_hashes.into_iter().collect());
+        self
+    }
+
+    /// Requires one of the given passkeys in every request
+    pub fn passkeys(mut self, passkeys: impl IntoIterator<Item = String>) -> Self {
+        self.passkeys = Some(passkeys.into_iter().collect());
+        self
+    }
+
+    pub fn requires_passkey(&self) -> bool {
+        self.passkeys.is_some()
+    }
+
+    fn effective_peer_timeout(&self) -> Duration {
+        self.peer_timeout
+            .unwrap_or_else(|| self.interval.saturating_mul(3) / 2)
+    }
+
+    /// Records the announcing peer at `ip` and picks peers for it
+    pub fn announce(
+        &self,
+        passkey: Option<&str>,
+        request: &AnnounceRequest,
+        ip: IpAddr,
+    ) -> Result<AnnounceResponse, Refusal> {
+        self.check_passkey(passkey)?;
+        self.check_torrent(&request.info_hash)?;
+        if request.port == 0 {
+            return Err(Refusal::InvalidParameter("port"));
+        }
+
+        let mut swarms = self.swarms.lock().unwrap();
+        if !swarms.contains_key(&request.info_hash) && swarms.len() >= self.max_swarms {
+            return Err(Refusal::TooManyTorrents);
+        }
+        let swarm = swarms.entry(request.info_hash).or_default();
+        let key = (request.peer_id, ip);
+        let peers = if request.event == Some(AnnounceEvent::Stopped) {
+            swarm.peers.remove(&key);
+            Vec::new()
+        } else {
+            // Otherwise one host could fill the swarm by changing its peer ID
+            if !swarm.peers.contains_key(&key) && swarm.peers_from(ip) >= self.max_peers_per_ip {
+                return Err(Refusal::TooManyPeers);
+            }
+            if request.event == Some(AnnounceEvent::Completed) {
+                swarm.downloaded = swarm.downloaded.saturating_add(1);
+            }
+            swarm.peers.insert(
+                key,
+                PeerEntry {
+                    addr: SocketAddr::new(ip, request.port),
+                    left: request.left,
+                    last_seen: Instant::now(),
+                },
+            );
+            let numwant = request
+                .numwant
+                .unwrap_or(self.default_numwant)
+                .min(self.max_numwant);
+            swarm.select(&request.peer_id, request.left == 0, numwant as usize)
+        };
+        let stats = swarm.stats();
+
+        Ok(AnnounceResponse {
+            interval: self.interval,
+            min_interval: Some(self.min_interval),
+            tracker_id: None,
+            complete: Some(stats.complete),
+            incomplete: Some(stats.incomplete),
+            peers,
+            warning_message: None,
+            external_ip: Some(ip),
+        })
+    }
+
+    /// Counts for the given torrents; torrents without a swarm are left out
+    pub fn scrape(
+        &self,
+        passkey: Option<&str>,
+        info_hashes: &[[u8; 20]],
+    ) -> Result<BTreeMap<[u8; 20], ScrapeStats>, Refusal> {
+        self.check_passkey(passkey)?;
+        let swarms = self.swarms.lock().unwrap();
+        Ok(info_hashes
+            .iter()
+            .filter(|info_hash| self.check_torrent(info_hash).is_ok())
+            .filter_map(|info_hash| Some((*info_hash, swarms.get(info_hash)?.stats())))
+            .collect())
+    }
+
+    /// Drops peers silent for longer than the peer timeout, and swarms left
+    /// with neither peers nor history; returns how many peers were dropped
+    pub fn expire_peers(&self) -> usize {
+        let now = Instant::now();
+        let peer_timeout = self.effective_peer_timeout();
+        let mut swarms = self.swarms.lock().unwrap();
+        let mut expired = 0;
+        swarms.retain(|_, swarm| {
+            expired += swarm.expire(now, peer_timeout);
+            !swarm.peers.is_empty() || swarm.downloaded > 0
+        });
+        expired
+    }
+
+    /// Writes every swarm's counts to `path` in the bencoded scrape format
+    pub fn save_stats(&self, path: &Path) -> io::Result<()> {
+        let files: BTreeMap<Vec<u8>, _> = self
+            .swarms
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(info_hash, swarm)| {
+                let stats = swarm.stats();
+                let stats = bencode!({
+                    "complete": (i64::from(stats.complete)),
+                    "incomplete": (i64::from(stats.incomplete)),
+                    "downloaded": (i64::from(stats.downloaded)),
+                });
+                (info_hash.to_vec(), stats)
+            })
+            .collect();
+        let data = encode(&bencode!({ "files": files }))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        // Write then rename, so a crash never leaves a truncated file behind
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, data)?;
+        fs::rename(temporary, path)
+    }
+
+    /// Restores the `downloaded` counts saved by `save_stats`; peers are not
+    /// restored, they come back with their next announce
+    pub fn load_stats(&self, path: &Path) -> io::Result<()> {
+        let data = fs::read(path)?;
+        let stats = parse_scrape_response(&data)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut swarms = self.swarms.lock().unwrap();
+        for (info_hash, stats) in stats {
+            swarms.entry(info_hash).or_default().downloaded = stats.downloaded;
+        }
+        Ok(())
+    }
+
+    fn check_passkey(&self, passkey: Option<&str>) -> Result<(), Refusal> {
+        match (&self.passkeys, passkey) {
+            (None, _) => Ok(()),
+            (Some(passkeys), Some(passkey)) if passkeys.contains(passkey) => Ok(()),
+            (Some(_), _) => Err(Refusal::InvalidPasskey),
+        }
+    }
+
+    fn check_torrent(&self, info_hash: &[u8; 20]) -> Result<(), Refusal> {
+        match &self.whitelist {
+            Some(whitelist) if !whitelist.contains(info_hash) => Err(Refusal::UnknownTorrent),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announce(peer: u8, left: u64, event: Option<AnnounceEvent>) -> AnnounceRequest {
+        let mut request = AnnounceRequest::new([1; 20], [peer; 20], 6881);
+        request.left = left;
+        request.event = event;
+        request
+    }
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::from([10, 0, 0, last])
+    }
+
+    #[test]
+    fn test_announce_and_scrape() {
+        let registry = SwarmRegistry::new();
+        let response = registry
+            .announce(None, &announce(1, 100, Some(AnnounceEvent::Started)), ip(1))
+            .unwrap();
+        assert!(response.peers.is_empty());
+        assert_eq!(response.incomplete, Some(1));
+
+        let response = registry
+            .announce(None, &announce(2, 0, None), ip(2))
+            .unwrap();
+        assert_eq!(response.complete, Some(1));
+        assert_eq!(response.peers.len(), 1);
+        assert_eq!(response.peers[0].addr, "10.0.0.1:6881".parse().unwrap());
+        assert_eq!(response.peers[0].peer_id, Some([1; 20]));
+
+        // A finished leecher turns into a seeder
+        registry
+            .announce(None, &announce(1, 0, Some(AnnounceEvent::Completed)), ip(1))
+            .unwrap();
+        assert_eq!(
+            registry.scrape(None, &[[1; 20], [2; 20]]),
+            Ok(BTreeMap::from([(
+                [1; 20],
+                ScrapeStats {
+                    complete: 2,
+                    incomplete: 0,
+                    downloaded: 1,
+                }
+            )]))
+        );
+
+        let response = registry
+            .announce(None, &announce(2, 0, Some(AnnounceEvent::Stopped)), ip(2))
+            .unwrap();
+        assert!(response.peers.is_empty());
+        assert_eq!(response.complete, Some(1));
+    }
+
+    #[test]
+    fn test_peer_selection() {
+        let registry = SwarmRegistry::new().numwant(3, 5);
+        for peer in 1..=10 {
+            registry
+                .announce(None, &announce(peer, u64::from(peer % 2), None), ip(peer))
+                .unwrap();
+        }
+
+        let leecher = announce(11, 1, None);
+        assert_eq!(
+            registry
+                .announce(None, &leecher, ip(11))
+                .unwrap()
+                .peers
+                .len(),
+            3
+        );
+        let mut greedy = leecher.clone();
+        greedy.numwant = Some(100);
+        let peers = registry.announce(None, &greedy, ip(11)).unwrap().peers;
+        assert_eq!(peers.len(), 5);
+        assert!(peers.iter().all(|peer| peer.peer_id != Some([11; 20])));
+
+        // Seeders only get leechers: peers 1, 3, 5, 7, 9 and 11
+        let mut seeder = announce(12, 0, None);
+        seeder.numwant = Some(100);
+        let peers = registry.announce(None, &seeder, ip(12)).unwrap().peers;
+        assert_eq!(peers.len(), 5);
+        assert!(peers.iter().all(|peer| peer.peer_id.unwrap()[0] % 2 == 1));
+    }
+
+    #[test]
+    fn test_peer_ids_are_bound_to_addresses() {
+        let registry = SwarmRegistry::new();
+        registry
+            .announce(None, &announce(1, 100, None), ip(1))
+            .unwrap();
+
+        // Another host sending the same peer ID neither stops nor moves the peer
+        registry
+            .announce(None, &announce(1, 0, Some(AnnounceEvent::Stopped)), ip(9))
+            .unwrap();
+        let mut hijack = announce(1, 100, None);
+        hijack.port = 9999;
+        registry.announce(None, &hijack, ip(9)).unwrap();
+
+        let peers = registry
+            .announce(None, &announce(2, 100, None), ip(2))
+            .unwrap()
+            .peers;
+        let mut addrs: Vec<SocketAddr> = peers.iter().map(|peer| peer.addr).collect();
+        addrs.sort();
+        assert_eq!(
+            addrs,
+            vec![
+                "10.0.0.1:6881".parse().unwrap(),
+                "10.0.0.9:9999".parse().unwrap()
+            ]
+        );
+
+        registry
+            .announce(None, &announce(1, 0, Some(AnnounceEvent::Stopped)), ip(1))
+            .unwrap();
+        let peers = registry
+            .announce(None, &announce(2, 100, None), ip(2))
+            .unwrap()
+            .peers;
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].addr, "10.0.0.9:9999".parse().unwrap());
+    }
+
+    #[test]
+    fn test_whitelist_and_passkeys() {
+        let registry = SwarmRegistry::new()
+            .whitelist([[1; 20]])
+            .passkeys(["secret".to_string()]);
+        let request = announce(1, 0, None);
+
+        assert_eq!(
+            registry.announce(None, &request, ip(1)),
+            Err(Refusal::InvalidPasskey)
+        );
+        assert_eq!(
+            registry.announce(Some("guess"), &request, ip(1)),
+            Err(Refusal::InvalidPasskey)
+        );
+        assert!(registry.announce(Some("secret"), &request, ip(1)).is_ok());
+
+        let mut unknown = request.clone();
+        unknown.info_hash = [2; 20];
+        assert_eq!(
+            registry.announce(Some("secret"), &unknown, ip(1)),
+            Err(Refusal::UnknownTorrent)
+        );
+        assert_eq!(
+            registry.scrape(Some("secret"), &[[2; 20]]),
+            Ok(BTreeMap::new())
+        );
+        assert_eq!(
+            registry.scrape(None, &[[1; 20]]),
+            Err(Refusal::InvalidPasskey)
+        );
+    }
+
+    #[test]
+    fn test_expiry() {
+        let registry = SwarmRegistry::new().peer_timeout(Duration::ZERO);
+        registry
+            .announce(None, &announce(1, 1, None), ip(1))
+            .unwrap();
+        registry
+            .announce(None, &announce(2, 0, Some(AnnounceEvent::Completed)), ip(2))
+            .unwrap();
+
+        assert_eq!(registry.expire_peers(), 2);
+        // The swarm stays for its download count
+        assert_eq!(
+            registry.scrape(None, &[[1; 20]]).unwrap()[&[1; 20]],
+            ScrapeStats {
+                complete: 0,
+                incomplete: 0,
+                downloaded: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let registry = SwarmRegistry::new().limits(2, 2);
+        for peer in 1..=2 {
+            registry
+                .announce(None, &announce(peer, 1, None), ip(1))
+                .unwrap();
+        }
+        assert_eq!(
+            registry.announce(None, &announce(3, 1, None), ip(1)),
+            Err(Refusal::TooManyPeers)
+        );
+        // Known peers and other addresses are unaffected
+        registry
+            .announce(None, &announce(2, 0, None), ip(1))
+            .unwrap();
+        registry
+            .announce(None, &announce(3, 1, None), ip(2))
+            .unwrap();
+
+        let mut other = announce(1, 1, None);
+        other.info_hash = [2; 20];
+        registry.announce(None, &other, ip(1)).unwrap();
+        other.info_hash = [3; 20];
+        assert_eq!(
+            registry.announce(None, &other, ip(1)),
+            Err(Refusal::TooManyTorrents)
+        );
+    }
+
+    #[test]
+    fn test_peer_timeout_follows_interval() {
+        let registry = SwarmRegistry::new();
+        assert_eq!(
+            registry.effective_peer_timeout(),
+            Duration::from_secs(45 * 60)
+        );
+        let registry = registry.interval(Duration::from_secs(3600));
+        assert_eq!(
+            registry.effective_peer_timeout(),
+            Duration::from_secs(90 * 60)
+        );
+        let registry = registry.peer_timeout(Duration::from_secs(10));
+        assert_eq!(registry.effective_peer_timeout(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_stats_persistence() {
+        let path = std::env::temp_dir().join(format!("swarm-stats-{}", std::process::id()));
+        let registry = SwarmRegistry::new();
+        for peer in 1..=3 {
+            registry
+                .announce(
+                    None,
+                    &announce(peer, 0, Some(AnnounceEvent::Completed)),
+                    ip(peer),
+                )
+                .unwrap();
+        }
+        registry.save_stats(&path).unwrap();
+
+        let restored = SwarmRegistry::new();
+        restored.load_stats(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            restored.scrape(None, &[[1; 20]]).unwrap()[&[1; 20]],
+            ScrapeStats {
+                complete: 0,
+                incomplete: 0,
+                downloaded: 3,
+            }
+        );
+    }
+}
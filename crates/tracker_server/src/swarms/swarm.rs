@@ -0,0 +1,57 @@
+use crate::models::peer_entry::PeerEntry;
+use rand::seq::IteratorRandom;
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+use tracker::models::{peer::Peer, scrape_stats::ScrapeStats};
+
+/// Peers of one torrent, keyed by peer ID and the address announcing it, so a
+/// client cannot take over or stop another's entry by sending its peer ID
+#[derive(Debug, Default, Clone)]
+pub struct Swarm {
+    pub peers: HashMap<([u8; 20], IpAddr), PeerEntry>,
+    /// `completed` events seen over the swarm's lifetime
+    pub downloaded: u32,
+}
+
+impl Swarm {
+    pub fn stats(&self) -> ScrapeStats {
+        let complete = self.peers.values().filter(|peer| peer.is_seeder()).count();
+        ScrapeStats {
+            complete: complete as u32,
+            incomplete: (self.peers.len() - complete) as u32,
+            downloaded: self.downloaded,
+        }
+    }
+
+    /// How many peers announce from `ip`
+    pub fn peers_from(&self, ip: IpAddr) -> usize {
+        self.peers
+            .keys()
+            .filter(|(_, peer_ip)| *peer_ip == ip)
+            .count()
+    }
+
+    /// Up to `count` random peers other than `peer_id`; seeders only get
+    /// leechers, as other seeders have nothing to offer them
+    pub fn select(&self, peer_id: &[u8; 20], seeder: bool, count: usize) -> Vec<Peer> {
+        self.peers
+            .iter()
+            .filter(|((id, _), peer)| id != peer_id && !(seeder && peer.is_seeder()))
+            .map(|((id, _), peer)| Peer {
+                addr: peer.addr,
+                peer_id: Some(*id),
+            })
+            .choose_multiple(&mut rand::rng(), count)
+    }
+
+    /// Drops peers not seen within `timeout`, returning how many were dropped
+    pub fn expire(&mut self, now: Instant, timeout: Duration) -> usize {
+        let before = self.peers.len();
+        self.peers
+            .retain(|_, peer| now.duration_since(peer.last_seen) < timeout);
+        before - self.peers.len()
+    }
+}
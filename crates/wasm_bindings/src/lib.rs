@@ -13,7 +13,7 @@ fn js_to_bencode(value: &JsValue, decode_utf: Option<bool>) -> Result<BencodeVal
 
     // Number
     if let Some(n) = value.as_f64() {
-        return Ok(BencodeValue::Int(n as isize));
+        return Ok(BencodeValue::Int(n as i64));
     }
 
     // String
@@ -124,7 +124,7 @@ fn bencode_to_js(value: BencodeValue, decode_utf: Option<bool>) -> JsValue {
 #[wasm_bindgen]
 pub fn bencode(value: JsValue, decode_utf: Option<bool>) -> Result<Vec<u8>, JsValue> {
     let tokens = js_to_bencode(&value, decode_utf)?;
-    let bytes = bencode::dispatcher::bencode::encode_bencode(tokens)
+    let bytes = bencode::dispatcher::bencode::encode(&tokens)
         .map_err(|_| JsValue::from_str("encode error"))?;
     Ok(bytes)
 }
@@ -135,3 +135,81 @@ pub fn bdecode(bytes: &[u8], decode_utf: Option<bool>) -> Result<JsValue, JsValu
         bencode::dispatcher::bdecode::decode_bencode(bytes).map_err(JsValue::from_str)?;
     Ok(bencode_to_js(tokens, decode_utf))
 }
+
+fn info_hash_to_js(hash: &metainfo::enums::info_hash::InfoHash) -> Result<JsValue, JsValue> {
+    let to_js = |hash: Option<String>| hash.map_or(JsValue::NULL, |h| JsValue::from_str(&h));
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(
+        &obj,
+        &JsValue::from_str("v1"),
+        &to_js(hash.v1().map(|v1| metainfo::encoding::hex::encode_hex(&v1))),
+    )?;
+    js_sys::Reflect::set(
+        &obj,
+        &JsValue::from_str("v2"),
+        &to_js(hash.v2().map(|v2| metainfo::encoding::hex::encode_hex(&v2))),
+    )?;
+    Ok(obj.into())
+}
+
+fn strings_to_js(strings: &[String]) -> JsValue {
+    strings
+        .iter()
+        .map(|s| JsValue::from_str(s))
+        .collect::<js_sys::Array>()
+        .into()
+}
+
+#[wasm_bindgen]
+pub fn info_hash(bytes: &[u8]) -> Result<JsValue, JsValue> {
+    let hash = metainfo::hashing::info_hash::info_hash(bytes)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    info_hash_to_js(&hash)
+}
+
+#[wasm_bindgen]
+pub fn parse_magnet(uri: &str) -> Result<JsValue, JsValue> {
+    let magnet = metainfo::parsers::magnet::parse_magnet(uri)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let select_only: js_sys::Array = magnet
+        .select_only
+        .iter()
+        .map(|range| {
+            js_sys::Array::of2(
+                &JsValue::from_f64(*range.start() as f64),
+                &JsValue::from_f64(*range.end() as f64),
+            )
+        })
+        .collect();
+    let extra: js_sys::Array = magnet
+        .extra
+        .iter()
+        .map(|(key, value)| js_sys::Array::of2(&JsValue::from_str(key), &JsValue::from_str(value)))
+        .collect();
+
+    let obj = js_sys::Object::new();
+    let set =
+        |key: &str, value: &JsValue| js_sys::Reflect::set(&obj, &JsValue::from_str(key), value);
+    set("info_hash", &info_hash_to_js(&magnet.info_hash)?)?;
+    set(
+        "name",
+        &magnet
+            .display_name
+            .as_deref()
+            .map_or(JsValue::NULL, JsValue::from_str),
+    )?;
+    set("trackers", &strings_to_js(&magnet.trackers))?;
+    set("web_seeds", &strings_to_js(&magnet.web_seeds))?;
+    set("peers", &strings_to_js(&magnet.peers))?;
+    set("select_only", &select_only)?;
+    set("extra", &extra)?;
+    Ok(obj.into())
+}
+
+#[wasm_bindgen]
+pub fn magnet_from_torrent(bytes: &[u8]) -> Result<String, JsValue> {
+    let metainfo = metainfo::parsers::metainfo::decode_metainfo(bytes)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(metainfo::models::magnet::MagnetLink::from_metainfo(&metainfo).to_uri())
+}
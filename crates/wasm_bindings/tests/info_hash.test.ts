@@ -0,0 +1,32 @@
+import { describe, it, expect } from 'vitest';
+import { createHash } from 'node:crypto';
+import { info_hash } from 'bencode_wasm';
+
+const encoder = new TextEncoder();
+
+function torrentWithInfo(info: Uint8Array): Uint8Array {
+    const head = encoder.encode('d4:info');
+    const torrent = new Uint8Array(head.length + info.length + 1);
+    torrent.set(head);
+    torrent.set(info, head.length);
+    torrent[torrent.length - 1] = 'e'.charCodeAt(0);
+    return torrent;
+}
+
+describe('INFO HASH', () => {
+    it('v1 info hash', () => {
+        const info = encoder.encode('d6:lengthi1e4:name1:a12:piece lengthi1e6:pieces0:e');
+        const expected = createHash('sha1').update(info).digest('hex');
+        expect(info_hash(torrentWithInfo(info))).toEqual({ v1: expected, v2: null });
+    });
+
+    it('v2 info hash', () => {
+        const info = encoder.encode('d12:meta versioni2e4:name1:ae');
+        const expected = createHash('sha256').update(info).digest('hex');
+        expect(info_hash(torrentWithInfo(info))).toEqual({ v1: null, v2: expected });
+    });
+
+    it('missing info dictionary', () => {
+        expect(() => info_hash(encoder.encode('de'))).toThrow();
+    });
+});
@@ -0,0 +1,37 @@
+import { describe, it, expect } from 'vitest';
+import { info_hash, magnet_from_torrent, parse_magnet } from 'bencode_wasm';
+
+const encoder = new TextEncoder();
+const V1 = 'c12fe1c06bba254a9dc9f519b335aa7c1367a88a';
+
+describe('MAGNET', () => {
+    it('parses a magnet link', () => {
+        const magnet = parse_magnet(
+            `magnet:?xt=urn:btih:${V1}&dn=Big+Buck+Bunny&tr=udp://a.example:1337&so=0,2-3&x.custom=1`,
+        );
+        expect(magnet).toEqual({
+            info_hash: { v1: V1, v2: null },
+            name: 'Big Buck Bunny',
+            trackers: ['udp://a.example:1337'],
+            web_seeds: [],
+            peers: [],
+            select_only: [
+                [0, 0],
+                [2, 3],
+            ],
+            extra: [['x.custom', '1']],
+        });
+    });
+
+    it('builds a magnet link from a torrent', () => {
+        const torrent = encoder.encode(
+            'd8:announce14:http://tracker4:infod6:lengthi1e4:name1:a12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee',
+        );
+        const v1 = info_hash(torrent).v1;
+        expect(magnet_from_torrent(torrent)).toBe(`magnet:?xt=urn:btih:${v1}&dn=a&tr=http://tracker`);
+    });
+
+    it('rejects a magnet link without an info-hash', () => {
+        expect(() => parse_magnet('magnet:?dn=no-hash')).toThrow();
+    });
+});